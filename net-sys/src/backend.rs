@@ -0,0 +1,303 @@
+use std::fmt::Debug;
+use std::thread;
+
+use crate::error::{ErrorKind, Operation};
+use crate::event::NicEvent;
+use crate::ifname::IfName;
+use crate::info::NicInfo;
+use crate::lladdr::LinkLevelAddress;
+use crate::retry::Retry;
+use crate::Result;
+
+#[cfg(feature = "libc")]
+use crate::nic;
+
+#[derive(Clone, PartialEq, Eq)]
+enum Error {
+    LinkLevelAddressNotApplied(IfName, LinkLevelAddress, LinkLevelAddress),
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::LinkLevelAddressNotApplied(ifname, requested, actual) => f
+                .debug_struct("NicBackend::LinkLevelAddressNotAppliedError")
+                .field("ifname", ifname)
+                .field("requested", requested)
+                .field("actual", actual)
+                .finish(),
+        }
+    }
+}
+
+impl From<Error> for crate::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::LinkLevelAddressNotApplied(ifname, lladdr, _) => {
+                crate::Error::detailed(Operation::SetLinkLevelAddress, None, &error)
+                    .with_ifname(ifname)
+                    .with_lladdr(lladdr)
+                    .with_kind(ErrorKind::NotApplied)
+            }
+        }
+    }
+}
+
+/// Link events of `NicBackend::monitor`, which `debounce` reads on a thread
+/// of their own.
+pub type Monitor = Box<dyn Iterator<Item = Result<NicEvent>> + Send>;
+
+/// Reads and changes network interfaces, the kernel's with `OsBackend`, or
+/// those of anything standing in for it, e.g. a fake in tests or another
+/// network namespace.
+pub trait NicBackend {
+    fn get_lladdr(&self, ifname: &IfName) -> Result<LinkLevelAddress>;
+
+    fn set_lladdr(&self, ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()>;
+
+    fn list(&self) -> Result<Vec<NicInfo>>;
+
+    fn monitor(&self) -> Result<Monitor>;
+
+    /// Permanent address burnt into the device, `None` if it has none, like
+    /// virtual interfaces, or if the system does not tell.
+    fn get_perm_lladdr(&self, _ifname: &IfName) -> Result<Option<LinkLevelAddress>> {
+        Ok(None)
+    }
+
+    /// Sets the address and reads it back, as some drivers report success
+    /// but keep their address, trying again as `retry` allows. Fails with
+    /// `NicBackend::LinkLevelAddressNotAppliedError`, of kind `NotApplied`,
+    /// if it never sticks.
+    fn set_lladdr_verified(
+        &self,
+        ifname: &IfName,
+        lladdr: &LinkLevelAddress,
+        retry: &Retry,
+    ) -> Result<()> {
+        let mut delays = retry.delays();
+        loop {
+            self.set_lladdr(ifname, lladdr)?;
+
+            let actual = self.get_lladdr(ifname)?;
+            if actual == *lladdr {
+                return Ok(());
+            }
+
+            match delays.next() {
+                Some(delay) => {
+                    log::debug!(
+                        "{ifname}: {actual} kept instead of {lladdr}, retrying in {delay:?}"
+                    );
+                    thread::sleep(delay);
+                }
+                None => {
+                    return Err(Error::LinkLevelAddressNotApplied(*ifname, *lladdr, actual).into())
+                }
+            }
+        }
+    }
+}
+
+impl<B: NicBackend + ?Sized> NicBackend for Box<B> {
+    fn get_lladdr(&self, ifname: &IfName) -> Result<LinkLevelAddress> {
+        (**self).get_lladdr(ifname)
+    }
+
+    fn set_lladdr(&self, ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()> {
+        (**self).set_lladdr(ifname, lladdr)
+    }
+
+    fn list(&self) -> Result<Vec<NicInfo>> {
+        (**self).list()
+    }
+
+    fn monitor(&self) -> Result<Monitor> {
+        (**self).monitor()
+    }
+
+    fn get_perm_lladdr(&self, ifname: &IfName) -> Result<Option<LinkLevelAddress>> {
+        (**self).get_perm_lladdr(ifname)
+    }
+
+    fn set_lladdr_verified(
+        &self,
+        ifname: &IfName,
+        lladdr: &LinkLevelAddress,
+        retry: &Retry,
+    ) -> Result<()> {
+        (**self).set_lladdr_verified(ifname, lladdr, retry)
+    }
+}
+
+/// The kernel's interfaces, through the `nic` functions.
+#[cfg(feature = "libc")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OsBackend;
+
+#[cfg(feature = "libc")]
+impl NicBackend for OsBackend {
+    fn get_lladdr(&self, ifname: &IfName) -> Result<LinkLevelAddress> {
+        nic::get_lladdr(ifname)
+    }
+
+    fn set_lladdr(&self, ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()> {
+        nic::set_lladdr(ifname, lladdr)
+    }
+
+    fn list(&self) -> Result<Vec<NicInfo>> {
+        nic::list()
+    }
+
+    fn monitor(&self) -> Result<Monitor> {
+        Ok(Box::new(nic::monitor()?))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn get_perm_lladdr(&self, ifname: &IfName) -> Result<Option<LinkLevelAddress>> {
+        nic::get_perm_lladdr(ifname)
+    }
+}
+
+/// Like `OsBackend`, but setting addresses with RTM_SETLINK, which reports
+/// the errno of the kernel ACK.
+#[cfg(all(feature = "libc", target_os = "linux"))]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NetlinkBackend;
+
+#[cfg(all(feature = "libc", target_os = "linux"))]
+impl NicBackend for NetlinkBackend {
+    fn get_lladdr(&self, ifname: &IfName) -> Result<LinkLevelAddress> {
+        nic::get_lladdr(ifname)
+    }
+
+    fn set_lladdr(&self, ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()> {
+        nic::set_lladdr_netlink(ifname, lladdr)
+    }
+
+    fn list(&self) -> Result<Vec<NicInfo>> {
+        nic::list()
+    }
+
+    fn monitor(&self) -> Result<Monitor> {
+        Ok(Box::new(nic::monitor()?))
+    }
+
+    fn get_perm_lladdr(&self, ifname: &IfName) -> Result<Option<LinkLevelAddress>> {
+        nic::get_perm_lladdr(ifname)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::{Cell, RefCell};
+    use std::sync::LazyLock;
+    use std::time::Duration;
+
+    use crate::error::ErrorKind;
+    use crate::event::NicEvent::NicNew;
+    use crate::ifname::IfName;
+    use crate::info::NicInfo;
+    use crate::lladdr::LinkLevelAddress;
+    use crate::retry::Retry;
+    use crate::Result;
+
+    use super::{Monitor, NicBackend};
+
+    static IFNAME: LazyLock<IfName> = LazyLock::new(|| "enx".try_into().unwrap());
+    static LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "00:11:22:33:44:55".parse().unwrap());
+    static NEW_LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "02:11:22:33:44:55".parse().unwrap());
+
+    const RETRY: Retry = Retry {
+        attempts: 3,
+        backoff: Duration::ZERO,
+    };
+
+    /// Single interface whose driver ignores the first `ignored` changes.
+    #[derive(Debug)]
+    struct Driver {
+        lladdr: RefCell<LinkLevelAddress>,
+        ignored: Cell<u32>,
+        sets: Cell<u32>,
+    }
+
+    impl Driver {
+        fn ignoring(ignored: u32) -> Self {
+            Self {
+                lladdr: RefCell::new(*LLADDR),
+                ignored: Cell::new(ignored),
+                sets: Cell::new(0),
+            }
+        }
+    }
+
+    impl NicBackend for Driver {
+        fn get_lladdr(&self, _ifname: &IfName) -> Result<LinkLevelAddress> {
+            Ok(*self.lladdr.borrow())
+        }
+
+        fn set_lladdr(&self, _ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()> {
+            self.sets.set(self.sets.get() + 1);
+            match self.ignored.get() {
+                0 => *self.lladdr.borrow_mut() = *lladdr,
+                ignored => self.ignored.set(ignored - 1),
+            }
+            Ok(())
+        }
+
+        fn list(&self) -> Result<Vec<NicInfo>> {
+            Ok(Vec::new())
+        }
+
+        fn monitor(&self) -> Result<Monitor> {
+            let lladdr = *self.lladdr.borrow();
+            Ok(Box::new(
+                vec![NicNew((7, *IFNAME, lladdr))].into_iter().map(Ok),
+            ))
+        }
+    }
+
+    #[test]
+    fn test_backend_set_lladdr_verified_retries() -> Result<()> {
+        let driver = Driver::ignoring(2);
+
+        driver.set_lladdr_verified(&IFNAME, &NEW_LLADDR, &RETRY)?;
+
+        assert_eq!(driver.get_lladdr(&IFNAME)?, *NEW_LLADDR);
+        assert_eq!(driver.sets.get(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_backend_set_lladdr_verified_not_applied() {
+        let expected_error = "NicBackend::LinkLevelAddressNotAppliedError { ifname: \"enx\", requested: \"02:11:22:33:44:55\", actual: \"00:11:22:33:44:55\" }";
+        let driver = Driver::ignoring(3);
+
+        let error = driver
+            .set_lladdr_verified(&IFNAME, &NEW_LLADDR, &RETRY)
+            .unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(error.kind(), ErrorKind::NotApplied);
+        assert_eq!(driver.sets.get(), 3);
+    }
+
+    #[test]
+    fn test_backend_boxed() -> Result<()> {
+        let backend: Box<dyn NicBackend> = Box::new(Driver::ignoring(0));
+
+        backend.set_lladdr_verified(&IFNAME, &NEW_LLADDR, &RETRY)?;
+
+        assert_eq!(backend.get_lladdr(&IFNAME)?, *NEW_LLADDR);
+        assert!(backend.list()?.is_empty());
+        assert_eq!(
+            backend.monitor()?.collect::<Result<Vec<_>>>()?,
+            vec![NicNew((7, *IFNAME, *NEW_LLADDR))]
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,480 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::event::NicEvent;
+use crate::ifname::IfName;
+use crate::Result;
+
+/// Source of the current time, replaced in tests.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+#[derive(Debug)]
+struct Pending {
+    event: NicEvent,
+    deadline: Instant,
+}
+
+/// What an event tells of an interface, coalesced apart from the other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Topic {
+    /// `NicNew` or `NicDel`.
+    Presence,
+    /// `NicUp` or `NicDown`.
+    Link,
+}
+
+/// Holds the events of an interface back until it stayed quiet for `quiet`,
+/// then gives its final state only, e.g. a single `NicNew` for a
+/// `NicNew`, `NicDel`, `NicNew` burst. Its presence and its link are
+/// coalesced apart, the link following the presence, and dropped with a
+/// `NicDel`. An event repeating the last one given for the same interface
+/// is dropped, and so is `NicNoop`.
+#[derive(Debug)]
+pub struct Coalescer<C: Clock = SystemClock> {
+    quiet: Duration,
+    clock: C,
+    /// In deadline order, at most one per interface and topic.
+    pending: Vec<Pending>,
+    given: HashMap<(IfName, Topic), NicEvent>,
+}
+
+impl Coalescer {
+    pub fn new(quiet: Duration) -> Self {
+        Self::with_clock(quiet, SystemClock)
+    }
+}
+
+impl<C: Clock> Coalescer<C> {
+    pub fn with_clock(quiet: Duration, clock: C) -> Self {
+        Self {
+            quiet,
+            clock,
+            pending: Vec::new(),
+            given: HashMap::new(),
+        }
+    }
+
+    /// Replaces the event pending for the same interface and topic, if any,
+    /// and starts its quiet period over.
+    pub fn push(&mut self, event: NicEvent) {
+        let Some((name, topic)) = key(&event) else {
+            return;
+        };
+
+        let link = match topic {
+            Topic::Presence => self
+                .pending
+                .iter()
+                .position(|pending| key(&pending.event) == Some((name, Topic::Link)))
+                .map(|at| self.pending.remove(at)),
+            Topic::Link => None,
+        };
+        let gone = matches!(event, NicEvent::NicDel(_));
+
+        let deadline = self.clock.now() + self.quiet;
+        self.pending
+            .retain(|pending| key(&pending.event) != Some((name, topic)));
+        self.pending.push(Pending { event, deadline });
+        if let Some(link) = link.filter(|_| !gone) {
+            self.pending.push(Pending {
+                event: link.event,
+                deadline,
+            });
+        }
+    }
+
+    /// Next event whose quiet period is over.
+    pub fn pop(&mut self) -> Option<NicEvent> {
+        let now = self.clock.now();
+        while self.pending.first()?.deadline <= now {
+            let pending = self.pending.remove(0);
+            if let Some(event) = self.give(pending.event) {
+                return Some(event);
+            }
+        }
+
+        None
+    }
+
+    /// Next pending event, quiet period over or not.
+    pub fn flush(&mut self) -> Option<NicEvent> {
+        while !self.pending.is_empty() {
+            let pending = self.pending.remove(0);
+            if let Some(event) = self.give(pending.event) {
+                return Some(event);
+            }
+        }
+
+        None
+    }
+
+    /// Time left until the next quiet period is over, `None` if nothing is
+    /// pending.
+    pub fn timeout(&self) -> Option<Duration> {
+        let pending = self.pending.first()?;
+        Some(pending.deadline.saturating_duration_since(self.clock.now()))
+    }
+
+    fn give(&mut self, event: NicEvent) -> Option<NicEvent> {
+        let (name, topic) = key(&event)?;
+        // A link coming back starts over from the state it comes with.
+        if let NicEvent::NicDel(_) = event {
+            self.given.remove(&(name, Topic::Link));
+        }
+        match self.given.insert((name, topic), event.clone()) {
+            Some(given) if given == event => None,
+            _ => Some(event),
+        }
+    }
+}
+
+fn key(event: &NicEvent) -> Option<(IfName, Topic)> {
+    match event {
+        NicEvent::NicNew((_, ifname, _)) | NicEvent::NicDel((_, ifname, _)) => {
+            Some((*ifname, Topic::Presence))
+        }
+        NicEvent::NicUp((_, ifname)) | NicEvent::NicDown((_, ifname)) => {
+            Some((*ifname, Topic::Link))
+        }
+        NicEvent::NicNoop => None,
+    }
+}
+
+/// Coalesces the events of `events`, e.g. `nic::monitor()`, read on a
+/// thread of their own.
+pub fn debounce<I>(events: I, quiet: Duration) -> Debounced
+where
+    I: Iterator<Item = Result<NicEvent>> + Send + 'static,
+{
+    Debounced::with_coalescer(events, Coalescer::new(quiet))
+}
+
+/// Iterator of `debounce`. An error of the underlying iterator is passed
+/// on as soon as it is read.
+#[derive(Debug)]
+pub struct Debounced<C: Clock = SystemClock> {
+    receiver: Receiver<Result<NicEvent>>,
+    coalescer: Coalescer<C>,
+    done: bool,
+}
+
+impl<C: Clock> Debounced<C> {
+    pub fn with_coalescer<I>(events: I, coalescer: Coalescer<C>) -> Self
+    where
+        I: Iterator<Item = Result<NicEvent>> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for event in events {
+                if sender.send(event).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            receiver,
+            coalescer,
+            done: false,
+        }
+    }
+}
+
+impl<C: Clock> Iterator for Debounced<C> {
+    type Item = Result<NicEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(event) = self.coalescer.pop() {
+                return Some(Ok(event));
+            }
+            if self.done {
+                return self.coalescer.flush().map(Ok);
+            }
+
+            let received = match self.coalescer.timeout() {
+                Some(timeout) => self.receiver.recv_timeout(timeout),
+                None => self
+                    .receiver
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            };
+            match received {
+                Ok(Ok(event)) => self.coalescer.push(event),
+                Ok(Err(error)) => return Some(Err(error)),
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => self.done = true,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::LazyLock;
+    use std::time::{Duration, Instant};
+
+    use crate::error::Operation;
+    use crate::event::NicEvent::{NicDel, NicDown, NicNew, NicNoop, NicUp};
+    use crate::ifname::IfName;
+    use crate::lladdr::LinkLevelAddress;
+    use crate::{Error, Result};
+
+    use super::{debounce, Clock, Coalescer, Debounced};
+
+    static ENX: LazyLock<IfName> = LazyLock::new(|| "en7".try_into().unwrap());
+    static ETH: LazyLock<IfName> = LazyLock::new(|| "eth1".try_into().unwrap());
+    static LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "00:e0:4c:68:00:01".parse().unwrap());
+    static NEW_LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "02:11:22:33:44:55".parse().unwrap());
+
+    const QUIET: Duration = Duration::from_millis(500);
+
+    /// Clock only moving on when told to.
+    #[derive(Clone, Debug)]
+    struct FakeClock(Rc<Cell<Instant>>);
+
+    impl FakeClock {
+        fn new() -> Self {
+            Self(Rc::new(Cell::new(Instant::now())))
+        }
+
+        fn advance(&self, duration: Duration) {
+            self.0.set(self.0.get() + duration);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.0.get()
+        }
+    }
+
+    fn coalescer() -> (Coalescer<FakeClock>, FakeClock) {
+        let clock = FakeClock::new();
+        (Coalescer::with_clock(QUIET, clock.clone()), clock)
+    }
+
+    #[test]
+    fn test_coalescer_waits_for_quiet_period() {
+        let (mut coalescer, clock) = coalescer();
+
+        coalescer.push(NicNew((7, *ENX, *LLADDR)));
+
+        assert_eq!(coalescer.pop(), None);
+        assert_eq!(coalescer.timeout(), Some(QUIET));
+
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), Some(NicNew((7, *ENX, *LLADDR))));
+        assert_eq!(coalescer.pop(), None);
+        assert_eq!(coalescer.timeout(), None);
+    }
+
+    #[test]
+    fn test_coalescer_deduplicates() {
+        let (mut coalescer, clock) = coalescer();
+
+        coalescer.push(NicNew((7, *ENX, *LLADDR)));
+        clock.advance(QUIET / 2);
+        coalescer.push(NicNew((7, *ENX, *LLADDR)));
+        clock.advance(QUIET / 2);
+
+        // The quiet period started over with the duplicate.
+        assert_eq!(coalescer.pop(), None);
+
+        clock.advance(QUIET / 2);
+
+        assert_eq!(coalescer.pop(), Some(NicNew((7, *ENX, *LLADDR))));
+
+        coalescer.push(NicNew((7, *ENX, *LLADDR)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), None);
+    }
+
+    #[test]
+    fn test_coalescer_collapses_flapping() {
+        let (mut coalescer, clock) = coalescer();
+
+        coalescer.push(NicNew((7, *ENX, *LLADDR)));
+        coalescer.push(NicDel((7, *ENX, *LLADDR)));
+        coalescer.push(NicNew((8, *ENX, *LLADDR)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), Some(NicNew((8, *ENX, *LLADDR))));
+
+        // Gone and back within the quiet period, nothing changed.
+        coalescer.push(NicDel((8, *ENX, *LLADDR)));
+        coalescer.push(NicNew((8, *ENX, *LLADDR)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), None);
+
+        coalescer.push(NicNew((8, *ENX, *NEW_LLADDR)));
+        coalescer.push(NicDel((8, *ENX, *NEW_LLADDR)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), Some(NicDel((8, *ENX, *NEW_LLADDR))));
+    }
+
+    #[test]
+    fn test_coalescer_interfaces_in_deadline_order() {
+        let (mut coalescer, clock) = coalescer();
+
+        coalescer.push(NicNew((7, *ENX, *LLADDR)));
+        coalescer.push(NicNoop);
+        clock.advance(QUIET / 2);
+        coalescer.push(NicNew((3, *ETH, *LLADDR)));
+        coalescer.push(NicNew((7, *ENX, *NEW_LLADDR)));
+
+        assert_eq!(coalescer.timeout(), Some(QUIET));
+
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), Some(NicNew((3, *ETH, *LLADDR))));
+        assert_eq!(coalescer.pop(), Some(NicNew((7, *ENX, *NEW_LLADDR))));
+    }
+
+    #[test]
+    fn test_coalescer_link_follows_presence() {
+        let (mut coalescer, clock) = coalescer();
+
+        coalescer.push(NicNew((7, *ENX, *LLADDR)));
+        coalescer.push(NicDown((7, *ENX)));
+        coalescer.push(NicUp((7, *ENX)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), Some(NicNew((7, *ENX, *LLADDR))));
+        assert_eq!(coalescer.pop(), Some(NicUp((7, *ENX))));
+
+        coalescer.push(NicDown((7, *ENX)));
+        coalescer.push(NicNew((7, *ENX, *NEW_LLADDR)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), Some(NicNew((7, *ENX, *NEW_LLADDR))));
+        assert_eq!(coalescer.pop(), Some(NicDown((7, *ENX))));
+        assert_eq!(coalescer.pop(), None);
+    }
+
+    #[test]
+    fn test_coalescer_link_dropped_when_gone() {
+        let (mut coalescer, clock) = coalescer();
+
+        coalescer.push(NicNew((7, *ENX, *LLADDR)));
+        coalescer.push(NicUp((7, *ENX)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), Some(NicNew((7, *ENX, *LLADDR))));
+        assert_eq!(coalescer.pop(), Some(NicUp((7, *ENX))));
+
+        coalescer.push(NicDown((7, *ENX)));
+        coalescer.push(NicDel((7, *ENX, *LLADDR)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), Some(NicDel((7, *ENX, *LLADDR))));
+        assert_eq!(coalescer.pop(), None);
+
+        // Back with the same link state, which is given again.
+        coalescer.push(NicNew((8, *ENX, *LLADDR)));
+        coalescer.push(NicUp((8, *ENX)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), Some(NicNew((8, *ENX, *LLADDR))));
+        assert_eq!(coalescer.pop(), Some(NicUp((8, *ENX))));
+    }
+
+    #[test]
+    fn test_coalescer_link_deduplicates() {
+        let (mut coalescer, clock) = coalescer();
+
+        coalescer.push(NicUp((7, *ENX)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), Some(NicUp((7, *ENX))));
+
+        coalescer.push(NicDown((7, *ENX)));
+        coalescer.push(NicUp((7, *ENX)));
+        clock.advance(QUIET);
+
+        assert_eq!(coalescer.pop(), None);
+    }
+
+    #[test]
+    fn test_coalescer_flush() {
+        let (mut coalescer, _clock) = coalescer();
+
+        coalescer.push(NicNew((7, *ENX, *LLADDR)));
+        coalescer.push(NicDel((3, *ETH, *LLADDR)));
+
+        assert_eq!(coalescer.flush(), Some(NicNew((7, *ENX, *LLADDR))));
+        assert_eq!(coalescer.flush(), Some(NicDel((3, *ETH, *LLADDR))));
+        assert_eq!(coalescer.flush(), None);
+    }
+
+    #[test]
+    fn test_debounced_flushes_at_end() -> Result<()> {
+        let events = vec![
+            NicNew((7, *ENX, *LLADDR)),
+            NicNew((7, *ENX, *LLADDR)),
+            NicDel((7, *ENX, *LLADDR)),
+            NicNew((7, *ENX, *NEW_LLADDR)),
+            NicNew((3, *ETH, *LLADDR)),
+        ];
+        let (coalescer, _clock) = coalescer();
+
+        let debounced = Debounced::with_coalescer(events.into_iter().map(Ok), coalescer);
+
+        let expected = vec![NicNew((7, *ENX, *NEW_LLADDR)), NicNew((3, *ETH, *LLADDR))];
+
+        assert_eq!(debounced.collect::<Result<Vec<_>>>()?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_debounced_passes_errors_on() {
+        let error = Error::from_errno(Operation::Read, libc::ENOBUFS);
+        let events = vec![Err(error.clone())];
+        let (coalescer, _clock) = coalescer();
+
+        let mut debounced = Debounced::with_coalescer(events.into_iter(), coalescer);
+
+        assert_eq!(debounced.next().unwrap().unwrap_err(), error);
+        assert!(debounced.next().is_none());
+    }
+
+    #[test]
+    fn test_debounce_zero_quiet_period_only_deduplicates() -> Result<()> {
+        let events = vec![
+            NicNew((7, *ENX, *LLADDR)),
+            NicNew((7, *ENX, *LLADDR)),
+            NicNoop,
+        ];
+
+        let debounced = debounce(events.into_iter().map(Ok), Duration::ZERO);
+
+        assert_eq!(
+            debounced.collect::<Result<Vec<_>>>()?,
+            vec![NicNew((7, *ENX, *LLADDR))]
+        );
+
+        Ok(())
+    }
+}
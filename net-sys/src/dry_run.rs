@@ -0,0 +1,308 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+use crate::backend::NicBackend;
+use crate::ifname::IfName;
+use crate::lladdr::LinkLevelAddress;
+use crate::retry::Retry;
+use crate::Result;
+
+#[cfg(not(test))]
+use crate::backend::OsBackend as Backend;
+#[cfg(test)]
+use mocks::nic::Backend;
+
+/// Wraps the link-level address operations of a `NicBackend`, the kernel's
+/// unless `with_backend` gives another one.
+///
+/// In a dry run, `set_lladdr` only logs the change it would make and records
+/// the address, which `get_lladdr` then reports for that interface. Callers
+/// checking their changes, and deciding on them, behave as if it had been
+/// applied.
+#[derive(Debug)]
+pub struct Nic<B: NicBackend = Backend> {
+    backend: B,
+    dry_run: bool,
+    planned: RefCell<HashMap<IfName, LinkLevelAddress>>,
+}
+
+impl Nic {
+    pub fn new(dry_run: bool) -> Self {
+        Self {
+            backend: Backend,
+            dry_run,
+            planned: RefCell::default(),
+        }
+    }
+}
+
+impl Default for Nic {
+    fn default() -> Self {
+        Self::new(false)
+    }
+}
+
+impl<B: NicBackend> Nic<B> {
+    pub fn with_backend<C: NicBackend>(self, backend: C) -> Nic<C> {
+        Nic {
+            backend,
+            dry_run: self.dry_run,
+            planned: self.planned,
+        }
+    }
+
+    /// Backend the addresses are read and set with, e.g. for its `monitor`.
+    pub fn backend(&self) -> &B {
+        &self.backend
+    }
+
+    pub fn into_backend(self) -> B {
+        self.backend
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn get_lladdr(&self, ifname: &IfName) -> Result<LinkLevelAddress> {
+        match self.planned.borrow().get(ifname) {
+            Some(lladdr) => Ok(*lladdr),
+            None => self.backend.get_lladdr(ifname),
+        }
+    }
+
+    /// Sets the address of `ifname`, `reason` only showing up in the dry-run
+    /// log.
+    pub fn set_lladdr(
+        &self,
+        ifname: &IfName,
+        lladdr: &LinkLevelAddress,
+        reason: &str,
+    ) -> Result<()> {
+        if !self.dry_run {
+            return self.backend.set_lladdr(ifname, lladdr);
+        }
+
+        let old = self.get_lladdr(ifname)?;
+        log::info!("dry-run: set_lladdr {ifname} {old} -> {lladdr} ({reason})");
+        self.planned.borrow_mut().insert(*ifname, *lladdr);
+
+        Ok(())
+    }
+
+    /// Like `set_lladdr`, reading the address back and retrying as `retry`
+    /// allows. A dry run has nothing to read back.
+    pub fn set_lladdr_verified(
+        &self,
+        ifname: &IfName,
+        lladdr: &LinkLevelAddress,
+        retry: &Retry,
+        reason: &str,
+    ) -> Result<()> {
+        if !self.dry_run {
+            return self.backend.set_lladdr_verified(ifname, lladdr, retry);
+        }
+
+        self.set_lladdr(ifname, lladdr, reason)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod mocks {
+    pub(crate) mod nic {
+        use mockdown::{mockdown, Mock};
+
+        use crate::backend::{Monitor, NicBackend};
+        use crate::ifname::IfName;
+        use crate::info::NicInfo;
+        use crate::lladdr::LinkLevelAddress;
+        use crate::retry::Retry;
+        use crate::Result;
+
+        pub(crate) struct GetLLAddr(pub fn(ifname: &IfName) -> Result<LinkLevelAddress>);
+        pub(crate) struct SetLLAddr(
+            pub fn(ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()>,
+        );
+        pub(crate) struct SetLLAddrVerified(
+            pub fn(ifname: &IfName, lladdr: &LinkLevelAddress, retry: &Retry) -> Result<()>,
+        );
+
+        #[derive(Debug, Default)]
+        pub struct Backend;
+
+        impl NicBackend for Backend {
+            fn get_lladdr(&self, ifname: &IfName) -> Result<LinkLevelAddress> {
+                mockdown().next(|GetLLAddr(mock)| mock(ifname)).unwrap()
+            }
+
+            fn set_lladdr(&self, ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()> {
+                mockdown()
+                    .next(|SetLLAddr(mock)| mock(ifname, lladdr))
+                    .unwrap()
+            }
+
+            fn list(&self) -> Result<Vec<NicInfo>> {
+                unreachable!("not used by dry_run::Nic")
+            }
+
+            fn monitor(&self) -> Result<Monitor> {
+                unreachable!("not used by dry_run::Nic")
+            }
+
+            fn set_lladdr_verified(
+                &self,
+                ifname: &IfName,
+                lladdr: &LinkLevelAddress,
+                retry: &Retry,
+            ) -> Result<()> {
+                mockdown()
+                    .next(|SetLLAddrVerified(mock)| mock(ifname, lladdr, retry))
+                    .unwrap()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use mockdown::{mockdown, Mock};
+
+    use crate::backend::{Monitor, NicBackend};
+    use crate::error::{ErrorKind, Operation};
+    use crate::ifname::IfName;
+    use crate::info::NicInfo;
+    use crate::lladdr::LinkLevelAddress;
+    use crate::retry::Retry;
+    use crate::{Error, Result};
+
+    use super::mocks::nic;
+    use super::Nic;
+
+    static IFNAME: LazyLock<IfName> = LazyLock::new(|| "enx".try_into().unwrap());
+    static LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "00:11:22:33:44:55".parse().unwrap());
+    static NEW_LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "02:11:22:33:44:55".parse().unwrap());
+
+    #[test]
+    fn test_nic_live() -> Result<()> {
+        mockdown()
+            .expect(nic::SetLLAddr(|ifname, lladdr| {
+                assert_eq!((*ifname, *lladdr), (*IFNAME, *NEW_LLADDR));
+                Ok(())
+            }))
+            .expect(nic::GetLLAddr(|ifname| {
+                assert_eq!(*ifname, *IFNAME);
+                Ok(*NEW_LLADDR)
+            }));
+
+        let nic = Nic::default();
+
+        nic.set_lladdr(&IFNAME, &NEW_LLADDR, "test")?;
+
+        assert!(!nic.is_dry_run());
+        assert_eq!(nic.get_lladdr(&IFNAME)?, *NEW_LLADDR);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nic_dry_run() -> Result<()> {
+        // Only the address before the first change is read, nothing is set.
+        mockdown().expect(nic::GetLLAddr(|ifname| {
+            assert_eq!(*ifname, *IFNAME);
+            Ok(*LLADDR)
+        }));
+
+        let nic = Nic::new(true);
+
+        nic.set_lladdr(&IFNAME, &NEW_LLADDR, "test")?;
+        nic.set_lladdr(&IFNAME, &LLADDR, "test")?;
+
+        assert!(nic.is_dry_run());
+        assert_eq!(nic.get_lladdr(&IFNAME)?, *LLADDR);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nic_verified() -> Result<()> {
+        mockdown().expect(nic::SetLLAddrVerified(|ifname, lladdr, retry| {
+            assert_eq!((*ifname, *lladdr), (*IFNAME, *NEW_LLADDR));
+            assert_eq!(*retry, Retry::default());
+            Ok(())
+        }));
+
+        Nic::default().set_lladdr_verified(&IFNAME, &NEW_LLADDR, &Retry::default(), "test")
+    }
+
+    #[test]
+    fn test_nic_dry_run_verified() -> Result<()> {
+        mockdown().expect(nic::GetLLAddr(|_ifname| Ok(*LLADDR)));
+
+        let nic = Nic::new(true);
+
+        nic.set_lladdr_verified(&IFNAME, &NEW_LLADDR, &Retry::default(), "test")?;
+
+        assert_eq!(nic.get_lladdr(&IFNAME)?, *NEW_LLADDR);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nic_dry_run_get_error() {
+        mockdown().expect(nic::GetLLAddr(|_ifname| {
+            Err(Error::new(Operation::GetLinkLevelAddress, ErrorKind::Other))
+        }));
+
+        let expected_error = Error::new(Operation::GetLinkLevelAddress, ErrorKind::Other);
+
+        let error = Nic::new(true)
+            .set_lladdr(&IFNAME, &NEW_LLADDR, "test")
+            .unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    /// Backend of an interface unplugged meanwhile.
+    #[derive(Debug)]
+    struct Unplugged;
+
+    impl NicBackend for Unplugged {
+        fn get_lladdr(&self, ifname: &IfName) -> Result<LinkLevelAddress> {
+            Err(
+                Error::from_errno(Operation::GetLinkLevelAddress, libc::ENODEV)
+                    .with_ifname(*ifname),
+            )
+        }
+
+        fn set_lladdr(&self, ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()> {
+            Err(
+                Error::from_errno(Operation::SetLinkLevelAddress, libc::ENODEV)
+                    .with_ifname(*ifname)
+                    .with_lladdr(*lladdr),
+            )
+        }
+
+        fn list(&self) -> Result<Vec<NicInfo>> {
+            Ok(Vec::new())
+        }
+
+        fn monitor(&self) -> Result<Monitor> {
+            Ok(Box::new(std::iter::empty()))
+        }
+    }
+
+    #[test]
+    fn test_nic_with_backend() {
+        let nic = Nic::new(true).with_backend(Unplugged);
+
+        let error = nic.set_lladdr(&IFNAME, &NEW_LLADDR, "test").unwrap_err();
+
+        assert!(nic.is_dry_run());
+        assert_eq!(error.operation, Operation::GetLinkLevelAddress);
+        assert_eq!(error.kind(), ErrorKind::NoSuchInterface);
+    }
+}
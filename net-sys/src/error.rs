@@ -0,0 +1,278 @@
+use std::ffi::c_int;
+use std::fmt::{Debug, Display};
+use std::io;
+
+use crate::ifname::IfName;
+use crate::lladdr::LinkLevelAddress;
+
+/// What a failed operation means to its caller, e.g. whether trying again
+/// may help.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// `EPERM` or `EACCES`, the process lacks a privilege.
+    PermissionDenied,
+    /// `ENODEV` or `ENXIO`, the interface is gone or never existed.
+    NoSuchInterface,
+    /// `EBUSY` or `EAGAIN`, trying again later may succeed.
+    Busy,
+    /// The interface or the system does not support the operation.
+    Unsupported,
+    /// A malformed name or address, or one the driver rejected.
+    InvalidInput,
+    /// The driver reported success but kept its address.
+    NotApplied,
+    /// A malformed message from the kernel, which the next read is not
+    /// affected by.
+    Decode,
+    /// Any other failure, e.g. an errno with no category of its own.
+    Other,
+}
+
+impl ErrorKind {
+    pub fn from_errno(errno: c_int) -> Self {
+        let is = |errnos: &[c_int]| errnos.contains(&errno);
+        if is(&[libc::EPERM, libc::EACCES]) {
+            ErrorKind::PermissionDenied
+        } else if is(&[libc::ENODEV, libc::ENXIO]) {
+            ErrorKind::NoSuchInterface
+        } else if is(&[libc::EBUSY, libc::EAGAIN]) {
+            ErrorKind::Busy
+        } else if is(&[
+            libc::EOPNOTSUPP,
+            libc::ENOTSUP,
+            libc::EAFNOSUPPORT,
+            libc::EPROTONOSUPPORT,
+        ]) {
+            ErrorKind::Unsupported
+        } else if is(&[libc::EINVAL, libc::EADDRNOTAVAIL]) {
+            ErrorKind::InvalidInput
+        } else {
+            ErrorKind::Other
+        }
+    }
+}
+
+impl From<ErrorKind> for io::ErrorKind {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::PermissionDenied => io::ErrorKind::PermissionDenied,
+            ErrorKind::NoSuchInterface => io::ErrorKind::NotFound,
+            ErrorKind::Busy => io::ErrorKind::ResourceBusy,
+            ErrorKind::Unsupported => io::ErrorKind::Unsupported,
+            ErrorKind::InvalidInput => io::ErrorKind::InvalidInput,
+            ErrorKind::Decode => io::ErrorKind::InvalidData,
+            ErrorKind::NotApplied | ErrorKind::Other => io::ErrorKind::Other,
+        }
+    }
+}
+
+/// Operation an `Error` comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Operation {
+    OpenSocket,
+    Bind,
+    Send,
+    Read,
+    Close,
+    GetLinks,
+    GetLinkLevelAddress,
+    GetPermLinkLevelAddress,
+    SetLinkLevelAddress,
+    ParseIfName,
+    ParseLinkLevelAddress,
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Operation::OpenSocket => "open_socket",
+            Operation::Bind => "bind",
+            Operation::Send => "send",
+            Operation::Read => "read",
+            Operation::Close => "close",
+            Operation::GetLinks => "get_links",
+            Operation::GetLinkLevelAddress => "get_lladdr",
+            Operation::GetPermLinkLevelAddress => "get_perm_lladdr",
+            Operation::SetLinkLevelAddress => "set_lladdr",
+            Operation::ParseIfName => "parse_ifname",
+            Operation::ParseLinkLevelAddress => "parse_lladdr",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Error of every `net-sys` operation, the one of `net_sys::Result`.
+///
+/// A struct rather than an enum: every failure carries the same operation,
+/// errno, interface and address, and callers branch on `kind()` instead.
+///
+/// Displays as the message of the module it comes from, e.g.
+/// `Socket::SetLinkLevelAddressError { .. }`.
+#[derive(Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Error {
+    pub operation: Operation,
+    pub errno: Option<c_int>,
+    pub ifname: Option<IfName>,
+    pub lladdr: Option<LinkLevelAddress>,
+    kind: ErrorKind,
+    detail: Option<String>,
+}
+
+impl Error {
+    /// Error of a system call failing with `errno`.
+    pub fn from_errno(operation: Operation, errno: c_int) -> Self {
+        Self {
+            errno: Some(errno),
+            ..Self::new(operation, ErrorKind::from_errno(errno))
+        }
+    }
+
+    pub fn new(operation: Operation, kind: ErrorKind) -> Self {
+        Self {
+            operation,
+            errno: None,
+            ifname: None,
+            lladdr: None,
+            kind,
+            detail: None,
+        }
+    }
+
+    /// Error of a module, showing as the `Debug` output of `detail`.
+    pub(crate) fn detailed(
+        operation: Operation,
+        errno: Option<c_int>,
+        detail: &impl Debug,
+    ) -> Self {
+        Self {
+            errno,
+            detail: Some(format!("{detail:?}")),
+            ..Self::new(
+                operation,
+                errno.map_or(ErrorKind::Other, ErrorKind::from_errno),
+            )
+        }
+    }
+
+    pub fn with_ifname(mut self, ifname: IfName) -> Self {
+        self.ifname = Some(ifname);
+        self
+    }
+
+    pub fn with_lladdr(mut self, lladdr: LinkLevelAddress) -> Self {
+        self.lladdr = Some(lladdr);
+        self
+    }
+
+    pub(crate) fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(detail) = &self.detail {
+            return write!(f, "{detail}");
+        }
+
+        let mut debug = f.debug_struct("Nic::Error");
+        debug.field("operation", &format_args!("{}", self.operation));
+        if let Some(ifname) = &self.ifname {
+            debug.field("ifname", ifname);
+        }
+        if let Some(lladdr) = &self.lladdr {
+            debug.field("lladdr", lladdr);
+        }
+        if let Some(errno) = &self.errno {
+            debug.field("errno", errno);
+        }
+        debug.field("kind", &self.kind).finish()
+    }
+}
+
+/// Keeps `error` as the inner error, found again with
+/// `io_error.get_ref()?.downcast_ref::<Error>()`.
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        io::Error::new(error.kind().into(), error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::sync::LazyLock;
+
+    use crate::ifname::IfName;
+    use crate::lladdr::LinkLevelAddress;
+
+    use super::{Error, ErrorKind, Operation};
+
+    static IFNAME: LazyLock<IfName> = LazyLock::new(|| "enx".try_into().unwrap());
+    static LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "00:11:22:33:44:55".parse().unwrap());
+
+    #[test]
+    fn test_error_kind_from_errno() {
+        let kinds = [
+            (libc::EPERM, ErrorKind::PermissionDenied),
+            (libc::EACCES, ErrorKind::PermissionDenied),
+            (libc::ENODEV, ErrorKind::NoSuchInterface),
+            (libc::ENXIO, ErrorKind::NoSuchInterface),
+            (libc::EBUSY, ErrorKind::Busy),
+            (libc::EAGAIN, ErrorKind::Busy),
+            (libc::EOPNOTSUPP, ErrorKind::Unsupported),
+            (libc::EINVAL, ErrorKind::InvalidInput),
+            (libc::EADDRNOTAVAIL, ErrorKind::InvalidInput),
+            (libc::EIO, ErrorKind::Other),
+        ];
+
+        for (errno, kind) in kinds {
+            assert_eq!((errno, ErrorKind::from_errno(errno)), (errno, kind));
+        }
+    }
+
+    #[test]
+    fn test_error_from_errno() {
+        let expected_error = "Nic::Error { operation: set_lladdr, ifname: \"enx\", lladdr: \"00:11:22:33:44:55\", errno: 1, kind: PermissionDenied }";
+
+        let error = Error::from_errno(Operation::SetLinkLevelAddress, libc::EPERM)
+            .with_ifname(*IFNAME)
+            .with_lladdr(*LLADDR);
+
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+        assert_eq!(error.errno, Some(libc::EPERM));
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+    }
+
+    #[test]
+    fn test_error_into_io_error() {
+        let error = Error::from_errno(Operation::GetLinkLevelAddress, libc::ENODEV);
+
+        let io_error = io::Error::from(error.clone());
+
+        assert_eq!(io_error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(io_error.to_string(), error.to_string());
+        assert_eq!(
+            io_error.get_ref().and_then(|inner| inner.downcast_ref()),
+            Some(&error)
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use std::ffi::c_int;
+
+use crate::ifname::IfName;
+use crate::lladdr::LinkLevelAddress;
+
+/// `NicUp` and `NicDown` tell whether a link carries traffic, e.g. a cable
+/// plugged in or Wi-Fi associated, whether or not the interface is new.
+///
+/// With the `serde` feature, serialized as a flat map, e.g.
+/// `{"event":"new","index":7,"ifname":"en7","lladdr":"00:e0:4c:68:00:01"}`
+/// or `{"event":"down","index":7,"ifname":"en7"}`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "NicEventRepr", from = "NicEventRepr")
+)]
+pub enum NicEvent {
+    NicNew((c_int, IfName, LinkLevelAddress)),
+    NicDel((c_int, IfName, LinkLevelAddress)),
+    NicUp((c_int, IfName)),
+    NicDown((c_int, IfName)),
+    NicNoop,
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(tag = "event", rename_all = "lowercase", deny_unknown_fields)]
+enum NicEventRepr {
+    New {
+        index: c_int,
+        ifname: IfName,
+        lladdr: LinkLevelAddress,
+    },
+    Del {
+        index: c_int,
+        ifname: IfName,
+        lladdr: LinkLevelAddress,
+    },
+    Up {
+        index: c_int,
+        ifname: IfName,
+    },
+    Down {
+        index: c_int,
+        ifname: IfName,
+    },
+    Noop,
+}
+
+#[cfg(feature = "serde")]
+impl From<NicEvent> for NicEventRepr {
+    fn from(event: NicEvent) -> Self {
+        match event {
+            NicEvent::NicNew((index, ifname, lladdr)) => Self::New {
+                index,
+                ifname,
+                lladdr,
+            },
+            NicEvent::NicDel((index, ifname, lladdr)) => Self::Del {
+                index,
+                ifname,
+                lladdr,
+            },
+            NicEvent::NicUp((index, ifname)) => Self::Up { index, ifname },
+            NicEvent::NicDown((index, ifname)) => Self::Down { index, ifname },
+            NicEvent::NicNoop => Self::Noop,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<NicEventRepr> for NicEvent {
+    fn from(repr: NicEventRepr) -> Self {
+        match repr {
+            NicEventRepr::New {
+                index,
+                ifname,
+                lladdr,
+            } => Self::NicNew((index, ifname, lladdr)),
+            NicEventRepr::Del {
+                index,
+                ifname,
+                lladdr,
+            } => Self::NicDel((index, ifname, lladdr)),
+            NicEventRepr::Up { index, ifname } => Self::NicUp((index, ifname)),
+            NicEventRepr::Down { index, ifname } => Self::NicDown((index, ifname)),
+            NicEventRepr::Noop => Self::NicNoop,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::NicEvent::{self, NicDel, NicDown, NicNew, NicNoop, NicUp};
+
+    const NEW_JSON: &str =
+        r#"{"event":"new","index":7,"ifname":"en7","lladdr":"00:e0:4c:68:00:01"}"#;
+    const DEL_JSON: &str =
+        r#"{"event":"del","index":7,"ifname":"en7","lladdr":"00:e0:4c:68:00:01"}"#;
+    const UP_JSON: &str = r#"{"event":"up","index":7,"ifname":"en7"}"#;
+    const DOWN_JSON: &str = r#"{"event":"down","index":7,"ifname":"en7"}"#;
+
+    #[test]
+    fn test_nic_event_serialize() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let new = NicNew((7, "en7".try_into()?, "00:e0:4c:68:00:01".parse()?));
+        let del = NicDel((7, "en7".try_into()?, "00:e0:4c:68:00:01".parse()?));
+
+        assert_eq!(serde_json::to_string(&new)?, NEW_JSON);
+        assert_eq!(serde_json::to_string(&del)?, DEL_JSON);
+        assert_eq!(
+            serde_json::to_string(&NicUp((7, "en7".try_into()?)))?,
+            UP_JSON
+        );
+        assert_eq!(
+            serde_json::to_string(&NicDown((7, "en7".try_into()?)))?,
+            DOWN_JSON
+        );
+        assert_eq!(serde_json::to_string(&NicNoop)?, r#"{"event":"noop"}"#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nic_event_deserialize() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let new = NicNew((7, "en7".try_into()?, "00:e0:4c:68:00:01".parse()?));
+        let del = NicDel((7, "en7".try_into()?, "00:e0:4c:68:00:01".parse()?));
+
+        assert_eq!(serde_json::from_str::<NicEvent>(NEW_JSON)?, new);
+        assert_eq!(serde_json::from_str::<NicEvent>(DEL_JSON)?, del);
+        assert_eq!(
+            serde_json::from_str::<NicEvent>(UP_JSON)?,
+            NicUp((7, "en7".try_into()?))
+        );
+        assert_eq!(
+            serde_json::from_str::<NicEvent>(DOWN_JSON)?,
+            NicDown((7, "en7".try_into()?))
+        );
+        assert_eq!(
+            serde_json::from_str::<NicEvent>(r#"{"event":"noop"}"#)?,
+            NicNoop
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nic_event_deserialize_invalid_ifname() {
+        let expected_error =
+            "IfName::TooLargeError { value: \"0123456789ABCDEF\", len: 16, max: 15 }";
+
+        let json =
+            r#"{"event":"new","index":7,"ifname":"0123456789ABCDEF","lladdr":"00:e0:4c:68:00:01"}"#;
+        let error = serde_json::from_str::<NicEvent>(json).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_nic_event_deserialize_unknown_event() {
+        let expected_error = "unknown variant `carrier`, expected one of `new`, `del`, `up`, `down`, `noop` at line 1 column 18";
+
+        let error = serde_json::from_str::<NicEvent>(r#"{"event":"carrier"}"#).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+}
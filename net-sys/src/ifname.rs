@@ -4,6 +4,7 @@ use std::mem;
 use std::ops::Deref;
 use std::ptr;
 
+use crate::error::{ErrorKind, Operation};
 use crate::format::{AsBytes, AsString};
 
 pub use crate::IF_NAME_SIZE;
@@ -21,14 +22,6 @@ enum Error {
     InvalidCString(String, String),
 }
 
-impl std::error::Error for Error {}
-
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
 impl Debug for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -53,6 +46,13 @@ impl Debug for Error {
     }
 }
 
+impl From<Error> for crate::Error {
+    fn from(error: Error) -> Self {
+        crate::Error::detailed(Operation::ParseIfName, None, &error)
+            .with_kind(ErrorKind::InvalidInput)
+    }
+}
+
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
 pub struct IfName(IfNameType);
 
@@ -86,6 +86,22 @@ impl Display for IfName {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for IfName {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Validated like `TryFrom<String>`, with its error as the message.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for IfName {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        IfName::try_from(value).map_err(serde::de::Error::custom)
+    }
+}
+
 impl From<&IfNameType> for IfName {
     fn from(value: &IfNameType) -> Self {
         Self(*value)
@@ -100,7 +116,7 @@ impl From<&SignedIfNameType> for IfName {
 }
 
 impl TryFrom<&str> for IfName {
-    type Error = Box<dyn std::error::Error>;
+    type Error = crate::Error;
 
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         String::from(value).try_into()
@@ -108,7 +124,7 @@ impl TryFrom<&str> for IfName {
 }
 
 impl TryFrom<String> for IfName {
-    type Error = Box<dyn std::error::Error>;
+    type Error = crate::Error;
 
     fn try_from(value: String) -> Result<Self, Self::Error> {
         let value = match value.len() {
@@ -127,7 +143,7 @@ impl TryFrom<String> for IfName {
 }
 
 impl TryFrom<&[i8]> for IfName {
-    type Error = Box<dyn std::error::Error>;
+    type Error = crate::Error;
 
     fn try_from(value: &[i8]) -> Result<Self, Self::Error> {
         let value = match value.len() {
@@ -256,6 +272,47 @@ mod tests {
         assert_eq!(ifname_str, "0123456789ABCDE");
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ifname_serialize() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let ifname = IfName(IF_NAME);
+
+        assert_eq!(serde_json::to_string(&ifname)?, "\"0123456789ABCDE\"");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ifname_deserialize() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let ifname: IfName = serde_json::from_str("\"0123456789ABCDE\"")?;
+
+        assert_eq!(ifname, IfName(IF_NAME));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ifname_deserialize_too_large() {
+        let expected_error =
+            "IfName::TooLargeError { value: \"0123456789ABCDEF\", len: 16, max: 15 }";
+
+        let error = serde_json::from_str::<IfName>("\"0123456789ABCDEF\"").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_ifname_deserialize_not_a_string() {
+        let expected_error = "invalid type: integer `7`, expected a string at line 1 column 1";
+
+        let error = serde_json::from_str::<IfName>("7").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
     #[test]
     fn test_ifname_debug() {
         let ifname = IfName(IF_NAME);
@@ -0,0 +1,126 @@
+use core::fmt::Display;
+use std::ffi::c_int;
+
+use crate::ifname::IfName;
+use crate::lladdr::LinkLevelAddress;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NicType {
+    Ether,
+    Loopback,
+    Other(u32),
+}
+
+impl Display for NicType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NicType::Ether => write!(f, "ether"),
+            NicType::Loopback => write!(f, "loopback"),
+            NicType::Other(value) => write!(f, "other({})", value),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for NicType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for NicType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        let other = |value: &str| {
+            let value = value.strip_prefix("other(")?.strip_suffix(')')?;
+            value.parse().ok().map(NicType::Other)
+        };
+        match value.as_str() {
+            "ether" => Ok(NicType::Ether),
+            "loopback" => Ok(NicType::Loopback),
+            value => other(value).ok_or_else(|| {
+                serde::de::Error::invalid_value(
+                    serde::de::Unexpected::Str(value),
+                    &"ether, loopback or other(<type>)",
+                )
+            }),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NicInfo {
+    pub index: c_int,
+    pub ifname: IfName,
+    pub lladdr: Option<LinkLevelAddress>,
+    pub nic_type: NicType,
+    pub flags: u32,
+    pub mtu: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NicType;
+
+    #[cfg(feature = "serde")]
+    use super::NicInfo;
+
+    #[cfg(feature = "serde")]
+    const LO0_JSON: &str = r#"{"index":1,"ifname":"lo0","lladdr":null,"nic_type":"loopback","flags":32841,"mtu":16384}"#;
+
+    #[test]
+    fn test_nic_type_display() {
+        assert_eq!(format!("{}", NicType::Ether), "ether");
+        assert_eq!(format!("{}", NicType::Loopback), "loopback");
+        assert_eq!(format!("{}", NicType::Other(0x18)), "other(24)");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_nic_info_serialize() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let info = NicInfo {
+            index: 1,
+            ifname: "lo0".try_into()?,
+            lladdr: None,
+            nic_type: NicType::Loopback,
+            flags: 0x8049,
+            mtu: 16384,
+        };
+
+        assert_eq!(serde_json::to_string(&info)?, LO0_JSON);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_nic_info_deserialize() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let info: NicInfo = serde_json::from_str(LO0_JSON)?;
+
+        assert_eq!(info.ifname, "lo0".try_into()?);
+        assert_eq!(info.nic_type, NicType::Loopback);
+        assert_eq!(info.mtu, 16384);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_nic_type_deserialize() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        for nic_type in [NicType::Ether, NicType::Loopback, NicType::Other(24)] {
+            let json = serde_json::to_string(&nic_type)?;
+            assert_eq!(serde_json::from_str::<NicType>(&json)?, nic_type);
+        }
+
+        let expected_error =
+            "invalid value: string \"other(x)\", expected ether, loopback or other(<type>)";
+
+        let error = serde_json::from_str::<NicType>("\"other(x)\"").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+
+        Ok(())
+    }
+}
@@ -1,14 +1,30 @@
 #[cfg(not(any(feature = "libc")))]
 compile_error!("Unsupported system!");
 
+pub mod backend;
+pub mod debounce;
+pub mod error;
+mod event;
 pub(crate) mod format;
 pub mod ifname;
+mod info;
 pub mod lladdr;
+#[cfg(test)]
+mod random;
+pub mod retry;
 
+#[cfg(feature = "libc")]
+pub mod dry_run;
 #[cfg(feature = "libc")]
 mod libc;
+#[cfg(any(test, target_os = "macos"))]
+mod rtax;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 
 #[cfg(feature = "libc")]
 pub use libc::{nic, IF_NAME_SIZE};
 
-pub type Result<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+pub use error::{Error, ErrorKind};
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -7,6 +7,9 @@ pub mod linux;
 #[cfg(target_os = "macos")]
 pub mod macos;
 
+#[cfg(target_os = "linux")]
+pub use linux::nic;
+
 #[cfg(target_os = "macos")]
 pub use macos::nic;
 
@@ -1 +1,6 @@
+pub mod nic;
 
+mod defs;
+mod socket;
+mod sys;
+mod types;
@@ -0,0 +1,4 @@
+pub(crate) mod ethtool;
+pub(crate) mod ifla;
+pub(crate) mod rtm;
+pub(crate) mod sio;
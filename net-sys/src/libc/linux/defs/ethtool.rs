@@ -0,0 +1,8 @@
+// https://github.com/torvalds/linux/blob/v6.6/include/uapi/linux/ethtool.h
+
+// Get permanent hardware address
+pub(crate) const ETHTOOL_GPERMADDR: u32 = 0x20;
+
+// Size of the address buffer of struct ethtool_perm_addr, MAX_ADDR_LEN in
+// include/linux/netdevice.h
+pub(crate) const MAX_ADDR_LEN: usize = 32;
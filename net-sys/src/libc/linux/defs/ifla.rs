@@ -0,0 +1,53 @@
+use libc::{c_uchar, c_ushort};
+
+// https://github.com/torvalds/linux/blob/v6.6/include/uapi/linux/if_link.h#L315
+
+// Interface Link Attributes
+pub(crate) const IFLA_ADDRESS: c_ushort = 1;
+pub(crate) const IFLA_IFNAME: c_ushort = 3;
+pub(crate) const IFLA_MTU: c_ushort = 4;
+pub(crate) const IFLA_OPERSTATE: c_ushort = 16;
+pub(crate) const IFLA_ALT_IFNAME: c_ushort = 53;
+
+// https://github.com/torvalds/linux/blob/v6.6/include/uapi/linux/if.h#L188
+
+// RFC 2863 operational status, the single byte of IFLA_OPERSTATE
+pub(crate) const IF_OPER_UNKNOWN: c_uchar = 0;
+pub(crate) const IF_OPER_UP: c_uchar = 6;
+
+#[cfg(test)]
+mod tests {
+    use super::{IFLA_ADDRESS, IFLA_ALT_IFNAME, IFLA_IFNAME, IFLA_MTU, IFLA_OPERSTATE};
+    use super::{IF_OPER_UNKNOWN, IF_OPER_UP};
+
+    #[test]
+    fn test_ifla_address() {
+        assert_eq!(IFLA_ADDRESS, libc::IFLA_ADDRESS)
+    }
+
+    #[test]
+    fn test_ifla_ifname() {
+        assert_eq!(IFLA_IFNAME, libc::IFLA_IFNAME)
+    }
+
+    #[test]
+    fn test_ifla_mtu() {
+        assert_eq!(IFLA_MTU, libc::IFLA_MTU)
+    }
+
+    #[test]
+    fn test_ifla_operstate() {
+        assert_eq!(IFLA_OPERSTATE, libc::IFLA_OPERSTATE)
+    }
+
+    #[test]
+    fn test_ifla_alt_ifname() {
+        assert_eq!(IFLA_ALT_IFNAME, libc::IFLA_ALT_IFNAME)
+    }
+
+    #[test]
+    fn test_if_oper() {
+        assert_eq!(IF_OPER_UNKNOWN as i32, libc::IF_OPER_UNKNOWN);
+        assert_eq!(IF_OPER_UP as i32, libc::IF_OPER_UP);
+    }
+}
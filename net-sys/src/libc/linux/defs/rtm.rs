@@ -0,0 +1,92 @@
+use std::fmt::Debug;
+
+use libc::c_int;
+
+// https://github.com/torvalds/linux/blob/v6.6/include/uapi/linux/netlink.h#L108
+// https://github.com/torvalds/linux/blob/v6.6/include/uapi/linux/rtnetlink.h#L24
+
+const NLMSG_NOOP: c_int = 0x1;
+const NLMSG_ERROR: c_int = 0x2;
+const NLMSG_DONE: c_int = 0x3;
+const NLMSG_OVERRUN: c_int = 0x4;
+const RTM_NEWLINK: c_int = 16;
+const RTM_DELLINK: c_int = 17;
+pub(crate) const RTM_GETLINK: c_int = 18;
+pub(crate) const RTM_SETLINK: c_int = 19;
+
+// Route Netlink Message
+#[repr(i32)]
+#[derive(PartialEq)]
+#[allow(clippy::enum_variant_names)]
+pub(crate) enum Rtm {
+    // netlink
+    NlmsgNoop = NLMSG_NOOP,
+    NlmsgError = NLMSG_ERROR,
+    NlmsgDone = NLMSG_DONE,
+    NlmsgOverrun = NLMSG_OVERRUN,
+    // rtnetlink
+    RtmNewlink = RTM_NEWLINK,
+    RtmDellink = RTM_DELLINK,
+    RtmGetlink = RTM_GETLINK,
+    RtmSetlink = RTM_SETLINK,
+    RtmInvalid(c_int),
+}
+
+impl From<c_int> for Rtm {
+    fn from(value: c_int) -> Self {
+        match value {
+            NLMSG_NOOP => Rtm::NlmsgNoop,
+            NLMSG_ERROR => Rtm::NlmsgError,
+            NLMSG_DONE => Rtm::NlmsgDone,
+            NLMSG_OVERRUN => Rtm::NlmsgOverrun,
+            RTM_NEWLINK => Rtm::RtmNewlink,
+            RTM_DELLINK => Rtm::RtmDellink,
+            RTM_GETLINK => Rtm::RtmGetlink,
+            RTM_SETLINK => Rtm::RtmSetlink,
+            value => Rtm::RtmInvalid(value),
+        }
+    }
+}
+
+impl Debug for Rtm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NlmsgNoop => write!(f, "NlmsgNoop"),
+            Self::NlmsgError => write!(f, "NlmsgError"),
+            Self::NlmsgDone => write!(f, "NlmsgDone"),
+            Self::NlmsgOverrun => write!(f, "NlmsgOverrun"),
+            Self::RtmNewlink => write!(f, "RtmNewlink"),
+            Self::RtmDellink => write!(f, "RtmDellink"),
+            Self::RtmGetlink => write!(f, "RtmGetlink"),
+            Self::RtmSetlink => write!(f, "RtmSetlink"),
+            Self::RtmInvalid(value) => f
+                .debug_tuple("RtmInvalid")
+                .field(&format!("{:x}", value))
+                .finish(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libc::c_int;
+
+    use super::Rtm;
+
+    #[test]
+    fn test_rtm_from_libc() {
+        assert!(Rtm::from(libc::NLMSG_ERROR) == Rtm::NlmsgError);
+        assert!(Rtm::from(libc::NLMSG_DONE) == Rtm::NlmsgDone);
+        assert!(Rtm::from(libc::RTM_NEWLINK as c_int) == Rtm::RtmNewlink);
+        assert!(Rtm::from(libc::RTM_DELLINK as c_int) == Rtm::RtmDellink);
+        assert!(Rtm::from(libc::RTM_GETLINK as c_int) == Rtm::RtmGetlink);
+        assert!(Rtm::from(libc::RTM_SETLINK as c_int) == Rtm::RtmSetlink);
+    }
+
+    #[test]
+    fn test_rtm_invalid_debug() {
+        let rtm = Rtm::from(0x42);
+
+        assert_eq!(format!("{:?}", rtm), "RtmInvalid(\"42\")");
+    }
+}
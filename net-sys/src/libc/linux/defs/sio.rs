@@ -0,0 +1,34 @@
+use libc::c_ulong;
+
+// https://github.com/torvalds/linux/blob/v6.6/include/uapi/linux/sockios.h
+
+// Get hardware address
+pub(crate) const SIOCGIFHWADDR: c_ulong = 0x8927;
+
+// Set hardware address
+pub(crate) const SIOCSIFHWADDR: c_ulong = 0x8924;
+
+// Ethtool interface
+pub(crate) const SIOCETHTOOL: c_ulong = 0x8946;
+
+#[cfg(test)]
+mod tests {
+    use libc::c_ulong;
+
+    use super::{SIOCETHTOOL, SIOCGIFHWADDR, SIOCSIFHWADDR};
+
+    #[test]
+    fn test_get_hardware_addr() {
+        assert_eq!(SIOCGIFHWADDR, libc::SIOCGIFHWADDR as c_ulong)
+    }
+
+    #[test]
+    fn test_set_hardware_addr() {
+        assert_eq!(SIOCSIFHWADDR, libc::SIOCSIFHWADDR as c_ulong)
+    }
+
+    #[test]
+    fn test_ethtool() {
+        assert_eq!(SIOCETHTOOL, libc::SIOCETHTOOL as c_ulong)
+    }
+}
@@ -0,0 +1,588 @@
+use std::collections::VecDeque;
+
+use crate::ifname::IfName;
+use crate::lladdr::LinkLevelAddress;
+use crate::Result;
+
+use super::defs::rtm::Rtm;
+use super::types::ethtool;
+use super::types::ifreq::{self, IfReq, IfReqWith};
+use super::types::nlmsg::{self, LinkEther, LinkState, NlMsgHdr};
+
+#[cfg(not(test))]
+use super::socket;
+#[cfg(test)]
+use mocks::socket;
+
+use super::socket::ReadResult::{EndOfRead, ReadLength};
+
+pub use crate::event::NicEvent;
+pub use crate::info::{NicInfo, NicType};
+
+pub fn monitor() -> Result<NicMonitor> {
+    Ok(NicMonitor {
+        socket: socket::open_route_netlink(libc::RTMGRP_LINK as u32)?,
+        events: VecDeque::new(),
+    })
+}
+
+#[derive(Debug)]
+pub struct NicMonitor {
+    socket: socket::OpenSocket,
+    events: VecDeque<NicEvent>,
+}
+
+// Source: https://man7.org/linux/man-pages/man7/rtnetlink.7.html
+
+impl NicMonitor {
+    fn parse_msg(msg: &NlMsgHdr) -> Option<NicEvent> {
+        let event = match msg.nlmsg_type() {
+            Rtm::RtmNewlink => {
+                let nic = msg.as_ifinfomsg()?.get_link_ether()?;
+                NicEvent::NicNew(nic)
+            }
+            Rtm::RtmDellink => {
+                let nic = msg.as_ifinfomsg()?.get_link_ether()?;
+                NicEvent::NicDel(nic)
+            }
+            Rtm::RtmInvalid(value) => {
+                log::warn!("{:?}", Rtm::RtmInvalid(value));
+                return None;
+            }
+            _ => return None,
+        };
+
+        Some(event)
+    }
+
+    /// Link state an RTM_NEWLINK reports along with the interface, from
+    /// IFLA_OPERSTATE or IFF_RUNNING.
+    fn parse_link(msg: &NlMsgHdr) -> Option<NicEvent> {
+        if msg.nlmsg_type() != Rtm::RtmNewlink {
+            return None;
+        }
+
+        let event = match msg.as_ifinfomsg()?.get_link_state()? {
+            (index, ifname, true) => NicEvent::NicUp((index, ifname)),
+            (index, ifname, false) => NicEvent::NicDown((index, ifname)),
+        };
+
+        Some(event)
+    }
+}
+
+impl Iterator for NicMonitor {
+    type Item = Result<NicEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.events.pop_front() {
+            return Some(Ok(event));
+        }
+
+        let mut nl_buf = nlmsg::new();
+        let event = match self.socket.read(&mut nl_buf) {
+            Ok(ReadLength(len)) => {
+                // A single datagram may carry several link messages, each
+                // followed by the state of its link.
+                self.events = nlmsg::iter(&nl_buf[..len as usize])
+                    .flat_map(|msg| [Self::parse_msg(&msg), Self::parse_link(&msg)])
+                    .flatten()
+                    .collect();
+                Ok(self.events.pop_front().unwrap_or(NicEvent::NicNoop))
+            }
+            Ok(EndOfRead) => return None,
+            Err(err) => Err(err),
+        };
+
+        Some(event)
+    }
+}
+
+pub fn get_lladdr(ifname: &IfName) -> Result<LinkLevelAddress> {
+    let mut ifreq = ifreq::new().with_name(ifname);
+
+    socket::open_local_dgram()?.get_lladdr(&mut ifreq)?;
+
+    Ok(ifreq.lladdr())
+}
+
+/// Reads the permanent address burnt into the device with ETHTOOL_GPERMADDR,
+/// `None` if the device has none, like virtual interfaces.
+pub fn get_perm_lladdr(ifname: &IfName) -> Result<Option<LinkLevelAddress>> {
+    let mut perm_addr = ethtool::perm_addr();
+    let mut ifreq = ifreq::new()
+        .with_name(ifname)
+        .with_data(perm_addr.as_mut_ptr());
+
+    socket::open_local_dgram()?.get_perm_lladdr(&mut ifreq)?;
+
+    Ok(perm_addr.lladdr())
+}
+
+pub fn set_lladdr(ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()> {
+    let mut ifreq = ifreq::new().with_name(ifname).with_lladdr(lladdr);
+
+    socket::open_local_dgram()?.set_lladdr(&mut ifreq)
+}
+
+/// Lists every interface known to the kernel, in the order of the
+/// RTM_GETLINK dump.
+pub fn list() -> Result<Vec<NicInfo>> {
+    socket::open_route_netlink(0)?.get_links()
+}
+
+/// Sets the link-level address over rtnetlink (RTM_SETLINK) instead of the
+/// SIOCSIFHWADDR ioctl, reporting the errno from the kernel ACK. The request
+/// addresses the link by `ifname`, which the kernel looks up itself.
+pub fn set_lladdr_netlink(ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()> {
+    socket::open_route_netlink(0)?.set_lladdr_netlink(ifname, lladdr)
+}
+
+#[cfg(test)]
+pub(crate) mod mocks {
+    pub(crate) mod socket {
+        use mockdown::{mockdown, Mock};
+
+        use crate::ifname::IfName;
+        use crate::info::NicInfo;
+        use crate::libc::linux::socket::ReadResult;
+        use crate::lladdr::LinkLevelAddress;
+        use crate::Result;
+
+        pub(crate) struct OpenLocalDgram(pub fn() -> Result<OpenSocket>);
+        pub(crate) struct OpenRouteNetlink(pub fn(groups: u32) -> Result<OpenSocket>);
+        pub(crate) struct GetLLAddr(pub fn(ifreq: &mut libc::ifreq) -> Result<()>);
+        pub(crate) struct GetPermLLAddr(pub fn(ifreq: &mut libc::ifreq) -> Result<()>);
+        pub(crate) struct SetLLAddr(pub fn(ifreq: &mut libc::ifreq) -> Result<()>);
+        pub(crate) struct Read(pub fn(buf: &mut [u8]) -> Result<ReadResult>);
+        pub(crate) struct GetLinks(pub fn() -> Result<Vec<NicInfo>>);
+        pub(crate) struct SetLLAddrNetlink(
+            pub fn(ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()>,
+        );
+
+        pub(crate) fn open_local_dgram() -> Result<OpenSocket> {
+            mockdown().next(|OpenLocalDgram(mock)| mock()).unwrap()
+        }
+
+        pub(crate) fn open_route_netlink(groups: u32) -> Result<OpenSocket> {
+            mockdown()
+                .next(|OpenRouteNetlink(mock)| mock(groups))
+                .unwrap()
+        }
+
+        #[derive(Debug)]
+        pub(crate) struct OpenSocket();
+
+        impl OpenSocket {
+            pub(crate) fn get_lladdr(&self, ifreq: &mut libc::ifreq) -> Result<()> {
+                mockdown().next(|GetLLAddr(mock)| mock(ifreq)).unwrap()
+            }
+
+            pub(crate) fn get_perm_lladdr(&self, ifreq: &mut libc::ifreq) -> Result<()> {
+                mockdown().next(|GetPermLLAddr(mock)| mock(ifreq)).unwrap()
+            }
+
+            pub(crate) fn set_lladdr(&self, ifreq: &mut libc::ifreq) -> Result<()> {
+                mockdown().next(|SetLLAddr(mock)| mock(ifreq)).unwrap()
+            }
+
+            pub(crate) fn get_links(&self) -> Result<Vec<NicInfo>> {
+                mockdown().next(|GetLinks(mock)| mock()).unwrap()
+            }
+
+            pub(crate) fn set_lladdr_netlink(
+                &self,
+                ifname: &IfName,
+                lladdr: &LinkLevelAddress,
+            ) -> Result<()> {
+                mockdown()
+                    .next(|SetLLAddrNetlink(mock)| mock(ifname, lladdr))
+                    .unwrap()
+            }
+
+            pub(crate) fn read(&self, buf: &mut [u8]) -> Result<ReadResult> {
+                mockdown().next(|Read(mock)| mock(buf)).unwrap()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use mockdown::{mockdown, Mock};
+
+    use crate::error::{ErrorKind, Operation};
+    use crate::ifname::IfName;
+    use crate::lladdr::LinkLevelAddress;
+    use crate::{Error, Result};
+
+    use super::super::socket::ReadResult::{EndOfRead, ReadLength};
+    use super::super::types::ethtool::tests::{fill, PtrAsPermAddr};
+    use super::super::types::ifreq::tests::IfReqData;
+    use super::super::types::ifreq::{IfReq, IfReqMut};
+    use super::super::types::nlmsg::tests::with_operstate;
+    use super::super::types::nlmsg::tests::{RTM_DELLINK_ENX, RTM_NEWLINK_ENX, RTM_NEWLINK_LO};
+    use super::mocks::socket::{self, OpenSocket};
+    use super::NicEvent::{NicDel, NicDown, NicNew, NicNoop, NicUp};
+    use super::{get_lladdr, get_perm_lladdr, list, monitor, set_lladdr, set_lladdr_netlink};
+    use super::{NicInfo, NicType};
+
+    static IFNAME: LazyLock<IfName> = LazyLock::new(|| "enx".try_into().unwrap());
+    static LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "00:11:22:33:44:55".parse().unwrap());
+    static ENX: LazyLock<IfName> = LazyLock::new(|| "enx001122334455".try_into().unwrap());
+
+    fn read_msgs(buf: &mut [u8], msgs: &[&[u8]]) -> Result<super::super::socket::ReadResult> {
+        let msgs = msgs.concat();
+        buf[..msgs.len()].copy_from_slice(&msgs);
+        Ok(ReadLength(msgs.len() as isize))
+    }
+
+    #[test]
+    fn test_get_lladdr() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenLocalDgram(|| Ok(OpenSocket())))
+            .expect(socket::GetLLAddr(|ifreq| {
+                assert_eq!(ifreq.name(), *IFNAME);
+                ifreq.change_lladdr(&LLADDR);
+                Ok(())
+            }));
+
+        let lladdr = get_lladdr(&IFNAME)?;
+
+        assert_eq!(lladdr, *LLADDR);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_lladdr_open_error() {
+        mockdown().expect(socket::OpenLocalDgram(|| {
+            Err(Error::new(Operation::OpenSocket, ErrorKind::Other))
+        }));
+
+        let expected_error = Error::new(Operation::OpenSocket, ErrorKind::Other);
+
+        let error = get_lladdr(&IFNAME).unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_get_lladdr_error() {
+        mockdown()
+            .expect(socket::OpenLocalDgram(|| Ok(OpenSocket())))
+            .expect(socket::GetLLAddr(|ifreq| {
+                assert_eq!(ifreq.name(), *IFNAME);
+                Err(Error::new(Operation::GetLinkLevelAddress, ErrorKind::Other))
+            }));
+
+        let expected_error = Error::new(Operation::GetLinkLevelAddress, ErrorKind::Other);
+
+        let error = get_lladdr(&IFNAME).unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_get_perm_lladdr() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenLocalDgram(|| Ok(OpenSocket())))
+            .expect(socket::GetPermLLAddr(|ifreq| {
+                assert_eq!(ifreq.name(), *IFNAME);
+                fill(ifreq.data().as_perm_addr(), &LLADDR[..]);
+                Ok(())
+            }));
+
+        let lladdr = get_perm_lladdr(&IFNAME)?;
+
+        assert_eq!(lladdr, Some(*LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_perm_lladdr_none() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenLocalDgram(|| Ok(OpenSocket())))
+            .expect(socket::GetPermLLAddr(|ifreq| {
+                fill(ifreq.data().as_perm_addr(), &[0; 6]);
+                Ok(())
+            }));
+
+        let lladdr = get_perm_lladdr(&IFNAME)?;
+
+        assert_eq!(lladdr, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_perm_lladdr_error() {
+        mockdown()
+            .expect(socket::OpenLocalDgram(|| Ok(OpenSocket())))
+            .expect(socket::GetPermLLAddr(|_ifreq| {
+                Err(Error::new(
+                    Operation::GetPermLinkLevelAddress,
+                    ErrorKind::Other,
+                ))
+            }));
+
+        let expected_error = Error::new(Operation::GetPermLinkLevelAddress, ErrorKind::Other);
+
+        let error = get_perm_lladdr(&IFNAME).unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_set_lladdr() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenLocalDgram(|| Ok(OpenSocket())))
+            .expect(socket::SetLLAddr(|ifreq| {
+                assert_eq!(ifreq.name(), *IFNAME);
+                assert_eq!(ifreq.lladdr(), *LLADDR);
+                Ok(())
+            }));
+
+        set_lladdr(&IFNAME, &LLADDR)
+    }
+
+    #[test]
+    fn test_set_lladdr_open_error() {
+        mockdown().expect(socket::OpenLocalDgram(|| {
+            Err(Error::new(Operation::OpenSocket, ErrorKind::Other))
+        }));
+
+        let expected_error = Error::new(Operation::OpenSocket, ErrorKind::Other);
+
+        let error = set_lladdr(&IFNAME, &LLADDR).unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_set_lladdr_error() {
+        mockdown()
+            .expect(socket::OpenLocalDgram(|| Ok(OpenSocket())))
+            .expect(socket::SetLLAddr(|ifreq| {
+                assert_eq!(ifreq.name(), *IFNAME);
+                assert_eq!(ifreq.lladdr(), *LLADDR);
+                Err(Error::new(Operation::SetLinkLevelAddress, ErrorKind::Other))
+            }));
+
+        let expected_error = Error::new(Operation::SetLinkLevelAddress, ErrorKind::Other);
+
+        let error = set_lladdr(&IFNAME, &LLADDR).unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_list() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenRouteNetlink(|groups| {
+                assert_eq!(groups, 0);
+                Ok(OpenSocket())
+            }))
+            .expect(socket::GetLinks(|| {
+                Ok(vec![NicInfo {
+                    index: 7,
+                    ifname: *ENX,
+                    lladdr: Some(*LLADDR),
+                    nic_type: NicType::Ether,
+                    flags: 0x11043,
+                    mtu: 1500,
+                }])
+            }));
+
+        let nics = list()?;
+
+        assert_eq!(nics.len(), 1);
+        assert_eq!(nics[0].ifname, *ENX);
+        assert_eq!(nics[0].lladdr, Some(*LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_open_error() {
+        mockdown().expect(socket::OpenRouteNetlink(|_groups| {
+            Err(Error::new(Operation::OpenSocket, ErrorKind::Other))
+        }));
+
+        let expected_error = Error::new(Operation::OpenSocket, ErrorKind::Other);
+
+        let error = list().unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_list_error() {
+        mockdown()
+            .expect(socket::OpenRouteNetlink(|_groups| Ok(OpenSocket())))
+            .expect(socket::GetLinks(|| {
+                Err(Error::new(Operation::GetLinks, ErrorKind::Other))
+            }));
+
+        let expected_error = Error::new(Operation::GetLinks, ErrorKind::Other);
+
+        let error = list().unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_set_lladdr_netlink() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenRouteNetlink(|groups| {
+                assert_eq!(groups, 0);
+                Ok(OpenSocket())
+            }))
+            .expect(socket::SetLLAddrNetlink(|ifname, lladdr| {
+                assert_eq!(*ifname, *IFNAME);
+                assert_eq!(*lladdr, *LLADDR);
+                Ok(())
+            }));
+
+        set_lladdr_netlink(&IFNAME, &LLADDR)
+    }
+
+    #[test]
+    fn test_set_lladdr_netlink_open_error() {
+        mockdown().expect(socket::OpenRouteNetlink(|_groups| {
+            Err(Error::new(Operation::OpenSocket, ErrorKind::Other))
+        }));
+
+        let expected_error = Error::new(Operation::OpenSocket, ErrorKind::Other);
+
+        let error = set_lladdr_netlink(&IFNAME, &LLADDR).unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_set_lladdr_netlink_error() {
+        mockdown()
+            .expect(socket::OpenRouteNetlink(|_groups| Ok(OpenSocket())))
+            .expect(socket::SetLLAddrNetlink(|_ifname, _lladdr| {
+                Err(Error::new(Operation::SetLinkLevelAddress, ErrorKind::Other))
+            }));
+
+        let expected_error = Error::new(Operation::SetLinkLevelAddress, ErrorKind::Other);
+
+        let error = set_lladdr_netlink(&IFNAME, &LLADDR).unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_monitor_open_error() {
+        mockdown().expect(socket::OpenRouteNetlink(|groups| {
+            assert_eq!(groups, libc::RTMGRP_LINK as u32);
+            Err(Error::new(Operation::OpenSocket, ErrorKind::Other))
+        }));
+
+        let expected_error = Error::new(Operation::OpenSocket, ErrorKind::Other);
+
+        let error = monitor().unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_monitor_new_and_del() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenRouteNetlink(|groups| {
+                assert_eq!(groups, libc::RTMGRP_LINK as u32);
+                Ok(OpenSocket())
+            }))
+            .expect(socket::Read(|buf| read_msgs(buf, &[&RTM_NEWLINK_ENX])))
+            .expect(socket::Read(|buf| read_msgs(buf, &[&RTM_DELLINK_ENX])))
+            .expect(socket::Read(|_buf| Ok(EndOfRead)));
+
+        let events = monitor()?.collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(
+            events,
+            vec![
+                NicNew((7, *ENX, *LLADDR)),
+                NicUp((7, *ENX)),
+                NicDel((7, *ENX, *LLADDR))
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_monitor_multiple_messages_per_read() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenRouteNetlink(|_groups| Ok(OpenSocket())))
+            .expect(socket::Read(|buf| {
+                read_msgs(buf, &[&RTM_NEWLINK_LO, &RTM_NEWLINK_ENX, &RTM_DELLINK_ENX])
+            }))
+            .expect(socket::Read(|_buf| Ok(EndOfRead)));
+
+        let events = monitor()?.collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(events[0], NicNew((7, ..))));
+        assert!(matches!(events[1], NicUp((7, _))));
+        assert!(matches!(events[2], NicDel((7, ..))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_monitor_link_down() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenRouteNetlink(|_groups| Ok(OpenSocket())))
+            .expect(socket::Read(|buf| {
+                // IF_OPER_LOWERLAYERDOWN, the cable unplugged.
+                read_msgs(buf, &[&with_operstate(&RTM_NEWLINK_ENX, 3)])
+            }))
+            .expect(socket::Read(|_buf| Ok(EndOfRead)));
+
+        let events = monitor()?.collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(events, vec![NicNew((7, *ENX, *LLADDR)), NicDown((7, *ENX))]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_monitor_non_ethernet_is_noop() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenRouteNetlink(|_groups| Ok(OpenSocket())))
+            .expect(socket::Read(|buf| read_msgs(buf, &[&RTM_NEWLINK_LO])))
+            .expect(socket::Read(|_buf| Ok(EndOfRead)));
+
+        let events = monitor()?.collect::<Result<Vec<_>>>()?;
+
+        assert_eq!(events.len(), 1);
+        assert!(matches!(events[0], NicNoop));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_monitor_read_error() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenRouteNetlink(|_groups| Ok(OpenSocket())))
+            .expect(socket::Read(|_buf| {
+                Err(Error::new(Operation::Read, ErrorKind::Other))
+            }));
+
+        let expected_error = Error::new(Operation::Read, ErrorKind::Other);
+
+        let error = monitor()?.next().unwrap().unwrap_err();
+
+        assert_eq!(error, expected_error);
+
+        Ok(())
+    }
+}
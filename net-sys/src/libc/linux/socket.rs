@@ -0,0 +1,1106 @@
+use std::fmt::Debug;
+use std::mem;
+
+use libc::{c_int, c_void, ssize_t};
+
+use crate::error::{ErrorKind, Operation};
+use crate::ifname::IfName;
+use crate::info::NicInfo;
+use crate::lladdr::LinkLevelAddress;
+use crate::Result;
+
+use super::defs::rtm::Rtm;
+use super::defs::sio;
+use super::types::ifreq::{IfReq, IfReqAsPtr};
+use super::types::nlmsg::{self, LinkInfo, NlMsgHdr};
+
+#[cfg(not(test))]
+use super::sys;
+#[cfg(test)]
+use mocks::sys;
+
+#[derive(Clone, PartialEq, Eq)]
+enum Error {
+    OpenLocalDgram(c_int, c_int),
+    GetLinkLevelAddress(c_int, IfName, c_int, c_int),
+    GetPermLinkLevelAddress(c_int, IfName, c_int, c_int),
+    SetLinkLevelAddress(c_int, IfName, LinkLevelAddress, c_int, c_int),
+    OpenRouteNetlink(c_int, c_int),
+    Bind(c_int, u32, c_int, c_int),
+    GetLinks(c_int, c_int, c_int),
+    Send(c_int, ssize_t, c_int),
+    Read(c_int, ssize_t, c_int),
+    Truncated(c_int, ssize_t, usize),
+    MissingAck(c_int, u32),
+    Close(c_int, c_int, c_int),
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::OpenLocalDgram(ret, errno) => f
+                .debug_struct("Socket::OpenLocalDgramError")
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+            Error::GetLinkLevelAddress(fd, ifname, ret, errno) => f
+                .debug_struct("Socket::GetLinkLevelAddressError")
+                .field("fd", fd)
+                .field("ifname", ifname)
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+            Error::GetPermLinkLevelAddress(fd, ifname, ret, errno) => f
+                .debug_struct("Socket::GetPermLinkLevelAddressError")
+                .field("fd", fd)
+                .field("ifname", ifname)
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+            Error::SetLinkLevelAddress(fd, ifname, lladdr, ret, errno) => f
+                .debug_struct("Socket::SetLinkLevelAddressError")
+                .field("fd", fd)
+                .field("ifname", ifname)
+                .field("lladdr", lladdr)
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+            Error::OpenRouteNetlink(ret, errno) => f
+                .debug_struct("Socket::OpenRouteNetlinkError")
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+            Error::Bind(fd, groups, ret, errno) => f
+                .debug_struct("Socket::BindError")
+                .field("fd", fd)
+                .field("groups", &format!("0x{:x}", groups))
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+            Error::GetLinks(fd, ret, errno) => f
+                .debug_struct("Socket::GetLinksError")
+                .field("fd", fd)
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+            Error::Send(fd, ret, errno) => f
+                .debug_struct("Socket::SendError")
+                .field("fd", fd)
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+            Error::Read(fd, ret, errno) => f
+                .debug_struct("Socket::ReadError")
+                .field("fd", fd)
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+            Error::Truncated(fd, len, buf_len) => f
+                .debug_struct("Socket::TruncatedError")
+                .field("fd", fd)
+                .field("len", len)
+                .field("buf_len", buf_len)
+                .finish(),
+            Error::MissingAck(fd, seq) => f
+                .debug_struct("Socket::MissingAckError")
+                .field("fd", fd)
+                .field("seq", seq)
+                .finish(),
+            Error::Close(fd, ret, errno) => f
+                .debug_struct("Socket::CloseError")
+                .field("fd", fd)
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+        }
+    }
+}
+
+impl From<Error> for crate::Error {
+    fn from(error: Error) -> Self {
+        let detailed = |operation| crate::Error::detailed(operation, error.errno(), &error);
+        match &error {
+            Error::OpenLocalDgram(_, _) | Error::OpenRouteNetlink(_, _) => {
+                detailed(Operation::OpenSocket)
+            }
+            Error::GetLinkLevelAddress(_, ifname, _, _) => {
+                detailed(Operation::GetLinkLevelAddress).with_ifname(*ifname)
+            }
+            Error::GetPermLinkLevelAddress(_, ifname, _, _) => {
+                detailed(Operation::GetPermLinkLevelAddress).with_ifname(*ifname)
+            }
+            Error::SetLinkLevelAddress(_, ifname, lladdr, _, _) => {
+                detailed(Operation::SetLinkLevelAddress)
+                    .with_ifname(*ifname)
+                    .with_lladdr(*lladdr)
+            }
+            Error::Bind(_, _, _, _) => detailed(Operation::Bind),
+            Error::GetLinks(_, _, _) => detailed(Operation::GetLinks),
+            Error::Send(_, _, _) => detailed(Operation::Send),
+            Error::Read(_, _, _) | Error::MissingAck(_, _) => detailed(Operation::Read),
+            Error::Truncated(_, _, _) => detailed(Operation::Read).with_kind(ErrorKind::Decode),
+            Error::Close(_, _, _) => detailed(Operation::Close),
+        }
+    }
+}
+
+impl Error {
+    fn errno(&self) -> Option<c_int> {
+        match self {
+            Error::OpenLocalDgram(_, errno)
+            | Error::GetLinkLevelAddress(_, _, _, errno)
+            | Error::GetPermLinkLevelAddress(_, _, _, errno)
+            | Error::SetLinkLevelAddress(_, _, _, _, errno)
+            | Error::OpenRouteNetlink(_, errno)
+            | Error::Bind(_, _, _, errno)
+            | Error::GetLinks(_, _, errno)
+            | Error::Send(_, _, errno)
+            | Error::Read(_, _, errno)
+            | Error::Close(_, _, errno) => Some(*errno),
+            Error::Truncated(_, _, _) | Error::MissingAck(_, _) => None,
+        }
+    }
+}
+
+pub(crate) fn open_local_dgram() -> Result<OpenSocket> {
+    let ty = libc::SOCK_DGRAM | libc::SOCK_CLOEXEC;
+    match sys::socket(libc::PF_LOCAL, ty, 0) {
+        fd if fd >= 0 => Ok(OpenSocket { fd }),
+        ret => {
+            let errno = sys::errno();
+            Err(Error::OpenLocalDgram(ret, errno).into())
+        }
+    }
+}
+
+pub(crate) fn open_route_netlink(groups: u32) -> Result<OpenSocket> {
+    let domain = libc::AF_NETLINK;
+    let ty = libc::SOCK_RAW | libc::SOCK_CLOEXEC;
+    let socket = match sys::socket(domain, ty, libc::NETLINK_ROUTE) {
+        fd if fd >= 0 => OpenSocket { fd },
+        ret => {
+            let errno = sys::errno();
+            return Err(Error::OpenRouteNetlink(ret, errno).into());
+        }
+    };
+
+    socket.bind(groups)?;
+
+    Ok(socket)
+}
+
+// Every netlink request is sent on its own socket, so a fixed sequence
+// number is enough to match the ACK.
+const NLMSG_SEQ: u32 = 1;
+
+#[derive(Debug)]
+pub enum ReadResult {
+    ReadLength(ssize_t),
+    EndOfRead,
+}
+
+#[derive(Debug)]
+pub(crate) struct OpenSocket {
+    fd: c_int,
+}
+
+impl OpenSocket {
+    fn bind(&self, groups: u32) -> Result<()> {
+        let fd = self.fd;
+        let mut addr: libc::sockaddr_nl = unsafe { mem::zeroed() };
+        addr.nl_family = libc::AF_NETLINK as libc::sa_family_t;
+        addr.nl_groups = groups;
+
+        let len = size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+        match sys::bind(fd, (&addr as *const libc::sockaddr_nl).cast(), len) {
+            0 => Ok(()),
+            ret => {
+                let errno = sys::errno();
+                Err(Error::Bind(fd, groups, ret, errno).into())
+            }
+        }
+    }
+
+    pub(crate) fn get_lladdr(&self, ifreq: &mut libc::ifreq) -> Result<()> {
+        let fd = self.fd;
+        match sys::ioctl(fd, sio::SIOCGIFHWADDR, ifreq.as_mut_ptr()) {
+            0 => Ok(()),
+            ret => {
+                let ifname = ifreq.name();
+                let errno = sys::errno();
+                Err(Error::GetLinkLevelAddress(fd, ifname, ret, errno).into())
+            }
+        }
+    }
+
+    /// Issues ETHTOOL_GPERMADDR, `ifreq` pointing at an `EthtoolPermAddr`
+    /// with its `ifru_data`.
+    pub(crate) fn get_perm_lladdr(&self, ifreq: &mut libc::ifreq) -> Result<()> {
+        let fd = self.fd;
+        match sys::ioctl(fd, sio::SIOCETHTOOL, ifreq.as_mut_ptr()) {
+            0 => Ok(()),
+            ret => {
+                let ifname = ifreq.name();
+                let errno = sys::errno();
+                Err(Error::GetPermLinkLevelAddress(fd, ifname, ret, errno).into())
+            }
+        }
+    }
+
+    pub(crate) fn set_lladdr(&self, ifreq: &mut libc::ifreq) -> Result<()> {
+        let fd = self.fd;
+        match sys::ioctl(fd, sio::SIOCSIFHWADDR, ifreq.as_mut_ptr()) {
+            0 => Ok(()),
+            ret => {
+                let ifname = ifreq.name();
+                let lladdr = ifreq.lladdr();
+                let errno = sys::errno();
+                Err(Error::SetLinkLevelAddress(fd, ifname, lladdr, ret, errno).into())
+            }
+        }
+    }
+
+    /// Sends RTM_SETLINK with IFLA_ADDRESS for the link named `ifname`, and
+    /// waits for the kernel ACK.
+    ///
+    /// A negative `nlmsgerr.error` is reported as `SetLinkLevelAddressError`,
+    /// with `ret` holding the raw value and `errno` its negation.
+    pub(crate) fn set_lladdr_netlink(
+        &self,
+        ifname: &IfName,
+        lladdr: &LinkLevelAddress,
+    ) -> Result<()> {
+        let fd = self.fd;
+        self.send(&nlmsg::setlink_lladdr(NLMSG_SEQ, &ifname[..], lladdr))?;
+
+        self.recv_reply(|msg| match msg.as_nlmsgerr()? {
+            0 => Some(Ok(())),
+            ret => {
+                let error = Error::SetLinkLevelAddress(fd, *ifname, *lladdr, ret, -ret);
+                Some(Err(error.into()))
+            }
+        })
+    }
+
+    /// Dumps every link with RTM_GETLINK, collecting the RTM_NEWLINK replies
+    /// until NLMSG_DONE.
+    pub(crate) fn get_links(&self) -> Result<Vec<NicInfo>> {
+        let fd = self.fd;
+        self.send(&nlmsg::getlink_dump(NLMSG_SEQ))?;
+
+        let mut links = Vec::new();
+        self.recv_reply(|msg| match msg.nlmsg_type() {
+            Rtm::RtmNewlink => {
+                links.extend(msg.as_ifinfomsg().and_then(|link| link.get_link_info()));
+                None
+            }
+            Rtm::NlmsgDone => Some(Ok(())),
+            Rtm::NlmsgError => match msg.as_nlmsgerr()? {
+                0 => None,
+                ret => Some(Err(Error::GetLinks(fd, ret, -ret).into())),
+            },
+            _ => None,
+        })?;
+
+        Ok(links)
+    }
+
+    /// Reads the replies to `NLMSG_SEQ` until `handle` returns a result.
+    fn recv_reply<T>(&self, mut handle: impl FnMut(&NlMsgHdr) -> Option<Result<T>>) -> Result<T> {
+        let fd = self.fd;
+        let mut nl_buf = nlmsg::new();
+        loop {
+            let len = match self.read(&mut nl_buf)? {
+                ReadResult::ReadLength(len) => len as usize,
+                ReadResult::EndOfRead => return Err(Error::MissingAck(fd, NLMSG_SEQ).into()),
+            };
+
+            let result = nlmsg::iter(&nl_buf[..len])
+                .filter(|msg| msg.nlmsg_seq == NLMSG_SEQ)
+                .find_map(|msg| handle(&msg));
+
+            if let Some(result) = result {
+                return result;
+            }
+        }
+    }
+
+    fn send(&self, buf: &[u8]) -> Result<()> {
+        let fd = self.fd;
+        match sys::send(fd, buf.as_ptr() as *const c_void, buf.len(), 0) {
+            ret if ret < 0 => {
+                let errno = sys::errno();
+                Err(Error::Send(fd, ret, errno).into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Reads a single datagram. With MSG_TRUNC, the kernel reports the
+    /// full length of one that did not fit in `buf`, which is then dropped
+    /// as `Socket::TruncatedError`.
+    pub(crate) fn read(&self, buf: &mut [u8]) -> Result<ReadResult> {
+        let fd = self.fd;
+        let len = buf.len();
+        match sys::recv(fd, buf.as_mut_ptr() as *mut c_void, len, libc::MSG_TRUNC) {
+            0 => Ok(ReadResult::EndOfRead),
+            ret if ret < 0 => {
+                let errno = sys::errno();
+                Err(Error::Read(fd, ret, errno).into())
+            }
+            ret if ret as usize > len => Err(Error::Truncated(fd, ret, len).into()),
+            ret => Ok(ReadResult::ReadLength(ret)),
+        }
+    }
+}
+
+impl Drop for OpenSocket {
+    fn drop(&mut self) {
+        let fd = self.fd;
+        match sys::close(fd) {
+            0 => (),
+            ret => {
+                let errno = sys::errno();
+                let error = Error::Close(fd, ret, errno);
+                log::error!("{:?}", error);
+            }
+        };
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod mocks {
+    pub(crate) mod sys {
+        use libc::{c_int, c_ulong, c_void, size_t, sockaddr, socklen_t, ssize_t};
+
+        use mockdown::{mockdown, Mock};
+
+        use super::super::super::sys;
+
+        pub(crate) use sys::strerror;
+
+        pub(crate) struct Socket(pub fn(domain: c_int, ty: c_int, protocol: c_int) -> c_int);
+        pub(crate) struct Ioctl(pub fn(fd: c_int, request: c_ulong, arg: *mut c_void) -> c_int);
+        pub(crate) struct Bind(pub fn(fd: c_int, addr: *const sockaddr, len: socklen_t) -> c_int);
+        pub(crate) struct Send(
+            pub fn(fd: c_int, buf: *const c_void, len: size_t, flags: c_int) -> ssize_t,
+        );
+        pub(crate) struct Recv(
+            pub fn(fd: c_int, buf: *mut c_void, len: size_t, flags: c_int) -> ssize_t,
+        );
+        pub(crate) struct Close(pub fn(fd: c_int) -> c_int);
+        pub(crate) struct ErrNo(pub fn() -> c_int);
+
+        pub(crate) fn socket(domain: c_int, ty: c_int, protocol: c_int) -> c_int {
+            mockdown()
+                .next(|Socket(mock)| mock(domain, ty, protocol))
+                .unwrap()
+        }
+
+        pub(crate) fn ioctl(fd: c_int, request: c_ulong, arg: *mut c_void) -> c_int {
+            mockdown()
+                .next(|Ioctl(mock)| mock(fd, request, arg))
+                .unwrap()
+        }
+
+        pub(crate) fn bind(fd: c_int, addr: *const sockaddr, len: socklen_t) -> c_int {
+            mockdown().next(|Bind(mock)| mock(fd, addr, len)).unwrap()
+        }
+
+        pub(crate) fn send(fd: c_int, buf: *const c_void, len: size_t, flags: c_int) -> ssize_t {
+            mockdown()
+                .next(|Send(mock)| mock(fd, buf, len, flags))
+                .unwrap()
+        }
+
+        pub(crate) fn recv(fd: c_int, buf: *mut c_void, len: size_t, flags: c_int) -> ssize_t {
+            mockdown()
+                .next(|Recv(mock)| mock(fd, buf, len, flags))
+                .unwrap()
+        }
+
+        pub(crate) fn close(fd: c_int) -> c_int {
+            mockdown().next(|Close(mock)| mock(fd)).unwrap()
+        }
+
+        pub(crate) fn errno() -> c_int {
+            mockdown().next(|ErrNo(mock)| mock()).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+
+    use libc::c_int;
+    use mockdown::{mockdown, Mock};
+
+    use crate::error::{ErrorKind, Operation};
+    use crate::ifname::IfName;
+    use crate::lladdr::LinkLevelAddress;
+    use crate::Result;
+
+    use super::super::defs::ethtool::ETHTOOL_GPERMADDR;
+    use super::super::defs::rtm::Rtm;
+    use super::super::defs::sio;
+    use super::super::types::ethtool::{self, tests::fill, tests::PtrAsPermAddr};
+    use super::super::types::ifreq::tests::{IfReqData, PtrAsIfReq};
+    use super::super::types::ifreq::{self, IfReq, IfReqMut, IfReqWith};
+    use super::super::types::nlmsg::tests::{
+        with_seq, NLMSG_ACK, NLMSG_DONE, NLMSG_ERROR_EBUSY, RTM_NEWLINK_LO,
+    };
+    use super::super::types::nlmsg::{self, tests::RTM_NEWLINK_ENX};
+    use super::ReadResult::{EndOfRead, ReadLength};
+    use super::{open_local_dgram, open_route_netlink, OpenSocket};
+
+    use super::mocks::sys;
+
+    const MOCK_FD: c_int = 3;
+    const MOCK_SUCCESS: c_int = 0;
+    const MOCK_FAILURE: c_int = -1;
+    const MOCK_SOCKET: (c_int, c_int, c_int) =
+        (libc::AF_LOCAL, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0);
+    const MOCK_NETLINK: (c_int, c_int, c_int) = (
+        libc::AF_NETLINK,
+        libc::SOCK_RAW | libc::SOCK_CLOEXEC,
+        libc::NETLINK_ROUTE,
+    );
+    const MOCK_GROUPS: u32 = libc::RTMGRP_LINK as u32;
+
+    static IFNAME: LazyLock<IfName> = LazyLock::new(|| "enx".try_into().unwrap());
+    static LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "00:11:22:33:44:55".parse().unwrap());
+
+    fn read_into(buf: *mut libc::c_void, count: usize, msg: &[u8]) -> isize {
+        assert!(count >= msg.len());
+        unsafe { std::ptr::copy_nonoverlapping(msg.as_ptr(), buf as *mut u8, msg.len()) };
+        msg.len() as isize
+    }
+
+    fn assert_setlink(fd: c_int, buf: *const libc::c_void, len: usize, flags: c_int) {
+        let buf = unsafe { std::slice::from_raw_parts(buf as *const u8, len) };
+        let msg = nlmsg::iter(buf).next().unwrap();
+        let ifinfomsg = msg.as_ifinfomsg().unwrap();
+        assert_eq!((MOCK_FD, 0), (fd, flags));
+        assert!(msg.nlmsg_type() == Rtm::RtmSetlink);
+        assert_eq!(ifinfomsg.ifi_index, 0);
+        assert_eq!(ifinfomsg.ifname(), Some(*IFNAME));
+        assert_eq!(ifinfomsg.lladdr(), Some(*LLADDR));
+    }
+
+    #[test]
+    fn test_socket_open_local_dgram() -> Result<()> {
+        const FD: c_int = 10;
+
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_SOCKET, (domain, ty, protocol));
+                FD
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_open_socket = "OpenSocket { fd: 10 }";
+
+        let open_socket = open_local_dgram()?;
+
+        assert_eq!(format!("{:?}", open_socket), expected_open_socket);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_socket_open_local_dgram_error() {
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_SOCKET, (domain, ty, protocol));
+                MOCK_FAILURE
+            }))
+            .expect(sys::ErrNo(|| libc::EPERM));
+
+        let expected_error = "Socket::OpenLocalDgramError { ret: -1, errno: 1, strerror: \"Operation not permitted\" }";
+
+        let error = open_local_dgram().unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+        assert_eq!(error.errno, Some(libc::EPERM));
+    }
+
+    #[test]
+    fn test_open_socket_debug() {
+        mockdown().expect(sys::Close(|fd| {
+            assert_eq!(MOCK_FD, fd);
+            MOCK_SUCCESS
+        }));
+
+        let expected_debug = "OpenSocket { fd: 3 }";
+
+        let box_open_socket = OpenSocket { fd: MOCK_FD };
+
+        assert_eq!(format!("{:?}", box_open_socket), expected_debug);
+    }
+
+    #[test]
+    fn test_open_socket_get_lladdr() -> Result<()> {
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_SOCKET, (domain, ty, protocol));
+                MOCK_FD
+            }))
+            .expect(sys::Ioctl(|fd, request, arg| {
+                assert_eq!((MOCK_FD, sio::SIOCGIFHWADDR), (fd, request));
+                assert_eq!(arg.as_ifreq().name(), *IFNAME);
+                arg.as_ifreq().change_lladdr(&LLADDR);
+                MOCK_SUCCESS
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let mut ifreq = ifreq::new().with_name(&IFNAME);
+
+        open_local_dgram()?.get_lladdr(&mut ifreq)?;
+
+        assert_eq!(ifreq.lladdr(), *LLADDR);
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_socket_get_lladdr_error() -> Result<()> {
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_SOCKET, (domain, ty, protocol));
+                MOCK_FD
+            }))
+            .expect(sys::Ioctl(|fd, request, arg| {
+                assert_eq!((MOCK_FD, sio::SIOCGIFHWADDR), (fd, request));
+                assert_eq!(arg.as_ifreq().name(), *IFNAME);
+                MOCK_FAILURE
+            }))
+            .expect(sys::ErrNo(|| libc::ENODEV))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_error = "Socket::GetLinkLevelAddressError { fd: 3, ifname: \"enx\", ret: -1, errno: 19, strerror: \"No such device\" }";
+        let mut ifreq = ifreq::new().with_name(&IFNAME);
+
+        let error = open_local_dgram()?.get_lladdr(&mut ifreq).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_socket_get_perm_lladdr() -> Result<()> {
+        mockdown()
+            .expect(sys::Ioctl(|fd, request, arg| {
+                let perm_addr = arg.as_ifreq().data().as_perm_addr();
+                assert_eq!((MOCK_FD, sio::SIOCETHTOOL), (fd, request));
+                assert_eq!(arg.as_ifreq().name(), *IFNAME);
+                assert_eq!(perm_addr.cmd, ETHTOOL_GPERMADDR);
+                fill(perm_addr, &LLADDR[..]);
+                MOCK_SUCCESS
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let mut perm_addr = ethtool::perm_addr();
+        let mut ifreq = ifreq::new()
+            .with_name(&IFNAME)
+            .with_data(perm_addr.as_mut_ptr());
+
+        OpenSocket { fd: MOCK_FD }.get_perm_lladdr(&mut ifreq)?;
+
+        assert_eq!(perm_addr.lladdr(), Some(*LLADDR));
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_socket_get_perm_lladdr_error() {
+        mockdown()
+            .expect(sys::Ioctl(|fd, request, _arg| {
+                assert_eq!((MOCK_FD, sio::SIOCETHTOOL), (fd, request));
+                MOCK_FAILURE
+            }))
+            .expect(sys::ErrNo(|| libc::EOPNOTSUPP))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_error = "Socket::GetPermLinkLevelAddressError { fd: 3, ifname: \"enx\", ret: -1, errno: 95, strerror: \"Operation not supported\" }";
+        let mut perm_addr = ethtool::perm_addr();
+        let mut ifreq = ifreq::new()
+            .with_name(&IFNAME)
+            .with_data(perm_addr.as_mut_ptr());
+
+        let error = OpenSocket { fd: MOCK_FD }
+            .get_perm_lladdr(&mut ifreq)
+            .unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+        assert_eq!(error.errno, Some(libc::EOPNOTSUPP));
+    }
+
+    #[test]
+    fn test_open_socket_set_lladdr() -> Result<()> {
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_SOCKET, (domain, ty, protocol));
+                MOCK_FD
+            }))
+            .expect(sys::Ioctl(|fd, request, arg| {
+                assert_eq!((MOCK_FD, sio::SIOCSIFHWADDR), (fd, request));
+                assert_eq!(arg.as_ifreq().name(), *IFNAME);
+                assert_eq!(arg.as_ifreq().lladdr(), *LLADDR);
+                MOCK_SUCCESS
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let mut ifreq = ifreq::new().with_name(&IFNAME).with_lladdr(&LLADDR);
+
+        open_local_dgram()?.set_lladdr(&mut ifreq)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_socket_set_lladdr_error() -> Result<()> {
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_SOCKET, (domain, ty, protocol));
+                MOCK_FD
+            }))
+            .expect(sys::Ioctl(|fd, request, arg| {
+                assert_eq!((MOCK_FD, sio::SIOCSIFHWADDR), (fd, request));
+                assert_eq!(arg.as_ifreq().name(), *IFNAME);
+                assert_eq!(arg.as_ifreq().lladdr(), *LLADDR);
+                MOCK_FAILURE
+            }))
+            .expect(sys::ErrNo(|| libc::EBUSY))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_error = "Socket::SetLinkLevelAddressError { fd: 3, ifname: \"enx\", lladdr: \"00:11:22:33:44:55\", ret: -1, errno: 16, strerror: \"Device or resource busy\" }";
+        let mut ifreq = ifreq::new().with_name(&IFNAME).with_lladdr(&LLADDR);
+
+        let error = open_local_dgram()?.set_lladdr(&mut ifreq).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+
+        assert_eq!(error.operation, Operation::SetLinkLevelAddress);
+        assert_eq!((error.ifname, error.lladdr), (Some(*IFNAME), Some(*LLADDR)));
+        assert_eq!(error.kind(), ErrorKind::Busy);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_socket_close() -> Result<()> {
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_SOCKET, (domain, ty, protocol));
+                MOCK_FD
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let open_socket = open_local_dgram()?;
+
+        drop(open_socket);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_socket_close_error() -> crate::Result<()> {
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_SOCKET, (domain, ty, protocol));
+                MOCK_FD
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_FAILURE
+            }))
+            .expect(sys::ErrNo(|| libc::EINTR));
+
+        let open_socket = open_local_dgram()?;
+
+        drop(open_socket);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_socket_open_route_netlink() -> Result<()> {
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_NETLINK, (domain, ty, protocol));
+                MOCK_FD
+            }))
+            .expect(sys::Bind(|fd, addr, len| {
+                let addr = unsafe { &*(addr as *const libc::sockaddr_nl) };
+                assert_eq!(MOCK_FD, fd);
+                assert_eq!(addr.nl_family, libc::AF_NETLINK as libc::sa_family_t);
+                assert_eq!(addr.nl_pid, 0);
+                assert_eq!(addr.nl_groups, MOCK_GROUPS);
+                assert_eq!(len as usize, size_of::<libc::sockaddr_nl>());
+                MOCK_SUCCESS
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_open_socket = "OpenSocket { fd: 3 }";
+
+        let open_socket = open_route_netlink(MOCK_GROUPS)?;
+
+        assert_eq!(format!("{:?}", open_socket), expected_open_socket);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_socket_open_route_netlink_error() {
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_NETLINK, (domain, ty, protocol));
+                MOCK_FAILURE
+            }))
+            .expect(sys::ErrNo(|| libc::EPROTONOSUPPORT));
+
+        let expected_error = "Socket::OpenRouteNetlinkError { ret: -1, errno: 93, strerror: \"Protocol not supported\" }";
+
+        let error = open_route_netlink(MOCK_GROUPS).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+    }
+
+    #[test]
+    fn test_socket_open_route_netlink_bind_error() {
+        mockdown()
+            .expect(sys::Socket(|domain, ty, protocol| {
+                assert_eq!(MOCK_NETLINK, (domain, ty, protocol));
+                MOCK_FD
+            }))
+            .expect(sys::Bind(|fd, _addr, _len| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_FAILURE
+            }))
+            .expect(sys::ErrNo(|| libc::EPERM))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_error = "Socket::BindError { fd: 3, groups: \"0x1\", ret: -1, errno: 1, strerror: \"Operation not permitted\" }";
+
+        let error = open_route_netlink(MOCK_GROUPS).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+    }
+
+    #[test]
+    fn test_open_socket_read() -> Result<()> {
+        mockdown()
+            .expect(sys::Recv(|fd, buf, count, flags| {
+                assert_eq!((MOCK_FD, libc::MSG_TRUNC), (fd, flags));
+                assert!(count >= RTM_NEWLINK_ENX.len());
+                unsafe {
+                    std::ptr::copy_nonoverlapping(
+                        RTM_NEWLINK_ENX.as_ptr(),
+                        buf as *mut u8,
+                        RTM_NEWLINK_ENX.len(),
+                    )
+                };
+                RTM_NEWLINK_ENX.len() as isize
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let mut nl_buf = nlmsg::new();
+
+        let result = OpenSocket { fd: MOCK_FD }.read(&mut nl_buf)?;
+
+        assert!(matches!(result, ReadLength(84)));
+        assert_eq!(nl_buf[..RTM_NEWLINK_ENX.len()], RTM_NEWLINK_ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_socket_read_end_of_read() -> Result<()> {
+        mockdown()
+            .expect(sys::Recv(|fd, _buf, _count, _flags| {
+                assert_eq!(MOCK_FD, fd);
+                0
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let mut nl_buf = nlmsg::new();
+
+        let result = OpenSocket { fd: MOCK_FD }.read(&mut nl_buf)?;
+
+        assert!(matches!(result, EndOfRead));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_socket_read_truncated() {
+        mockdown()
+            .expect(sys::Recv(|_fd, _buf, count, _flags| count as isize + 1))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_error = format!(
+            "Socket::TruncatedError {{ fd: 3, len: {}, buf_len: {} }}",
+            nlmsg::new().len() + 1,
+            nlmsg::new().len()
+        );
+        let mut nl_buf = nlmsg::new();
+
+        let error = OpenSocket { fd: MOCK_FD }.read(&mut nl_buf).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(error.kind(), ErrorKind::Decode);
+        assert_eq!(error.errno, None);
+    }
+
+    #[test]
+    fn test_open_socket_read_error() {
+        mockdown()
+            .expect(sys::Recv(|fd, _buf, _count, _flags| {
+                assert_eq!(MOCK_FD, fd);
+                -1
+            }))
+            .expect(sys::ErrNo(|| libc::ENOBUFS))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_error = "Socket::ReadError { fd: 3, ret: -1, errno: 105, strerror: \"No buffer space available\" }";
+        let mut nl_buf = nlmsg::new();
+
+        let error = OpenSocket { fd: MOCK_FD }.read(&mut nl_buf).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+    }
+
+    #[test]
+    fn test_open_socket_set_lladdr_netlink() -> Result<()> {
+        mockdown()
+            .expect(sys::Send(|fd, buf, len, flags| {
+                assert_setlink(fd, buf, len, flags);
+                len as isize
+            }))
+            .expect(sys::Recv(|_fd, buf, count, _flags| {
+                read_into(buf, count, &NLMSG_ACK)
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        OpenSocket { fd: MOCK_FD }.set_lladdr_netlink(&IFNAME, &LLADDR)
+    }
+
+    #[test]
+    fn test_open_socket_set_lladdr_netlink_skips_other_messages() -> Result<()> {
+        mockdown()
+            .expect(sys::Send(|_fd, _buf, len, _flags| len as isize))
+            .expect(sys::Recv(|_fd, buf, count, _flags| {
+                read_into(buf, count, &RTM_NEWLINK_LO)
+            }))
+            .expect(sys::Recv(|_fd, buf, count, _flags| {
+                read_into(buf, count, &NLMSG_ACK)
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        OpenSocket { fd: MOCK_FD }.set_lladdr_netlink(&IFNAME, &LLADDR)
+    }
+
+    #[test]
+    fn test_open_socket_set_lladdr_netlink_error() {
+        mockdown()
+            .expect(sys::Send(|_fd, _buf, len, _flags| len as isize))
+            .expect(sys::Recv(|_fd, buf, count, _flags| {
+                read_into(buf, count, &NLMSG_ERROR_EBUSY)
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_error = "Socket::SetLinkLevelAddressError { fd: 3, ifname: \"enx\", lladdr: \"00:11:22:33:44:55\", ret: -16, errno: 16, strerror: \"Device or resource busy\" }";
+
+        let error = OpenSocket { fd: MOCK_FD }
+            .set_lladdr_netlink(&IFNAME, &LLADDR)
+            .unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+    }
+
+    #[test]
+    fn test_open_socket_set_lladdr_netlink_send_error() {
+        mockdown()
+            .expect(sys::Send(|_fd, _buf, _len, _flags| -1))
+            .expect(sys::ErrNo(|| libc::ENOBUFS))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_error = "Socket::SendError { fd: 3, ret: -1, errno: 105, strerror: \"No buffer space available\" }";
+
+        let error = OpenSocket { fd: MOCK_FD }
+            .set_lladdr_netlink(&IFNAME, &LLADDR)
+            .unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+    }
+
+    #[test]
+    fn test_open_socket_set_lladdr_netlink_missing_ack() {
+        mockdown()
+            .expect(sys::Send(|_fd, _buf, len, _flags| len as isize))
+            .expect(sys::Recv(|_fd, _buf, _count, _flags| 0))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_error = "Socket::MissingAckError { fd: 3, seq: 1 }";
+
+        let error = OpenSocket { fd: MOCK_FD }
+            .set_lladdr_netlink(&IFNAME, &LLADDR)
+            .unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+        assert_eq!(error.errno, None);
+    }
+
+    #[test]
+    fn test_open_socket_get_links() -> Result<()> {
+        mockdown()
+            .expect(sys::Send(|fd, buf, len, _flags| {
+                let buf = unsafe { std::slice::from_raw_parts(buf as *const u8, len) };
+                let msg = nlmsg::iter(buf).next().unwrap();
+                assert_eq!(MOCK_FD, fd);
+                assert!(msg.nlmsg_type() == Rtm::RtmGetlink);
+                assert_eq!(
+                    msg.nlmsg_flags as c_int,
+                    libc::NLM_F_REQUEST | libc::NLM_F_DUMP
+                );
+                len as isize
+            }))
+            .expect(sys::Recv(|_fd, buf, count, _flags| {
+                let msgs = [with_seq(&RTM_NEWLINK_LO, 1), with_seq(&RTM_NEWLINK_ENX, 1)];
+                read_into(buf, count, &msgs.concat())
+            }))
+            .expect(sys::Recv(|_fd, buf, count, _flags| {
+                read_into(buf, count, &NLMSG_DONE)
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let links = OpenSocket { fd: MOCK_FD }.get_links()?;
+
+        assert_eq!(links.len(), 2);
+        assert_eq!((links[0].index, links[1].index), (1, 7));
+        assert_eq!(links[1].lladdr, Some(*LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_socket_get_links_ignores_other_seq() -> Result<()> {
+        mockdown()
+            .expect(sys::Send(|_fd, _buf, len, _flags| len as isize))
+            .expect(sys::Recv(|_fd, buf, count, _flags| {
+                let msgs = [&RTM_NEWLINK_LO[..], &NLMSG_DONE[..]];
+                read_into(buf, count, &msgs.concat())
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let links = OpenSocket { fd: MOCK_FD }.get_links()?;
+
+        assert!(links.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_socket_get_links_error() {
+        mockdown()
+            .expect(sys::Send(|_fd, _buf, len, _flags| len as isize))
+            .expect(sys::Recv(|_fd, buf, count, _flags| {
+                read_into(buf, count, &NLMSG_ERROR_EBUSY)
+            }))
+            .expect(sys::Close(|fd| {
+                assert_eq!(MOCK_FD, fd);
+                MOCK_SUCCESS
+            }));
+
+        let expected_error = "Socket::GetLinksError { fd: 3, ret: -16, errno: 16, strerror: \"Device or resource busy\" }";
+
+        let error = OpenSocket { fd: MOCK_FD }.get_links().unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+    }
+}
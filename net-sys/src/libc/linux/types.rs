@@ -0,0 +1,3 @@
+pub(crate) mod ethtool;
+pub(crate) mod ifreq;
+pub(crate) mod nlmsg;
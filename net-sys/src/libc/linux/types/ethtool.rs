@@ -0,0 +1,119 @@
+use libc::c_void;
+
+use crate::lladdr::LinkLevelAddress;
+
+use super::super::defs::ethtool::{ETHTOOL_GPERMADDR, MAX_ADDR_LEN};
+
+// https://github.com/torvalds/linux/blob/v6.6/include/uapi/linux/ethtool.h#L1132
+
+#[repr(C)]
+#[derive(Debug)]
+pub(crate) struct EthtoolPermAddr {
+    pub(crate) cmd: u32,
+    pub(crate) size: u32,
+    pub(crate) data: [u8; MAX_ADDR_LEN],
+}
+
+pub(crate) fn perm_addr() -> EthtoolPermAddr {
+    EthtoolPermAddr {
+        cmd: ETHTOOL_GPERMADDR,
+        size: MAX_ADDR_LEN as u32,
+        data: [0; MAX_ADDR_LEN],
+    }
+}
+
+impl EthtoolPermAddr {
+    /// Permanent address filled in by the kernel, `None` unless it is a
+    /// non-zero 6-octet address.
+    pub(crate) fn lladdr(&self) -> Option<LinkLevelAddress> {
+        let octets: &[u8; 6] = self.data.get(..self.size as usize)?.try_into().ok()?;
+        match octets.iter().any(|&octet| octet != 0) {
+            true => Some(LinkLevelAddress::from(octets)),
+            false => None,
+        }
+    }
+
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut c_void {
+        (self as *mut EthtoolPermAddr).cast()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use libc::c_void;
+
+    use crate::lladdr::LinkLevelAddress;
+
+    use super::super::super::defs::ethtool::ETHTOOL_GPERMADDR;
+    use super::{perm_addr, EthtoolPermAddr};
+
+    const PERM_ADDR_SIZE: usize = 40;
+    const LLADDR: [u8; 6] = [0x00, 0xe0, 0x4c, 0x68, 0x00, 0x01];
+
+    pub(crate) trait PtrAsPermAddr {
+        fn as_perm_addr<'a>(&self) -> &'a mut EthtoolPermAddr;
+    }
+
+    impl PtrAsPermAddr for *mut c_void {
+        fn as_perm_addr<'a>(&self) -> &'a mut EthtoolPermAddr {
+            unsafe { &mut *(*self as *mut EthtoolPermAddr) }
+        }
+    }
+
+    pub(crate) fn fill(perm_addr: &mut EthtoolPermAddr, lladdr: &[u8]) {
+        perm_addr.size = lladdr.len() as u32;
+        perm_addr.data[..lladdr.len()].copy_from_slice(lladdr);
+    }
+
+    #[test]
+    fn test_perm_addr_size() {
+        assert_eq!(size_of::<EthtoolPermAddr>(), PERM_ADDR_SIZE);
+    }
+
+    #[test]
+    fn test_perm_addr_new() {
+        let perm_addr = perm_addr();
+
+        assert_eq!(perm_addr.cmd, ETHTOOL_GPERMADDR);
+        assert_eq!(perm_addr.size, 32);
+        assert_eq!(perm_addr.lladdr(), None);
+    }
+
+    #[test]
+    fn test_perm_addr_lladdr() {
+        let mut perm_addr = perm_addr();
+
+        fill(&mut perm_addr, &LLADDR);
+
+        assert_eq!(perm_addr.lladdr(), Some(LinkLevelAddress::from(&LLADDR)));
+    }
+
+    #[test]
+    fn test_perm_addr_lladdr_zero() {
+        let mut perm_addr = perm_addr();
+
+        fill(&mut perm_addr, &[0; 6]);
+
+        assert_eq!(perm_addr.lladdr(), None);
+    }
+
+    #[test]
+    fn test_perm_addr_lladdr_wrong_size() {
+        let mut perm_addr = perm_addr();
+
+        fill(&mut perm_addr, &[0x01; 8]);
+
+        assert_eq!(perm_addr.lladdr(), None);
+    }
+
+    #[test]
+    fn test_perm_addr_as_mut_ptr() {
+        let mut perm_addr = perm_addr();
+        let expected_ptr: *mut c_void = (&mut perm_addr as *mut EthtoolPermAddr).cast();
+
+        let ptr = perm_addr.as_mut_ptr();
+
+        assert_eq!(ptr, expected_ptr);
+        assert_eq!(ptr.as_perm_addr().cmd, ETHTOOL_GPERMADDR);
+    }
+}
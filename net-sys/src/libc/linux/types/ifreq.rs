@@ -0,0 +1,264 @@
+use std::{mem, ptr};
+
+use libc::c_void;
+
+use crate::ifname::IfName;
+use crate::lladdr::{LinkLevelAddress, SignedOctetsType};
+
+pub(crate) fn new() -> libc::ifreq {
+    unsafe { std::mem::zeroed() }
+}
+
+pub(crate) trait IfReqWith {
+    fn with_name(self, ifname: &IfName) -> Self;
+    fn with_lladdr(self, lladdr: &LinkLevelAddress) -> Self;
+    fn with_data(self, data: *mut c_void) -> Self;
+}
+
+impl IfReqWith for libc::ifreq {
+    fn with_name(mut self, ifname: &IfName) -> Self {
+        self.change_name(ifname);
+        self
+    }
+
+    fn with_lladdr(mut self, lladdr: &LinkLevelAddress) -> Self {
+        self.change_lladdr(lladdr);
+        self
+    }
+
+    fn with_data(mut self, data: *mut c_void) -> Self {
+        self.change_data(data);
+        self
+    }
+}
+
+pub(crate) trait IfReqMut {
+    fn change_name(&mut self, ifname: &IfName);
+    fn change_lladdr(&mut self, lladdr: &LinkLevelAddress);
+    fn change_data(&mut self, data: *mut c_void);
+}
+
+impl IfReqMut for libc::ifreq {
+    fn change_name(&mut self, ifname: &IfName) {
+        unsafe {
+            ptr::copy_nonoverlapping(
+                ifname.as_signed_ptr(),
+                self.ifr_name.as_mut_ptr().cast(),
+                ifname.len(),
+            );
+        }
+    }
+
+    fn change_lladdr(&mut self, lladdr: &LinkLevelAddress) {
+        unsafe {
+            ptr::copy_nonoverlapping(
+                lladdr.as_signed_ptr(),
+                self.ifr_ifru.ifru_hwaddr.sa_data.as_mut_ptr().cast(),
+                lladdr.len(),
+            );
+        }
+        // SIOCSIFHWADDR rejects addresses whose family differs from the device type.
+        self.ifr_ifru.ifru_hwaddr.sa_family = libc::ARPHRD_ETHER;
+    }
+
+    fn change_data(&mut self, data: *mut c_void) {
+        self.ifr_ifru.ifru_data = data.cast();
+    }
+}
+
+pub(crate) trait IfReq {
+    fn name(&self) -> IfName;
+    fn lladdr(&self) -> LinkLevelAddress;
+}
+
+impl IfReq for libc::ifreq {
+    fn name(&self) -> IfName {
+        IfName::from(&self.ifr_name)
+    }
+
+    fn lladdr(&self) -> LinkLevelAddress {
+        let sa_data = unsafe { &self.ifr_ifru.ifru_hwaddr.sa_data };
+        let sa_data: &SignedOctetsType = unsafe { mem::transmute(sa_data) };
+        LinkLevelAddress::from(sa_data)
+    }
+}
+
+pub(crate) trait IfReqAsPtr {
+    fn as_mut_ptr(&mut self) -> *mut c_void;
+}
+
+impl IfReqAsPtr for libc::ifreq {
+    fn as_mut_ptr(&mut self) -> *mut c_void {
+        (self as *mut libc::ifreq).cast()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::mem;
+
+    use libc::{c_char, c_void};
+
+    use crate::format::{AsBytes, AsHexColon};
+    use crate::ifname::IfName;
+    use crate::lladdr::{LinkLevelAddress, SignedOctetsType};
+    use crate::Result;
+
+    use super::new;
+    use super::{IfReq, IfReqAsPtr, IfReqMut, IfReqWith};
+
+    const IFREQ_SIZE: usize = 40;
+    const NAME_SIZE: usize = 16;
+    const NAME: [c_char; NAME_SIZE] = [
+        // '0'..'9' and 'A'..'F'
+        0x30, 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x41, 0x42, 0x43, 0x44, 0x45,
+        0x00,
+    ];
+    const LLADDR_SIZE: usize = 6;
+    const LLADDR: [c_char; LLADDR_SIZE] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06];
+
+    pub(crate) trait PtrAsIfReq {
+        fn as_ifreq<'a>(&self) -> &'a mut libc::ifreq;
+    }
+
+    impl PtrAsIfReq for *mut c_void {
+        fn as_ifreq<'a>(&self) -> &'a mut libc::ifreq {
+            unsafe { &mut *(*self as *mut libc::ifreq) }
+        }
+    }
+
+    pub(crate) trait IfReqData {
+        fn data(&self) -> *mut c_void;
+    }
+
+    impl IfReqData for libc::ifreq {
+        fn data(&self) -> *mut c_void {
+            unsafe { self.ifr_ifru.ifru_data.cast() }
+        }
+    }
+
+    #[test]
+    fn test_ifreq_size() {
+        let expected_size = size_of::<libc::ifreq>();
+
+        assert_eq!(IFREQ_SIZE, expected_size);
+    }
+
+    #[test]
+    fn test_ifreq_new() {
+        let expected_ifreq: libc::ifreq = unsafe { std::mem::zeroed() };
+
+        let ifreq = new();
+
+        assert_eq!(ifreq.as_hex_colon(), expected_ifreq.as_hex_colon());
+    }
+
+    #[test]
+    fn test_ifreq_with_name() {
+        let ifreq = new().with_name(&IfName::from(&NAME));
+
+        assert_eq!(ifreq.ifr_name, NAME);
+    }
+
+    #[test]
+    fn test_ifreq_with_lladdr() -> Result<()> {
+        let ifreq = new().with_lladdr(&LinkLevelAddress::from(&LLADDR));
+
+        let sa_data = unsafe { &ifreq.ifr_ifru.ifru_hwaddr.sa_data };
+        let sa_data_ref: &SignedOctetsType = unsafe { mem::transmute(sa_data) };
+
+        assert_eq!((*sa_data_ref).as_hex_colon(), LLADDR.as_hex_colon());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ifreq_with_data() {
+        let mut data = [0u8; 4];
+        let data_ptr: *mut c_void = data.as_mut_ptr().cast();
+
+        let ifreq = new().with_data(data_ptr);
+
+        assert_eq!(ifreq.data(), data_ptr);
+    }
+
+    #[test]
+    fn test_ifreq_change_name() {
+        let mut ifreq = new();
+
+        ifreq.change_name(&IfName::from(&NAME));
+
+        assert_eq!(ifreq.ifr_name, NAME);
+    }
+
+    #[test]
+    fn test_ifreq_change_lladdr() -> Result<()> {
+        let mut ifreq = new();
+
+        ifreq.change_lladdr(&LinkLevelAddress::from(&LLADDR));
+
+        let sa_data = unsafe { &ifreq.ifr_ifru.ifru_hwaddr.sa_data };
+        let sa_data_ref: &SignedOctetsType = unsafe { mem::transmute(sa_data) };
+
+        assert_eq!((*sa_data_ref).as_hex_colon(), LLADDR.as_hex_colon());
+        assert_eq!(
+            unsafe { ifreq.ifr_ifru.ifru_hwaddr.sa_family },
+            libc::ARPHRD_ETHER
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ifreq_name() {
+        let mut ifreq = new();
+
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                NAME.as_bytes_ptr(),
+                ifreq.ifr_name.as_mut_ptr(),
+                NAME.len(),
+            );
+        }
+
+        let ifname = ifreq.name();
+
+        assert_eq!(ifname.as_bytes(), NAME.as_bytes());
+    }
+
+    #[test]
+    fn test_ifreq_lladdr() {
+        let mut ifreq = new();
+        unsafe {
+            std::ptr::copy_nonoverlapping(
+                LLADDR.as_bytes_ptr(),
+                ifreq.ifr_ifru.ifru_hwaddr.sa_data.as_mut_ptr(),
+                LLADDR.len(),
+            );
+        }
+
+        let lladdr = ifreq.lladdr();
+
+        assert_eq!(lladdr.as_bytes(), LLADDR.as_bytes());
+    }
+
+    #[test]
+    fn test_ifreq_as_mut_ptr() {
+        let mut ifreq = new();
+        let exptected_ifreq_ptr: *mut c_void = (&mut ifreq as *mut libc::ifreq).cast();
+
+        let ifreq_ptr = ifreq.as_mut_ptr();
+
+        assert_eq!(ifreq_ptr, exptected_ifreq_ptr);
+    }
+
+    #[test]
+    fn test_mut_ptr_as_ifreq() {
+        let mut expected_ifreq = new();
+        let ifreq_ptr: *mut c_void = (&mut expected_ifreq as *mut libc::ifreq).cast();
+
+        let ifreq = ifreq_ptr.as_ifreq();
+
+        assert_eq!((*ifreq).as_hex_colon(), expected_ifreq.as_hex_colon());
+    }
+}
@@ -0,0 +1,867 @@
+use core::fmt::Debug;
+
+use libc::{c_int, c_ushort};
+
+use crate::ifname::IfName;
+use crate::info::{NicInfo, NicType};
+use crate::lladdr::LinkLevelAddress;
+use crate::IF_NAME_SIZE;
+
+use super::super::defs::ifla::{
+    IFLA_ADDRESS, IFLA_ALT_IFNAME, IFLA_IFNAME, IFLA_MTU, IFLA_OPERSTATE,
+};
+use super::super::defs::ifla::{IF_OPER_UNKNOWN, IF_OPER_UP};
+use super::super::defs::rtm::{Rtm, RTM_GETLINK, RTM_SETLINK};
+
+// https://github.com/torvalds/linux/blob/v6.6/include/uapi/linux/netlink.h#L52
+// https://github.com/torvalds/linux/blob/v6.6/include/uapi/linux/rtnetlink.h#L560
+
+// Large enough for the 32 KiB skb netlink_dump fills at most, and for a
+// single large RTM_NEWLINK, e.g. of a device with many VFs. Datagrams that
+// still do not fit are reported as truncated by `OpenSocket::read`.
+const NL_BUF_SIZE: usize = 32768;
+
+pub(crate) type NlBuf = [u8; NL_BUF_SIZE];
+
+pub(crate) fn new() -> NlBuf {
+    [0; NL_BUF_SIZE]
+}
+
+const NLMSG_HDRLEN: usize = 16;
+const NLMSGERR_LEN: usize = 4;
+const IFINFOMSG_LEN: usize = 16;
+const RTA_HDRLEN: usize = 4;
+
+// NLMSG_ALIGN and RTA_ALIGN, both round up to 4 bytes.
+const fn align(len: usize) -> usize {
+    (len + 3) & !3
+}
+
+fn u16_at(buf: &[u8], offset: usize) -> u16 {
+    u16::from_ne_bytes([buf[offset], buf[offset + 1]])
+}
+
+fn u32_at(buf: &[u8], offset: usize) -> u32 {
+    u32::from_ne_bytes([
+        buf[offset],
+        buf[offset + 1],
+        buf[offset + 2],
+        buf[offset + 3],
+    ])
+}
+
+pub(crate) struct NlMsgHdr<'a> {
+    pub(crate) nlmsg_len: u32,
+    pub(crate) nlmsg_type: u16,
+    pub(crate) nlmsg_flags: u16,
+    pub(crate) nlmsg_seq: u32,
+    pub(crate) nlmsg_pid: u32,
+    payload: &'a [u8],
+}
+
+impl<'a> NlMsgHdr<'a> {
+    pub fn nlmsg_type(&self) -> Rtm {
+        Rtm::from(self.nlmsg_type as c_int)
+    }
+
+    pub fn as_ifinfomsg(&self) -> Option<IfInfoMsg<'a>> {
+        IfInfoMsg::parse(self.payload)
+    }
+
+    /// Returns the `error` field of an NLMSG_ERROR message: zero for an ACK,
+    /// otherwise the negated errno reported by the kernel.
+    pub fn as_nlmsgerr(&self) -> Option<c_int> {
+        if self.nlmsg_type() != Rtm::NlmsgError || self.payload.len() < NLMSGERR_LEN {
+            return None;
+        }
+
+        Some(u32_at(self.payload, 0) as c_int)
+    }
+}
+
+impl Debug for NlMsgHdr<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("nlmsghdr")
+            .field("nlmsg_len", &self.nlmsg_len)
+            .field("nlmsg_type", &self.nlmsg_type())
+            .field("nlmsg_flags", &format!("0x{:x}", &self.nlmsg_flags))
+            .field("nlmsg_seq", &self.nlmsg_seq)
+            .field("nlmsg_pid", &self.nlmsg_pid)
+            .finish()
+    }
+}
+
+/// Walks the netlink messages packed in a single datagram.
+///
+/// Iteration stops at the first header whose `nlmsg_len` does not fit in
+/// the remaining bytes, so a truncated read never yields a partial message.
+pub(crate) struct NlMsgIter<'a>(&'a [u8]);
+
+pub(crate) fn iter(buf: &[u8]) -> NlMsgIter<'_> {
+    NlMsgIter(buf)
+}
+
+impl<'a> Iterator for NlMsgIter<'a> {
+    type Item = NlMsgHdr<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let buf = self.0;
+        if buf.len() < NLMSG_HDRLEN {
+            return None;
+        }
+
+        let nlmsg_len = u32_at(buf, 0);
+        let len = nlmsg_len as usize;
+        if len < NLMSG_HDRLEN || len > buf.len() {
+            self.0 = &[];
+            return None;
+        }
+
+        self.0 = &buf[align(len).min(buf.len())..];
+
+        Some(NlMsgHdr {
+            nlmsg_len,
+            nlmsg_type: u16_at(buf, 4),
+            nlmsg_flags: u16_at(buf, 6),
+            nlmsg_seq: u32_at(buf, 8),
+            nlmsg_pid: u32_at(buf, 12),
+            payload: &buf[NLMSG_HDRLEN..len],
+        })
+    }
+}
+
+pub(crate) struct IfInfoMsg<'a> {
+    pub(crate) ifi_family: u8,
+    pub(crate) ifi_type: u16,
+    pub(crate) ifi_index: c_int,
+    pub(crate) ifi_flags: u32,
+    pub(crate) ifi_change: u32,
+    attrs: &'a [u8],
+}
+
+impl<'a> IfInfoMsg<'a> {
+    fn parse(buf: &'a [u8]) -> Option<Self> {
+        if buf.len() < IFINFOMSG_LEN {
+            return None;
+        }
+
+        Some(Self {
+            ifi_family: buf[0],
+            ifi_type: u16_at(buf, 2),
+            ifi_index: u32_at(buf, 4) as i32,
+            ifi_flags: u32_at(buf, 8),
+            ifi_change: u32_at(buf, 12),
+            attrs: &buf[IFINFOMSG_LEN..],
+        })
+    }
+
+    pub fn attrs(&self) -> RtAttrIter<'a> {
+        RtAttrIter(self.attrs)
+    }
+
+    pub fn get_attr(&self, rta_type: c_ushort) -> Option<&'a [u8]> {
+        self.attrs()
+            .find(|(attr_type, _)| *attr_type == rta_type)
+            .map(|(_, data)| data)
+    }
+
+    pub fn ifname(&self) -> Option<IfName> {
+        let data = self.get_attr(IFLA_IFNAME)?;
+        let name = data.split(|c| *c == 0).next()?;
+        if name.is_empty() || name.len() >= IF_NAME_SIZE {
+            return None;
+        }
+        // Names from the kernel are copied as is, short ones like "lo" included.
+        let mut ifname = [0; IF_NAME_SIZE];
+        ifname[..name.len()].copy_from_slice(name);
+        Some(IfName::from(&ifname))
+    }
+
+    pub fn lladdr(&self) -> Option<LinkLevelAddress> {
+        let data = self.get_attr(IFLA_ADDRESS)?;
+        let octets: &[u8; 6] = data.try_into().ok()?;
+        Some(LinkLevelAddress::from(octets))
+    }
+
+    pub fn mtu(&self) -> Option<u32> {
+        let data = self.get_attr(IFLA_MTU)?;
+        let bytes: [u8; 4] = data.try_into().ok()?;
+        Some(u32::from_ne_bytes(bytes))
+    }
+
+    pub fn operstate(&self) -> Option<u8> {
+        let data = self.get_attr(IFLA_OPERSTATE)?;
+        let [operstate]: [u8; 1] = data.try_into().ok()?;
+        Some(operstate)
+    }
+
+    /// Whether the link can carry packets, IF_OPER_UP as RFC 2863 has it.
+    /// Drivers leaving the state unknown, or kernels not reporting it, fall
+    /// back on IFF_RUNNING.
+    pub fn is_link_up(&self) -> bool {
+        match self.operstate() {
+            Some(IF_OPER_UP) => true,
+            Some(IF_OPER_UNKNOWN) | None => self.ifi_flags & libc::IFF_RUNNING as u32 != 0,
+            Some(_) => false,
+        }
+    }
+
+    fn ifname_or_index(&self) -> Option<IfName> {
+        match self.ifname() {
+            Some(ifname) => Some(ifname),
+            None => IfName::try_from(format!("index{}", self.ifi_index)).ok(),
+        }
+    }
+}
+
+impl Debug for IfInfoMsg<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ifinfomsg")
+            .field("ifi_family", &self.ifi_family)
+            .field("ifi_type", &self.ifi_type)
+            .field("ifi_index", &self.ifi_index)
+            .field("ifi_flags", &format!("0x{:x}", &self.ifi_flags))
+            .field("ifi_change", &format!("0x{:x}", &self.ifi_change))
+            .field("ifi_ifname", &self.ifname())
+            .field("ifi_lladdr", &self.lladdr())
+            .finish()
+    }
+}
+
+/// Walks the `rtattr` list that follows a fixed message header, yielding
+/// `(rta_type, data)` pairs.
+pub(crate) struct RtAttrIter<'a>(&'a [u8]);
+
+impl<'a> Iterator for RtAttrIter<'a> {
+    type Item = (c_ushort, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let buf = self.0;
+        if buf.len() < RTA_HDRLEN {
+            return None;
+        }
+
+        let len = u16_at(buf, 0) as usize;
+        if len < RTA_HDRLEN || len > buf.len() {
+            self.0 = &[];
+            return None;
+        }
+
+        self.0 = &buf[align(len).min(buf.len())..];
+
+        Some((u16_at(buf, 2), &buf[RTA_HDRLEN..len]))
+    }
+}
+
+pub(crate) trait LinkEther {
+    fn get_link_ether(&self) -> Option<(c_int, IfName, LinkLevelAddress)>;
+}
+
+impl LinkEther for IfInfoMsg<'_> {
+    fn get_link_ether(&self) -> Option<(c_int, IfName, LinkLevelAddress)> {
+        if self.ifi_type != libc::ARPHRD_ETHER {
+            return None;
+        }
+
+        let ifname = self.ifname_or_index()?;
+
+        Some((self.ifi_index, ifname, self.lladdr()?))
+    }
+}
+
+pub(crate) trait LinkState {
+    fn get_link_state(&self) -> Option<(c_int, IfName, bool)>;
+}
+
+impl LinkState for IfInfoMsg<'_> {
+    /// Index, name and link state of an Ethernet interface, address or not.
+    fn get_link_state(&self) -> Option<(c_int, IfName, bool)> {
+        if self.ifi_type != libc::ARPHRD_ETHER {
+            return None;
+        }
+
+        let ifname = self.ifname_or_index()?;
+
+        Some((self.ifi_index, ifname, self.is_link_up()))
+    }
+}
+
+pub(crate) trait LinkInfo {
+    fn get_link_info(&self) -> Option<NicInfo>;
+}
+
+impl LinkInfo for IfInfoMsg<'_> {
+    fn get_link_info(&self) -> Option<NicInfo> {
+        let nic_type = match self.ifi_type {
+            libc::ARPHRD_ETHER => NicType::Ether,
+            libc::ARPHRD_LOOPBACK => NicType::Loopback,
+            value => NicType::Other(value as u32),
+        };
+
+        Some(NicInfo {
+            index: self.ifi_index,
+            ifname: self.ifname_or_index()?,
+            lladdr: self.lladdr(),
+            nic_type,
+            flags: self.ifi_flags,
+            mtu: self.mtu().unwrap_or(0),
+        })
+    }
+}
+
+/// Builds a request message one attribute at a time, keeping `nlmsg_len`
+/// and the 4-byte alignment of every `rtattr` up to date.
+pub(crate) struct NlMsgBuilder(Vec<u8>);
+
+impl NlMsgBuilder {
+    pub(crate) fn new(nlmsg_type: c_int, nlmsg_flags: c_int, nlmsg_seq: u32) -> Self {
+        let mut buf = Vec::with_capacity(NLMSG_HDRLEN);
+        buf.extend_from_slice(&(NLMSG_HDRLEN as u32).to_ne_bytes());
+        buf.extend_from_slice(&(nlmsg_type as u16).to_ne_bytes());
+        buf.extend_from_slice(&(nlmsg_flags as u16).to_ne_bytes());
+        buf.extend_from_slice(&nlmsg_seq.to_ne_bytes());
+        // nlmsg_pid is zero, the kernel fills in the port id of the socket.
+        buf.extend_from_slice(&0u32.to_ne_bytes());
+        Self(buf)
+    }
+
+    pub(crate) fn ifinfomsg(mut self, ifi_index: i32) -> Self {
+        // ifi_family AF_UNSPEC, ifi_type 0, ifi_flags 0 and ifi_change 0.
+        self.0.extend_from_slice(&[0; 4]);
+        self.0.extend_from_slice(&ifi_index.to_ne_bytes());
+        self.0.extend_from_slice(&[0; 8]);
+        self.update_len()
+    }
+
+    pub(crate) fn attr(mut self, rta_type: c_ushort, data: &[u8]) -> Self {
+        let rta_len = RTA_HDRLEN + data.len();
+        self.0.extend_from_slice(&(rta_len as u16).to_ne_bytes());
+        self.0.extend_from_slice(&rta_type.to_ne_bytes());
+        self.0.extend_from_slice(data);
+        self.0.resize(align(self.0.len()), 0);
+        self.update_len()
+    }
+
+    fn update_len(mut self) -> Self {
+        let len = (self.0.len() as u32).to_ne_bytes();
+        self.0[..4].copy_from_slice(&len);
+        self
+    }
+
+    pub(crate) fn build(self) -> Vec<u8> {
+        self.0
+    }
+}
+
+/// RTM_SETLINK request changing the link-level address of the link named
+/// `name`, up to its first NUL if any.
+///
+/// With `ifi_index` 0 the kernel looks the link up by name itself, in
+/// IFLA_IFNAME if it fits IFNAMSIZ and else in IFLA_ALT_IFNAME, as iproute2
+/// does. The kernel is asked to ACK, so the result always comes back as
+/// NLMSG_ERROR.
+pub(crate) fn setlink_lladdr(seq: u32, name: &[u8], lladdr: &LinkLevelAddress) -> Vec<u8> {
+    let flags = libc::NLM_F_REQUEST | libc::NLM_F_ACK;
+    let name = name.split(|c| *c == 0).next().unwrap_or_default();
+    let name_type = match name.len() < IF_NAME_SIZE {
+        true => IFLA_IFNAME,
+        false => IFLA_ALT_IFNAME,
+    };
+
+    NlMsgBuilder::new(RTM_SETLINK, flags, seq)
+        .ifinfomsg(0)
+        .attr(name_type, &[name, &[0]].concat())
+        .attr(IFLA_ADDRESS, &lladdr[..])
+        .build()
+}
+
+/// RTM_GETLINK dump request, answered with one RTM_NEWLINK per interface
+/// followed by NLMSG_DONE.
+pub(crate) fn getlink_dump(seq: u32) -> Vec<u8> {
+    let flags = libc::NLM_F_REQUEST | libc::NLM_F_DUMP;
+
+    NlMsgBuilder::new(RTM_GETLINK, flags, seq)
+        .ifinfomsg(0)
+        .build()
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::ifname::IfName;
+    use crate::lladdr::LinkLevelAddress;
+    use crate::random;
+    use crate::Result;
+
+    use super::super::super::defs::ifla::{IFLA_ALT_IFNAME, IFLA_IFNAME};
+    use super::super::super::defs::rtm::Rtm;
+    use crate::info::{NicInfo, NicType};
+    use crate::IF_NAME_SIZE;
+
+    use super::{getlink_dump, iter, setlink_lladdr, LinkEther, LinkInfo, LinkState, NlMsgBuilder};
+
+    // RTM_NEWLINK for "enx001122334455" (index 7, 00:11:22:33:44:55) as the kernel
+    // lays it out on little-endian hosts, trimmed to IFLA_IFNAME, IFLA_MTU,
+    // IFLA_ADDRESS and IFLA_BROADCAST.
+    pub(crate) const RTM_NEWLINK_ENX: [u8; 84] = [
+        0x54, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, // nlmsg_len, type, flags
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // nlmsg_seq, nlmsg_pid
+        0x00, 0x00, 0x01, 0x00, 0x07, 0x00, 0x00, 0x00, // ifi_family, ifi_type, ifi_index
+        0x43, 0x10, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, // ifi_flags, ifi_change
+        0x14, 0x00, 0x03, 0x00, 0x65, 0x6e, 0x78, 0x30, // IFLA_IFNAME "enx0
+        0x30, 0x31, 0x31, 0x32, 0x32, 0x33, 0x33, 0x34, // 01122334
+        0x34, 0x35, 0x35, 0x00, 0x08, 0x00, 0x04, 0x00, // 455", IFLA_MTU
+        0xdc, 0x05, 0x00, 0x00, 0x0a, 0x00, 0x01, 0x00, // 1500, IFLA_ADDRESS
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x00, 0x00, // 00:11:22:33:44:55
+        0x0a, 0x00, 0x02, 0x00, 0xff, 0xff, 0xff, 0xff, // IFLA_BROADCAST
+        0xff, 0xff, 0x00, 0x00, // ff:ff:ff:ff:ff:ff
+    ];
+
+    // RTM_DELLINK for the same interface.
+    pub(crate) const RTM_DELLINK_ENX: [u8; 84] = [
+        0x54, 0x00, 0x00, 0x00, 0x11, 0x00, 0x00, 0x00, // nlmsg_len, type, flags
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // nlmsg_seq, nlmsg_pid
+        0x00, 0x00, 0x01, 0x00, 0x07, 0x00, 0x00, 0x00, // ifi_family, ifi_type, ifi_index
+        0x02, 0x10, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, // ifi_flags, ifi_change
+        0x14, 0x00, 0x03, 0x00, 0x65, 0x6e, 0x78, 0x30, // IFLA_IFNAME "enx0
+        0x30, 0x31, 0x31, 0x32, 0x32, 0x33, 0x33, 0x34, // 01122334
+        0x34, 0x35, 0x35, 0x00, 0x08, 0x00, 0x04, 0x00, // 455", IFLA_MTU
+        0xdc, 0x05, 0x00, 0x00, 0x0a, 0x00, 0x01, 0x00, // 1500, IFLA_ADDRESS
+        0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x00, 0x00, // 00:11:22:33:44:55
+        0x0a, 0x00, 0x02, 0x00, 0xff, 0xff, 0xff, 0xff, // IFLA_BROADCAST
+        0xff, 0xff, 0x00, 0x00, // ff:ff:ff:ff:ff:ff
+    ];
+
+    // RTM_NEWLINK for "lo" (index 1, ARPHRD_LOOPBACK).
+    pub(crate) const RTM_NEWLINK_LO: [u8; 52] = [
+        0x34, 0x00, 0x00, 0x00, 0x10, 0x00, 0x00, 0x00, // nlmsg_len, type, flags
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // nlmsg_seq, nlmsg_pid
+        0x00, 0x00, 0x04, 0x03, 0x01, 0x00, 0x00, 0x00, // ifi_family, ifi_type, ifi_index
+        0x49, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, // ifi_flags, ifi_change
+        0x07, 0x00, 0x03, 0x00, 0x6c, 0x6f, 0x00, 0x00, // IFLA_IFNAME "lo"
+        0x0a, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, // IFLA_ADDRESS
+        0x00, 0x00, 0x00, 0x00, // 00:00:00:00:00:00
+    ];
+
+    // NLMSG_ERROR with error 0, the ACK for an RTM_SETLINK request with seq 1.
+    pub(crate) const NLMSG_ACK: [u8; 36] = [
+        0x24, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x01, // nlmsg_len, type, flags
+        0x01, 0x00, 0x00, 0x00, 0x10, 0x20, 0x00, 0x00, // nlmsg_seq, nlmsg_pid
+        0x00, 0x00, 0x00, 0x00, // error 0
+        0x34, 0x00, 0x00, 0x00, 0x13, 0x00, 0x05, 0x00, // request nlmsg_len, type, flags
+        0x01, 0x00, 0x00, 0x00, 0x10, 0x20, 0x00, 0x00, // request nlmsg_seq, nlmsg_pid
+    ];
+
+    // NLMSG_ERROR with error -EBUSY for the same request.
+    pub(crate) const NLMSG_ERROR_EBUSY: [u8; 36] = [
+        0x24, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, // nlmsg_len, type, flags
+        0x01, 0x00, 0x00, 0x00, 0x10, 0x20, 0x00, 0x00, // nlmsg_seq, nlmsg_pid
+        0xf0, 0xff, 0xff, 0xff, // error -16
+        0x34, 0x00, 0x00, 0x00, 0x13, 0x00, 0x05, 0x00, // request nlmsg_len, type, flags
+        0x01, 0x00, 0x00, 0x00, 0x10, 0x20, 0x00, 0x00, // request nlmsg_seq, nlmsg_pid
+    ];
+
+    // RTM_SETLINK for "enx0" with IFLA_ADDRESS 00:11:22:33:44:55 and seq 1.
+    const RTM_SETLINK_ENX: [u8; 56] = [
+        0x38, 0x00, 0x00, 0x00, 0x13, 0x00, 0x05, 0x00, // nlmsg_len, type, flags
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // nlmsg_seq, nlmsg_pid
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ifi_family, ifi_type, ifi_index
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ifi_flags, ifi_change
+        0x09, 0x00, 0x03, 0x00, 0x65, 0x6e, 0x78, 0x30, // IFLA_IFNAME "enx0
+        0x00, 0x00, 0x00, 0x00, // "
+        0x0a, 0x00, 0x01, 0x00, 0x00, 0x11, 0x22, 0x33, // IFLA_ADDRESS 00:11:22:33
+        0x44, 0x55, 0x00, 0x00, // 44:55
+    ];
+
+    // NLMSG_DONE closing a dump with seq 1.
+    pub(crate) const NLMSG_DONE: [u8; 20] = [
+        0x14, 0x00, 0x00, 0x00, 0x03, 0x00, 0x02, 0x00, // nlmsg_len, type, flags
+        0x01, 0x00, 0x00, 0x00, 0x10, 0x20, 0x00, 0x00, // nlmsg_seq, nlmsg_pid
+        0x00, 0x00, 0x00, 0x00, // error 0
+    ];
+
+    /// Copy of `msg` with `nlmsg_seq` rewritten, for replies to a request.
+    pub(crate) fn with_seq(msg: &[u8], seq: u32) -> Vec<u8> {
+        let mut msg = msg.to_vec();
+        msg[8..12].copy_from_slice(&seq.to_ne_bytes());
+        msg
+    }
+
+    /// Copy of `msg` with an IFLA_OPERSTATE attribute appended.
+    pub(crate) fn with_operstate(msg: &[u8], operstate: u8) -> Vec<u8> {
+        let mut msg = [msg, &[0x05, 0x00, 0x10, 0x00, operstate, 0x00, 0x00, 0x00]].concat();
+        let len = (msg.len() as u32).to_ne_bytes();
+        msg[..4].copy_from_slice(&len);
+        msg
+    }
+
+    fn concat(messages: &[&[u8]]) -> Vec<u8> {
+        messages.concat()
+    }
+
+    /// Reads everything the monitor and `list` would from `buf`.
+    fn decode(buf: &[u8]) {
+        for msg in iter(buf) {
+            assert!(msg.nlmsg_len as usize <= buf.len());
+            msg.as_nlmsgerr();
+            if let Some(ifinfomsg) = msg.as_ifinfomsg() {
+                let _ = format!("{:?}", ifinfomsg);
+                ifinfomsg.mtu();
+                ifinfomsg.get_link_ether();
+                ifinfomsg.get_link_state();
+                ifinfomsg.get_link_info();
+            }
+        }
+    }
+
+    #[test]
+    fn test_nlmsg_iter_newlink() -> Result<()> {
+        let expected_ifname: IfName = "enx001122334455".try_into()?;
+        let expected_lladdr: LinkLevelAddress = "00:11:22:33:44:55".parse()?;
+
+        let msgs = iter(&RTM_NEWLINK_ENX).collect::<Vec<_>>();
+
+        assert_eq!(msgs.len(), 1);
+        assert!(msgs[0].nlmsg_type() == Rtm::RtmNewlink);
+
+        let ifinfomsg = msgs[0].as_ifinfomsg().unwrap();
+
+        assert_eq!(ifinfomsg.ifi_index, 7);
+        assert_eq!(ifinfomsg.ifi_type, libc::ARPHRD_ETHER);
+        assert_eq!(ifinfomsg.ifname(), Some(expected_ifname));
+        assert_eq!(ifinfomsg.lladdr(), Some(expected_lladdr));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nlmsg_get_link_ether() -> Result<()> {
+        let expected_nic = (
+            7,
+            "enx001122334455".try_into()?,
+            "00:11:22:33:44:55".parse()?,
+        );
+
+        let msg = iter(&RTM_DELLINK_ENX).next().unwrap();
+        let nic = msg.as_ifinfomsg().unwrap().get_link_ether();
+
+        assert!(msg.nlmsg_type() == Rtm::RtmDellink);
+        assert_eq!(nic, Some(expected_nic));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nlmsg_get_link_ether_large_index() -> Result<()> {
+        let mut msg = RTM_NEWLINK_ENX.to_vec();
+        msg[20..24].copy_from_slice(&70000i32.to_ne_bytes());
+
+        let msg = iter(&msg).next().unwrap();
+        let nic = msg.as_ifinfomsg().unwrap().get_link_ether();
+
+        assert_eq!(nic.map(|(index, ..)| index), Some(70000));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nlmsg_get_link_ether_loopback() {
+        let msg = iter(&RTM_NEWLINK_LO).next().unwrap();
+        let nic = msg.as_ifinfomsg().unwrap().get_link_ether();
+
+        assert_eq!(nic, None);
+    }
+
+    #[test]
+    fn test_nlmsg_get_link_state() -> Result<()> {
+        let enx: IfName = "enx001122334455".try_into()?;
+
+        let link_state = |msg: &[u8]| {
+            let msg = iter(msg).next().unwrap();
+            msg.as_ifinfomsg().unwrap().get_link_state()
+        };
+
+        // IFF_RUNNING set in RTM_NEWLINK_ENX, not in RTM_DELLINK_ENX.
+        assert_eq!(link_state(&RTM_NEWLINK_ENX), Some((7, enx, true)));
+        assert_eq!(link_state(&RTM_DELLINK_ENX), Some((7, enx, false)));
+        assert_eq!(link_state(&RTM_NEWLINK_LO), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nlmsg_get_link_state_operstate() -> Result<()> {
+        let enx: IfName = "enx001122334455".try_into()?;
+        let if_oper_down = 2;
+        let if_oper_dormant = 5;
+
+        let link_state = |msg: &[u8], operstate| {
+            let msg = with_operstate(msg, operstate);
+            let msg = iter(&msg).next().unwrap();
+            let ifinfomsg = msg.as_ifinfomsg().unwrap();
+            assert_eq!(ifinfomsg.operstate(), Some(operstate));
+            ifinfomsg.get_link_state()
+        };
+
+        assert_eq!(link_state(&RTM_NEWLINK_ENX, 6), Some((7, enx, true)));
+        assert_eq!(link_state(&RTM_DELLINK_ENX, 6), Some((7, enx, true)));
+        // Running, yet without carrier or still authenticating.
+        assert_eq!(
+            link_state(&RTM_NEWLINK_ENX, if_oper_down),
+            Some((7, enx, false))
+        );
+        assert_eq!(
+            link_state(&RTM_NEWLINK_ENX, if_oper_dormant),
+            Some((7, enx, false))
+        );
+        // IF_OPER_UNKNOWN falls back on IFF_RUNNING.
+        assert_eq!(link_state(&RTM_NEWLINK_ENX, 0), Some((7, enx, true)));
+        assert_eq!(link_state(&RTM_DELLINK_ENX, 0), Some((7, enx, false)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nlmsg_iter_multiple_messages() {
+        let buf = concat(&[&RTM_NEWLINK_LO, &RTM_NEWLINK_ENX, &RTM_DELLINK_ENX]);
+
+        let types = iter(&buf).map(|msg| msg.nlmsg_type()).collect::<Vec<_>>();
+
+        assert!(types == vec![Rtm::RtmNewlink, Rtm::RtmNewlink, Rtm::RtmDellink]);
+    }
+
+    #[test]
+    fn test_nlmsg_iter_truncated_message() {
+        let buf = concat(&[&RTM_NEWLINK_LO, &RTM_NEWLINK_ENX[..40]]);
+
+        let count = iter(&buf).count();
+
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn test_nlmsg_iter_truncated_header() {
+        let count = iter(&RTM_NEWLINK_ENX[..8]).count();
+
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_nlmsg_iter_invalid_length() {
+        let mut buf = RTM_NEWLINK_ENX;
+        buf[0] = 0x04;
+
+        let count = iter(&buf).count();
+
+        assert_eq!(count, 0);
+    }
+
+    #[test]
+    fn test_nlmsg_truncated_attribute() {
+        let mut buf = RTM_NEWLINK_ENX;
+        // IFLA_ADDRESS claims more bytes than the message holds.
+        buf[60] = 0xff;
+
+        let msg = iter(&buf).next().unwrap();
+        let ifinfomsg = msg.as_ifinfomsg().unwrap();
+
+        assert!(ifinfomsg.ifname().is_some());
+        assert_eq!(ifinfomsg.lladdr(), None);
+        assert_eq!(ifinfomsg.get_link_ether(), None);
+    }
+
+    #[test]
+    fn test_nlmsg_debug() {
+        let expected_debug = "nlmsghdr { nlmsg_len: 84, nlmsg_type: RtmNewlink, nlmsg_flags: \"0x0\", nlmsg_seq: 0, nlmsg_pid: 0 }";
+
+        let msg = iter(&RTM_NEWLINK_ENX).next().unwrap();
+
+        assert_eq!(format!("{:?}", msg), expected_debug);
+    }
+
+    #[test]
+    fn test_ifinfomsg_debug() {
+        let expected_debug = "ifinfomsg { ifi_family: 0, ifi_type: 1, ifi_index: 7, ifi_flags: \"0x11043\", ifi_change: \"0x0\", ifi_ifname: Some(\"enx001122334455\"), ifi_lladdr: Some(\"00:11:22:33:44:55\") }";
+
+        let msg = iter(&RTM_NEWLINK_ENX).next().unwrap();
+
+        assert_eq!(format!("{:?}", msg.as_ifinfomsg().unwrap()), expected_debug);
+    }
+
+    #[test]
+    fn test_nlmsg_as_nlmsgerr_ack() {
+        let msg = iter(&NLMSG_ACK).next().unwrap();
+
+        assert!(msg.nlmsg_type() == Rtm::NlmsgError);
+        assert_eq!(msg.nlmsg_seq, 1);
+        assert_eq!(msg.as_nlmsgerr(), Some(0));
+    }
+
+    #[test]
+    fn test_nlmsg_as_nlmsgerr_error() {
+        let msg = iter(&NLMSG_ERROR_EBUSY).next().unwrap();
+
+        assert_eq!(msg.as_nlmsgerr(), Some(-libc::EBUSY));
+    }
+
+    #[test]
+    fn test_nlmsg_as_nlmsgerr_not_an_error() {
+        let msg = iter(&RTM_NEWLINK_ENX).next().unwrap();
+
+        assert_eq!(msg.as_nlmsgerr(), None);
+    }
+
+    #[test]
+    fn test_nlmsg_as_nlmsgerr_truncated() {
+        let mut buf = NLMSG_ACK;
+        buf[0] = 0x12;
+
+        let msg = iter(&buf).next().unwrap();
+
+        assert_eq!(msg.as_nlmsgerr(), None);
+    }
+
+    #[test]
+    fn test_nlmsg_setlink_lladdr() -> Result<()> {
+        let lladdr: LinkLevelAddress = "00:11:22:33:44:55".parse()?;
+
+        let buf = setlink_lladdr(1, b"enx0\0\0\0\0", &lladdr);
+
+        assert_eq!(buf, RTM_SETLINK_ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nlmsg_setlink_lladdr_roundtrip() -> Result<()> {
+        let lladdr: LinkLevelAddress = "00:11:22:33:44:55".parse()?;
+
+        let buf = setlink_lladdr(7, b"enx001122334455", &lladdr);
+        let msg = iter(&buf).next().unwrap();
+        let ifinfomsg = msg.as_ifinfomsg().unwrap();
+
+        assert_eq!(msg.nlmsg_len as usize, buf.len());
+        assert!(msg.nlmsg_type() == Rtm::RtmSetlink);
+        assert_eq!(msg.nlmsg_seq, 7);
+        assert_eq!(ifinfomsg.ifi_index, 0);
+        assert_eq!(ifinfomsg.ifname(), Some("enx001122334455".try_into()?));
+        assert_eq!(ifinfomsg.get_attr(IFLA_ALT_IFNAME), None);
+        assert_eq!(ifinfomsg.lladdr(), Some(lladdr));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nlmsg_setlink_lladdr_alt_ifname() -> Result<()> {
+        let lladdr: LinkLevelAddress = "00:11:22:33:44:55".parse()?;
+
+        let buf = setlink_lladdr(1, b"enx001122334455-dock", &lladdr);
+        let ifinfomsg = iter(&buf).next().unwrap().as_ifinfomsg().unwrap();
+
+        assert_eq!(ifinfomsg.ifi_index, 0);
+        assert_eq!(ifinfomsg.get_attr(IFLA_IFNAME), None);
+        assert_eq!(
+            ifinfomsg.get_attr(IFLA_ALT_IFNAME),
+            Some(&b"enx001122334455-dock\0"[..])
+        );
+        assert_eq!(ifinfomsg.lladdr(), Some(lladdr));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nlmsg_builder_aligns_attrs() {
+        let buf = NlMsgBuilder::new(0, 0, 0)
+            .attr(1, &[0xaa])
+            .attr(2, &[])
+            .build();
+
+        assert_eq!(buf.len(), 16 + 8 + 4);
+        assert_eq!(buf[..4], [0x1c, 0x00, 0x00, 0x00]);
+        assert_eq!(
+            buf[16..24],
+            [0x05, 0x00, 0x01, 0x00, 0xaa, 0x00, 0x00, 0x00]
+        );
+        assert_eq!(buf[24..], [0x04, 0x00, 0x02, 0x00]);
+    }
+
+    #[test]
+    fn test_nlmsg_getlink_dump() {
+        let expected_buf = [
+            0x20, 0x00, 0x00, 0x00, 0x12, 0x00, 0x01, 0x03, // nlmsg_len, type, flags
+            0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // nlmsg_seq, nlmsg_pid
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ifi_family, ifi_type, ifi_index
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // ifi_flags, ifi_change
+        ];
+
+        let buf = getlink_dump(1);
+
+        assert_eq!(buf, expected_buf);
+    }
+
+    #[test]
+    fn test_nlmsg_get_link_info() -> Result<()> {
+        let expected_info = NicInfo {
+            index: 7,
+            ifname: "enx001122334455".try_into()?,
+            lladdr: Some("00:11:22:33:44:55".parse()?),
+            nic_type: NicType::Ether,
+            flags: 0x11043,
+            mtu: 1500,
+        };
+
+        let msg = iter(&RTM_NEWLINK_ENX).next().unwrap();
+        let info = msg.as_ifinfomsg().unwrap().get_link_info();
+
+        assert_eq!(info, Some(expected_info));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nlmsg_get_link_info_large_index() {
+        let mut msg = RTM_NEWLINK_ENX.to_vec();
+        msg[20..24].copy_from_slice(&70000i32.to_ne_bytes());
+
+        let msg = iter(&msg).next().unwrap();
+        let info = msg.as_ifinfomsg().unwrap().get_link_info();
+
+        assert_eq!(info.map(|info| info.index), Some(70000));
+    }
+
+    #[test]
+    fn test_nlmsg_get_link_info_loopback() -> Result<()> {
+        // "lo" is shorter than IfName accepts from user input.
+        let mut lo = [0; IF_NAME_SIZE];
+        lo[..2].copy_from_slice(b"lo");
+        let expected_info = NicInfo {
+            index: 1,
+            ifname: IfName::from(&lo),
+            lladdr: Some("00:00:00:00:00:00".parse()?),
+            nic_type: NicType::Loopback,
+            flags: 0x10049,
+            mtu: 0,
+        };
+
+        let msg = iter(&RTM_NEWLINK_LO).next().unwrap();
+        let info = msg.as_ifinfomsg().unwrap().get_link_info();
+
+        assert_eq!(info, Some(expected_info));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nlmsg_done() {
+        let msg = iter(&NLMSG_DONE).next().unwrap();
+
+        assert!(msg.nlmsg_type() == Rtm::NlmsgDone);
+        assert_eq!(msg.as_nlmsgerr(), None);
+    }
+
+    #[test]
+    fn test_nlmsg_fuzz() {
+        let dump = concat(&[&RTM_NEWLINK_LO, &RTM_NEWLINK_ENX, &NLMSG_DONE]);
+
+        random::fuzz(0x2545f4914f6cdd1d, &[&dump, &NLMSG_ACK], decode);
+    }
+}
@@ -1,6 +1,7 @@
 pub mod nic;
 
 mod defs;
+mod ifaddrs;
 mod socket;
 mod sys;
 mod types;
@@ -18,12 +18,10 @@ pub(crate) const SIOCSIFLLADDR: c_ulong = ioc::iow(ioc::I, 60, IFREQ_SIZE);
 mod tests {
     use libc::c_ulong;
 
-    use crate::Result;
-
     use super::{IFREQ_SIZE, SIOCGIFLLADDR, SIOCSIFLLADDR};
 
     #[test]
-    fn test_ifreq_size() -> Result<()> {
+    fn test_ifreq_size() -> std::result::Result<(), Box<dyn std::error::Error>> {
         let expected_size: c_ulong = std::mem::size_of::<libc::ifreq>().try_into()?;
 
         assert_eq!(IFREQ_SIZE, expected_size);
@@ -0,0 +1,252 @@
+use std::ffi::CStr;
+use std::fmt::Debug;
+use std::ptr;
+
+use libc::c_int;
+
+use crate::error::Operation;
+use crate::ifname::IfName;
+use crate::info::NicInfo;
+use crate::Result;
+
+use super::defs::af::Af;
+use super::types::sockaddrdl::LinkInfo;
+
+#[cfg(not(test))]
+use super::sys;
+#[cfg(test)]
+use mocks::sys;
+
+#[derive(Clone, PartialEq, Eq)]
+enum Error {
+    GetIfAddrs(c_int, c_int),
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::GetIfAddrs(ret, errno) => f
+                .debug_struct("IfAddrs::GetIfAddrsError")
+                .field("ret", ret)
+                .field("errno", errno)
+                .field("strerror", &sys::strerror(*errno))
+                .finish(),
+        }
+    }
+}
+
+impl From<Error> for crate::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::GetIfAddrs(_, errno) => {
+                crate::Error::detailed(Operation::GetLinks, Some(errno), &error)
+            }
+        }
+    }
+}
+
+pub(crate) fn get_ifaddrs() -> Result<IfAddrs> {
+    let mut ifap = ptr::null_mut();
+    match sys::getifaddrs(&mut ifap) {
+        0 => Ok(IfAddrs { ifap }),
+        ret => {
+            let errno = sys::errno();
+            Err(Error::GetIfAddrs(ret, errno).into())
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct IfAddrs {
+    ifap: *mut libc::ifaddrs,
+}
+
+impl IfAddrs {
+    /// getifaddrs reports one AF_LINK entry per interface, with `if_data`
+    /// in `ifa_data`; the entries of other families are skipped.
+    pub(crate) fn links(&self) -> Vec<NicInfo> {
+        let mut links = Vec::new();
+        let mut ifa = self.ifap;
+        while let Some(entry) = unsafe { ifa.as_ref() } {
+            links.extend(link_info(entry));
+            ifa = entry.ifa_next;
+        }
+        links
+    }
+}
+
+fn link_info(ifa: &libc::ifaddrs) -> Option<NicInfo> {
+    let addr = unsafe { ifa.ifa_addr.as_ref()? };
+    if Af::from(addr.sa_family as c_int) != Af::AfLink {
+        return None;
+    }
+
+    let name = unsafe { CStr::from_ptr(ifa.ifa_name) };
+    let ifname = IfName::try_from(name.to_str().ok()?).ok()?;
+
+    let mtu = match unsafe { (ifa.ifa_data as *const libc::if_data).as_ref() } {
+        Some(data) => data.ifi_mtu,
+        None => 0,
+    };
+
+    let sdl = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_dl) };
+    sdl.get_link_info(ifname, ifa.ifa_flags, mtu)
+}
+
+impl Drop for IfAddrs {
+    fn drop(&mut self) {
+        sys::freeifaddrs(self.ifap);
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod mocks {
+    pub(crate) mod sys {
+        use libc::c_int;
+
+        use mockdown::{mockdown, Mock};
+
+        use super::super::super::sys;
+
+        pub(crate) use sys::strerror;
+
+        pub(crate) struct GetIfAddrs(pub fn(ifap: *mut *mut libc::ifaddrs) -> c_int);
+        pub(crate) struct FreeIfAddrs(pub fn(ifa: *mut libc::ifaddrs));
+        pub(crate) struct ErrNo(pub fn() -> c_int);
+
+        pub(crate) fn getifaddrs(ifap: *mut *mut libc::ifaddrs) -> c_int {
+            mockdown().next(|GetIfAddrs(mock)| mock(ifap)).unwrap()
+        }
+
+        pub(crate) fn freeifaddrs(ifa: *mut libc::ifaddrs) {
+            mockdown().next(|FreeIfAddrs(mock)| mock(ifa)).unwrap()
+        }
+
+        pub(crate) fn errno() -> c_int {
+            mockdown().next(|ErrNo(mock)| mock()).unwrap()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{mem, ptr};
+
+    use libc::{c_char, c_int, c_ushort};
+    use mockdown::{mockdown, Mock};
+
+    use crate::info::NicType;
+    use crate::Result;
+
+    use super::get_ifaddrs;
+    use super::mocks::sys;
+
+    const IFF_LOOPBACK: u32 = (libc::IFF_UP | libc::IFF_LOOPBACK | libc::IFF_RUNNING) as u32;
+    const IFF_ETHER: u32 = (libc::IFF_UP | libc::IFF_BROADCAST | libc::IFF_RUNNING) as u32;
+
+    fn sockaddr_dl(index: c_ushort, ty: c_int, name: &str, addr: &[u8]) -> libc::sockaddr_dl {
+        let mut sdl: libc::sockaddr_dl = unsafe { mem::zeroed() };
+        sdl.sdl_len = size_of::<libc::sockaddr_dl>() as u8;
+        sdl.sdl_family = libc::AF_LINK as u8;
+        sdl.sdl_index = index;
+        sdl.sdl_type = ty as u8;
+        sdl.sdl_nlen = name.len() as u8;
+        sdl.sdl_alen = addr.len() as u8;
+        for (i, c) in name.bytes().chain(addr.iter().copied()).enumerate() {
+            sdl.sdl_data[i] = c as c_char;
+        }
+        sdl
+    }
+
+    // Leaked on purpose: the mocks are plain fn pointers and cannot capture.
+    fn ifaddrs(
+        next: *mut libc::ifaddrs,
+        name: &'static [u8],
+        flags: u32,
+        addr: *mut libc::sockaddr,
+        mtu: u32,
+    ) -> *mut libc::ifaddrs {
+        let mut data: libc::if_data = unsafe { mem::zeroed() };
+        data.ifi_mtu = mtu;
+
+        let mut ifa: libc::ifaddrs = unsafe { mem::zeroed() };
+        ifa.ifa_next = next;
+        ifa.ifa_name = name.as_ptr() as *mut c_char;
+        ifa.ifa_flags = flags;
+        ifa.ifa_addr = addr;
+        ifa.ifa_data = Box::into_raw(Box::new(data)).cast();
+        Box::into_raw(Box::new(ifa))
+    }
+
+    fn ifaddrs_list() -> *mut libc::ifaddrs {
+        let en0 = sockaddr_dl(4, 0x06, "en0", &[0x00, 0x11, 0x22, 0x33, 0x44, 0x55]);
+        let lo0 = sockaddr_dl(1, 0x18, "lo0", &[]);
+        let mut inet: libc::sockaddr = unsafe { mem::zeroed() };
+        inet.sa_family = libc::AF_INET as u8;
+
+        let en0 = Box::into_raw(Box::new(en0)).cast();
+        let lo0 = Box::into_raw(Box::new(lo0)).cast();
+        let inet = Box::into_raw(Box::new(inet));
+
+        let ifa = ifaddrs(ptr::null_mut(), b"en0\0", IFF_ETHER, en0, 1500);
+        let ifa = ifaddrs(ifa, b"lo0\0", IFF_LOOPBACK, inet, 16384);
+        ifaddrs(ifa, b"lo0\0", IFF_LOOPBACK, lo0, 16384)
+    }
+
+    #[test]
+    fn test_ifaddrs_links() -> Result<()> {
+        mockdown()
+            .expect(sys::GetIfAddrs(|ifap| {
+                unsafe { *ifap = ifaddrs_list() };
+                0
+            }))
+            .expect(sys::FreeIfAddrs(|ifa| assert!(!ifa.is_null())));
+
+        let links = get_ifaddrs()?.links();
+
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].index, 1);
+        assert_eq!(links[0].ifname, "lo0".try_into()?);
+        assert_eq!(links[0].lladdr, None);
+        assert_eq!(links[0].nic_type, NicType::Loopback);
+        assert_eq!(links[0].flags, IFF_LOOPBACK);
+        assert_eq!(links[0].mtu, 16384);
+        assert_eq!(links[1].index, 4);
+        assert_eq!(links[1].ifname, "en0".try_into()?);
+        assert_eq!(links[1].lladdr, Some("00:11:22:33:44:55".parse()?));
+        assert_eq!(links[1].nic_type, NicType::Ether);
+        assert_eq!(links[1].flags, IFF_ETHER);
+        assert_eq!(links[1].mtu, 1500);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ifaddrs_links_empty() -> Result<()> {
+        mockdown()
+            .expect(sys::GetIfAddrs(|_ifap| 0))
+            .expect(sys::FreeIfAddrs(|ifa| assert!(ifa.is_null())));
+
+        let links = get_ifaddrs()?.links();
+
+        assert!(links.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ifaddrs_error() {
+        mockdown()
+            .expect(sys::GetIfAddrs(|_ifap| -1))
+            .expect(sys::ErrNo(|| libc::ENOMEM));
+
+        let expected_error =
+            "IfAddrs::GetIfAddrsError { ret: -1, errno: 12, strerror: \"Cannot allocate memory\" }";
+
+        let error = get_ifaddrs().unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+        assert_eq!(error.errno, Some(libc::ENOMEM));
+    }
+}
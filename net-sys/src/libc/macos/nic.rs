@@ -1,28 +1,25 @@
+use std::ffi::c_int;
 use std::fmt::Debug;
 
+use crate::error::{ErrorKind, Operation};
 use crate::ifname::IfName;
 use crate::lladdr::LinkLevelAddress;
 use crate::Result;
 
+use super::defs::ift::Ift;
 use super::defs::rtm::Rtm;
 use super::types::ifreq::{self, IfReq, IfReqWith};
 use super::types::rtbuf::{self, AsMsgHdr, RtBuf};
-use super::types::sockaddrdl::LinkEther;
 
 #[cfg(not(test))]
-use super::socket;
-use libc::c_ushort;
+use super::{ifaddrs, socket};
 #[cfg(test)]
-use mocks::socket;
+use mocks::{ifaddrs, socket};
 
 use super::socket::ReadResult::{EndOfRead, ReadLength};
 
-#[derive(Clone, Debug)]
-pub enum NicEvent {
-    NicNew((c_ushort, IfName, LinkLevelAddress)),
-    NicDel((c_ushort, IfName, LinkLevelAddress)),
-    NicNoop,
-}
+pub use crate::event::NicEvent;
+pub use crate::info::{NicInfo, NicType};
 
 pub fn monitor() -> Result<NicMonitor> {
     Ok(NicMonitor {
@@ -38,47 +35,97 @@ pub struct NicMonitor {
 // Source: https://github.com/freebsd/freebsd-src/blob/main/sbin/route/route.c
 
 impl NicMonitor {
-    fn parse_msg(&self, rt_buf: &RtBuf, _len: isize) -> Option<NicEvent> {
+    fn parse_msg(rt_buf: &RtBuf, len: isize) -> std::result::Result<NicEvent, rtbuf::Error> {
+        let msglen = rtbuf::msglen(rt_buf, len)?;
         let rtm = rt_buf.as_rt_msghdr();
 
         if rtm.rtm_version as i32 != libc::RTM_VERSION {
-            eprintln!(
+            log::warn!(
                 "routing message version {} is not understood",
                 rtm.rtm_version
             );
-            return Some(NicEvent::NicNoop);
+            return Ok(NicEvent::NicNoop);
         }
 
         let event = match rtm.rtm_type() {
-            Rtm::RtmNewmaddr => {
-                let nic = rt_buf.as_ifma_msghdr().get_ifp()?.get_link_ether()?;
-                NicEvent::NicNew(nic)
-            }
-            Rtm::RtmDelmaddr => {
-                let nic = rt_buf.as_ifma_msghdr().get_ifp()?.get_link_ether()?;
-                NicEvent::NicDel(nic)
-            }
+            Rtm::RtmNewmaddr => match Self::parse_ifp(rt_buf, msglen)? {
+                Some(nic) => NicEvent::NicNew(nic),
+                None => NicEvent::NicNoop,
+            },
+            Rtm::RtmDelmaddr => match Self::parse_ifp(rt_buf, msglen)? {
+                Some(nic) => NicEvent::NicDel(nic),
+                None => NicEvent::NicNoop,
+            },
+            Rtm::RtmIfinfo => Self::parse_link(rt_buf, msglen)?,
             Rtm::RtmInvalid(value) => {
-                eprintln!("{:?}", Rtm::RtmInvalid(value));
+                log::warn!("{:?}", Rtm::RtmInvalid(value));
                 NicEvent::NicNoop
             }
             _ => NicEvent::NicNoop,
         };
 
-        Some(event)
+        Ok(event)
+    }
+
+    /// Link state of an Ethernet interface, named after its index as
+    /// getifaddrs lists it, an RTM_IFINFO carrying no name.
+    fn parse_link(rt_buf: &RtBuf, msglen: usize) -> std::result::Result<NicEvent, rtbuf::Error> {
+        let ifm = rt_buf.as_if_msghdr(msglen)?;
+        if ifm.ifi_type() != Ift::IftEther {
+            return Ok(NicEvent::NicNoop);
+        }
+
+        // Gone meanwhile, its RTM_DELMADDR follows.
+        let index = ifm.ifm_index.into();
+        let Some(ifname) = Self::ifname(index) else {
+            return Ok(NicEvent::NicNoop);
+        };
+
+        let link = (index, ifname);
+        let event = match ifm.is_link_up() {
+            true => NicEvent::NicUp(link),
+            false => NicEvent::NicDown(link),
+        };
+
+        Ok(event)
+    }
+
+    fn ifname(index: c_int) -> Option<IfName> {
+        match ifaddrs::get_ifaddrs() {
+            Ok(ifaddrs) => ifaddrs
+                .links()
+                .into_iter()
+                .find(|nic| nic.index == index)
+                .map(|nic| nic.ifname),
+            Err(error) => {
+                log::warn!("{}", error);
+                None
+            }
+        }
+    }
+
+    fn parse_ifp(
+        rt_buf: &RtBuf,
+        msglen: usize,
+    ) -> std::result::Result<Option<(c_int, IfName, LinkLevelAddress)>, rtbuf::Error> {
+        match rt_buf.as_ifma_msghdr(msglen)?.get_ifp()? {
+            Some(ifp) => Ok(ifp.link_ether()),
+            None => Ok(None),
+        }
     }
 }
 
+/// A malformed message comes back as an error of kind `Decode`, after
+/// which reading goes on with the next message.
 impl Iterator for NicMonitor {
     type Item = Result<NicEvent>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let mut rt_buf = rtbuf::new();
-        let event = match self.socket.read(&mut rt_buf) {
-            Ok(ReadLength(len)) => match self.parse_msg(&rt_buf, len) {
-                Some(event) => Ok(event),
-                None => Ok(NicEvent::NicNoop),
-            },
+        let event = match self.socket.read(&mut rt_buf[..]) {
+            Ok(ReadLength(len)) => Self::parse_msg(&rt_buf, len).map_err(|error| {
+                crate::Error::detailed(Operation::Read, None, &error).with_kind(ErrorKind::Decode)
+            }),
             Ok(EndOfRead) => return None,
             Err(err) => Err(err),
         };
@@ -87,6 +134,11 @@ impl Iterator for NicMonitor {
     }
 }
 
+/// Lists every interface with an AF_LINK address, in getifaddrs order.
+pub fn list() -> Result<Vec<NicInfo>> {
+    Ok(ifaddrs::get_ifaddrs()?.links())
+}
+
 pub fn get_lladdr(ifname: &IfName) -> Result<LinkLevelAddress> {
     let mut ifreq = ifreq::new().with_name(ifname);
 
@@ -103,6 +155,29 @@ pub fn set_lladdr(ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()> {
 
 #[cfg(test)]
 pub(crate) mod mocks {
+    pub(crate) mod ifaddrs {
+        use mockdown::{mockdown, Mock};
+
+        use crate::info::NicInfo;
+        use crate::Result;
+
+        pub(crate) struct GetIfAddrs(pub fn() -> Result<IfAddrs>);
+        pub(crate) struct Links(pub fn() -> Vec<NicInfo>);
+
+        pub(crate) fn get_ifaddrs() -> Result<IfAddrs> {
+            mockdown().next(|GetIfAddrs(mock)| mock()).unwrap()
+        }
+
+        #[derive(Debug)]
+        pub(crate) struct IfAddrs();
+
+        impl IfAddrs {
+            pub(crate) fn links(&self) -> Vec<NicInfo> {
+                mockdown().next(|Links(mock)| mock()).unwrap()
+            }
+        }
+    }
+
     pub(crate) mod socket {
         use libc::c_char;
         use mockdown::{mockdown, Mock};
@@ -117,11 +192,11 @@ pub(crate) mod mocks {
         pub(crate) struct Read(pub fn(buf: &mut [c_char]) -> Result<ReadResult>);
 
         pub(crate) fn open_local_dgram() -> Result<OpenSocket> {
-            mockdown().next(|OpenLocalDgram(mock)| mock())?
+            mockdown().next(|OpenLocalDgram(mock)| mock()).unwrap()
         }
 
         pub(crate) fn open_route_raw() -> Result<OpenSocket> {
-            mockdown().next(|OpenRouteRaw(mock)| mock())?
+            mockdown().next(|OpenRouteRaw(mock)| mock()).unwrap()
         }
 
         #[derive(Debug)]
@@ -129,14 +204,15 @@ pub(crate) mod mocks {
 
         impl OpenSocket {
             pub(crate) fn get_lladdr(&self, ifreq: &mut libc::ifreq) -> Result<()> {
-                mockdown().next(|GetLLAddr(mock)| mock(ifreq))?
+                mockdown().next(|GetLLAddr(mock)| mock(ifreq)).unwrap()
             }
 
             pub(crate) fn set_lladdr(&self, ifreq: &mut libc::ifreq) -> Result<()> {
-                mockdown().next(|SetLLAddr(mock)| mock(ifreq))?
+                mockdown().next(|SetLLAddr(mock)| mock(ifreq)).unwrap()
             }
+
             pub(crate) fn read(&self, buf: &mut [c_char]) -> Result<ReadResult> {
-                mockdown().next(|Read(mock)| mock(buf))?
+                mockdown().next(|Read(mock)| mock(buf)).unwrap()
             }
         }
     }
@@ -148,18 +224,61 @@ mod tests {
 
     use mockdown::{mockdown, Mock};
 
+    use crate::error::{ErrorKind, Operation};
     use crate::ifname::IfName;
     use crate::lladdr::LinkLevelAddress;
-    use crate::Result;
+    use crate::random;
+    use crate::{Error, Result};
 
+    use super::super::socket::ReadResult::ReadLength;
     use super::super::types::ifreq::{IfReq, IfReqMut};
+    use super::super::types::rtbuf::{self, RtBuf};
+    use super::mocks::ifaddrs::{self, IfAddrs};
     use super::mocks::socket::{self, OpenSocket};
-    use super::{get_lladdr, set_lladdr};
+    use super::{get_lladdr, list, monitor, set_lladdr};
+    use super::{NicEvent, NicInfo, NicMonitor, NicType};
 
     static IFNAME: LazyLock<IfName> = LazyLock::new(|| "enx".try_into().unwrap());
     static LLADDR: LazyLock<LinkLevelAddress> =
         LazyLock::new(|| "00:11:22:33:44:55".parse().unwrap());
 
+    #[test]
+    fn test_list() -> Result<()> {
+        mockdown()
+            .expect(ifaddrs::GetIfAddrs(|| Ok(IfAddrs())))
+            .expect(ifaddrs::Links(|| {
+                vec![NicInfo {
+                    index: 4,
+                    ifname: *IFNAME,
+                    lladdr: Some(*LLADDR),
+                    nic_type: NicType::Ether,
+                    flags: 0x8863,
+                    mtu: 1500,
+                }]
+            }));
+
+        let nics = list()?;
+
+        assert_eq!(nics.len(), 1);
+        assert_eq!(nics[0].ifname, *IFNAME);
+        assert_eq!(nics[0].lladdr, Some(*LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_error() {
+        mockdown().expect(ifaddrs::GetIfAddrs(|| {
+            Err(Error::new(Operation::GetLinks, ErrorKind::Other))
+        }));
+
+        let expected_error = Error::new(Operation::GetLinks, ErrorKind::Other);
+
+        let error = list().unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
     #[test]
     fn test_get_lladdr() -> Result<()> {
         mockdown()
@@ -180,14 +299,14 @@ mod tests {
     #[test]
     fn test_get_lladdr_open_error() {
         mockdown().expect(socket::OpenLocalDgram(|| {
-            Err("GetLinkLevelAddressOpenError".into())
+            Err(Error::new(Operation::OpenSocket, ErrorKind::Other))
         }));
 
-        let expected_error = "GetLinkLevelAddressOpenError";
+        let expected_error = Error::new(Operation::OpenSocket, ErrorKind::Other);
 
         let error = get_lladdr(&IFNAME).unwrap_err();
 
-        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(error, expected_error);
     }
 
     #[test]
@@ -196,14 +315,14 @@ mod tests {
             .expect(socket::OpenLocalDgram(|| Ok(OpenSocket())))
             .expect(socket::GetLLAddr(|ifreq| {
                 assert_eq!(ifreq.name(), *IFNAME);
-                Err("GetLinkLevelAddressError".into())
+                Err(Error::new(Operation::GetLinkLevelAddress, ErrorKind::Other))
             }));
 
-        let expected_error = "GetLinkLevelAddressError";
+        let expected_error = Error::new(Operation::GetLinkLevelAddress, ErrorKind::Other);
 
         let error = get_lladdr(&IFNAME).unwrap_err();
 
-        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(error, expected_error);
     }
 
     #[test]
@@ -222,14 +341,14 @@ mod tests {
     #[test]
     fn test_set_lladdr_open_error() {
         mockdown().expect(socket::OpenLocalDgram(|| {
-            Err("SetLinkLevelAddressOpenError".into())
+            Err(Error::new(Operation::OpenSocket, ErrorKind::Other))
         }));
 
-        let expected_error = "SetLinkLevelAddressOpenError";
+        let expected_error = Error::new(Operation::OpenSocket, ErrorKind::Other);
 
         let error = set_lladdr(&IFNAME, &LLADDR).unwrap_err();
 
-        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(error, expected_error);
     }
 
     #[test]
@@ -239,13 +358,233 @@ mod tests {
             .expect(socket::SetLLAddr(|ifreq| {
                 assert_eq!(ifreq.name(), *IFNAME);
                 assert_eq!(ifreq.lladdr(), *LLADDR);
-                Err("SetLinkLevelAddressError".into())
+                Err(Error::new(Operation::SetLinkLevelAddress, ErrorKind::Other))
             }));
 
-        let expected_error = "SetLinkLevelAddressError";
+        let expected_error = Error::new(Operation::SetLinkLevelAddress, ErrorKind::Other);
 
         let error = set_lladdr(&IFNAME, &LLADDR).unwrap_err();
 
-        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(error, expected_error);
+    }
+
+    // RTM_NEWMADDR with only RTA_IFP, the AF_LINK address of "en0" (index 4,
+    // 00:11:22:33:44:55), as laid out on little-endian hosts.
+    const RTM_NEWMADDR_EN0: [u8; 36] = [
+        0x24, 0x00, 0x05, 0x0f, 0x10, 0x00, 0x00, 0x00, // msglen, version, type, addrs
+        0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, // flags, index
+        0x14, 0x12, 0x04, 0x00, 0x06, 0x03, 0x06, 0x00, // sdl_len .. sdl_slen
+        0x65, 0x6e, 0x30, 0x00, 0x11, 0x22, 0x33, 0x44, // "en0", 00:11:22:33:44
+        0x55, 0x00, 0x00, 0x00, // 55
+    ];
+
+    // RTM_DELMADDR for "bridge100" (index 9), whose name and address run past
+    // the 12 bytes declared for sdl_data.
+    const RTM_DELMADDR_BRIDGE100: [u8; 40] = [
+        0x28, 0x00, 0x05, 0x10, 0x10, 0x00, 0x00, 0x00, // msglen, version, type, addrs
+        0x00, 0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, // flags, index
+        0x17, 0x12, 0x09, 0x00, 0x06, 0x09, 0x06, 0x00, // sdl_len .. sdl_slen
+        0x62, 0x72, 0x69, 0x64, 0x67, 0x65, 0x31, 0x30, // "bridge10
+        0x30, 0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x00, // 0", 00:11:22:33:44:55
+    ];
+
+    // RTM_IFINFO for "en0" (index 4, IFT_ETHER) with `ifm_flags`, as laid out
+    // on little-endian hosts. if_data is zeroed past its leading bytes.
+    fn rtm_ifinfo_en0(ifm_flags: u32) -> [u8; 112] {
+        let mut msg = [0; 112];
+        msg[..24].copy_from_slice(&[
+            0x70, 0x00, 0x05, 0x0e, 0x00, 0x00, 0x00, 0x00, // msglen, version, type, addrs
+            0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, // flags, index
+            0x06, 0x00, 0x00, 0x06, 0x0e, 0x00, 0x00, 0x00, // ifi_type .. ifi_unused1
+        ]);
+        msg[8..12].copy_from_slice(&ifm_flags.to_ne_bytes());
+        msg
+    }
+
+    // IFF_UP, IFF_BROADCAST, IFF_SMART, IFF_RUNNING, IFF_SIMPLEX and
+    // IFF_MULTICAST, then the same without IFF_RUNNING.
+    const IFF_EN0_UP: u32 = 0x8863;
+    const IFF_EN0_DOWN: u32 = 0x8823;
+
+    fn expect_links() {
+        mockdown()
+            .expect(ifaddrs::GetIfAddrs(|| Ok(IfAddrs())))
+            .expect(ifaddrs::Links(|| {
+                vec![NicInfo {
+                    index: 4,
+                    ifname: "en0".try_into().unwrap(),
+                    lladdr: Some(*LLADDR),
+                    nic_type: NicType::Ether,
+                    flags: IFF_EN0_UP,
+                    mtu: 1500,
+                }]
+            }));
+    }
+
+    fn rt_buf(msg: &[u8]) -> RtBuf {
+        let mut rt_buf = rtbuf::new();
+        for (c, b) in rt_buf.iter_mut().zip(msg) {
+            *c = *b as _;
+        }
+        rt_buf
+    }
+
+    fn parse(msg: &[u8]) -> String {
+        match NicMonitor::parse_msg(&rt_buf(msg), msg.len() as isize) {
+            Ok(event) => format!("{:?}", event),
+            Err(error) => format!("{:?}", error),
+        }
+    }
+
+    #[test]
+    fn test_monitor() -> Result<()> {
+        mockdown()
+            .expect(socket::OpenRouteRaw(|| Ok(OpenSocket())))
+            .expect(socket::Read(|buf| {
+                for (c, b) in buf.iter_mut().zip(RTM_NEWMADDR_EN0) {
+                    *c = b as _;
+                }
+                Ok(ReadLength(RTM_NEWMADDR_EN0.len() as isize))
+            }))
+            .expect(socket::Read(|_buf| Ok(ReadLength(2))))
+            .expect(socket::Read(|buf| {
+                for (c, b) in buf.iter_mut().zip(RTM_DELMADDR_BRIDGE100) {
+                    *c = b as _;
+                }
+                Ok(ReadLength(RTM_DELMADDR_BRIDGE100.len() as isize))
+            }));
+
+        let mut monitor = monitor()?;
+
+        assert_eq!(
+            monitor.next().unwrap()?,
+            NicEvent::NicNew((4, "en0".try_into()?, *LLADDR))
+        );
+
+        // A truncated message, after which reading goes on.
+        let error = monitor.next().unwrap().unwrap_err();
+
+        assert_eq!(
+            format!("{}", error),
+            "RtBuf::TruncatedError { read: 2, needed: 4 }"
+        );
+        assert_eq!(error.kind(), ErrorKind::Decode);
+        assert!(matches!(
+            monitor.next(),
+            Some(Ok(NicEvent::NicDel((9, ..))))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_msg_long_name() {
+        let expected_event = "NicDel((9, \"bridge100\", \"00:11:22:33:44:55\"))";
+
+        assert_eq!(parse(&RTM_DELMADDR_BRIDGE100), expected_event);
+    }
+
+    #[test]
+    fn test_parse_msg_truncated() {
+        let expected_error = "RtBuf::MessageLengthError { rtm_msglen: 36, read: 35 }";
+
+        assert_eq!(parse(&RTM_NEWMADDR_EN0[..35]), expected_error);
+    }
+
+    #[test]
+    fn test_parse_msg_gateway_before_ifp() {
+        let expected_event = "NicNew((4, \"en0\", \"00:11:22:33:44:55\"))";
+        // RTA_GATEWAY holds the 14 bytes of 33:33:00:00:00:fb, padded to 16.
+        let msg = [
+            &[0x34, 0x00, 0x05, 0x0f, 0x12, 0x00, 0x00, 0x00][..],
+            &[0x00, 0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00],
+            &[0x0e, 0x12, 0x04, 0x00, 0x06, 0x00, 0x06, 0x00],
+            &[0x33, 0x33, 0x00, 0x00, 0x00, 0xfb, 0x00, 0x00],
+            &RTM_NEWMADDR_EN0[16..],
+        ]
+        .concat();
+
+        assert_eq!(parse(&msg), expected_event);
+    }
+
+    #[test]
+    fn test_parse_msg_sockaddr_past_msglen() {
+        let expected_error = "Rtax::TruncatedError { rtax: Ifp, sa_len: 20, left: 19 }";
+        let mut msg = RTM_NEWMADDR_EN0;
+        msg[0] = 35;
+
+        assert_eq!(parse(&msg), expected_error);
+    }
+
+    #[test]
+    fn test_parse_msg_sdl_data_past_sdl_len() {
+        let expected_error = "Rtax::SockaddrDlDataError { rtax: Ifp, sdl_nlen: 9, sdl_alen: 6, sdl_slen: 0, sa_len: 20 }";
+        let mut msg = RTM_DELMADDR_BRIDGE100;
+        msg[16] = 20;
+
+        assert_eq!(parse(&msg), expected_error);
+    }
+
+    #[test]
+    fn test_parse_msg_link_up() {
+        expect_links();
+
+        assert_eq!(parse(&rtm_ifinfo_en0(IFF_EN0_UP)), "NicUp((4, \"en0\"))");
+    }
+
+    #[test]
+    fn test_parse_msg_link_down() {
+        expect_links();
+
+        assert_eq!(
+            parse(&rtm_ifinfo_en0(IFF_EN0_DOWN)),
+            "NicDown((4, \"en0\"))"
+        );
+    }
+
+    #[test]
+    fn test_parse_msg_link_of_unknown_index() {
+        expect_links();
+        let mut msg = rtm_ifinfo_en0(IFF_EN0_UP);
+        msg[12] = 5;
+
+        assert_eq!(parse(&msg), "NicNoop");
+    }
+
+    #[test]
+    fn test_parse_msg_link_getifaddrs_error() {
+        mockdown().expect(ifaddrs::GetIfAddrs(|| {
+            Err(Error::new(Operation::GetLinks, ErrorKind::Other))
+        }));
+
+        assert_eq!(parse(&rtm_ifinfo_en0(IFF_EN0_UP)), "NicNoop");
+    }
+
+    #[test]
+    fn test_parse_msg_link_not_ether() {
+        let mut msg = rtm_ifinfo_en0(IFF_EN0_UP);
+        msg[16] = 0x18;
+
+        assert_eq!(parse(&msg), "NicNoop");
+    }
+
+    #[test]
+    fn test_parse_msg_link_truncated() {
+        let expected_error = "RtBuf::TruncatedError { read: 24, needed: 112 }";
+        let mut msg = rtm_ifinfo_en0(IFF_EN0_UP);
+        msg[0] = 24;
+
+        assert_eq!(parse(&msg[..24]), expected_error);
+    }
+
+    #[test]
+    fn test_parse_msg_fuzz() {
+        random::fuzz(
+            0x9e3779b97f4a7c15,
+            &[&RTM_NEWMADDR_EN0, &RTM_DELMADDR_BRIDGE100],
+            |msg| {
+                parse(msg);
+            },
+        );
     }
 }
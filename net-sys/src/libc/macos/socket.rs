@@ -1,7 +1,8 @@
-use std::fmt::{Debug, Display};
+use std::fmt::Debug;
 
 use libc::{c_char, c_int, c_void, ssize_t};
 
+use crate::error::Operation;
 use crate::ifname::IfName;
 use crate::lladdr::LinkLevelAddress;
 use crate::Result;
@@ -23,14 +24,6 @@ enum Error {
     Close(c_int, c_int, c_int),
 }
 
-impl std::error::Error for Error {}
-
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
 impl Debug for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -75,6 +68,37 @@ impl Debug for Error {
     }
 }
 
+impl From<Error> for crate::Error {
+    fn from(error: Error) -> Self {
+        let detailed = |operation| crate::Error::detailed(operation, error.errno(), &error);
+        match &error {
+            Error::OpenLocalDgram(_, _) => detailed(Operation::OpenSocket),
+            Error::GetLinkLevelAddress(_, ifname, _, _) => {
+                detailed(Operation::GetLinkLevelAddress).with_ifname(*ifname)
+            }
+            Error::SetLinkLevelAddress(_, ifname, lladdr, _, _) => {
+                detailed(Operation::SetLinkLevelAddress)
+                    .with_ifname(*ifname)
+                    .with_lladdr(*lladdr)
+            }
+            Error::Read(_, _, _) => detailed(Operation::Read),
+            Error::Close(_, _, _) => detailed(Operation::Close),
+        }
+    }
+}
+
+impl Error {
+    fn errno(&self) -> Option<c_int> {
+        match self {
+            Error::OpenLocalDgram(_, errno)
+            | Error::GetLinkLevelAddress(_, _, _, errno)
+            | Error::SetLinkLevelAddress(_, _, _, _, errno)
+            | Error::Read(_, _, errno)
+            | Error::Close(_, _, errno) => Some(*errno),
+        }
+    }
+}
+
 pub(crate) fn open_local_dgram() -> Result<OpenSocket> {
     match sys::socket(libc::PF_LOCAL, libc::SOCK_DGRAM, 0) {
         fd if fd >= 0 => Ok(OpenSocket { fd }),
@@ -152,7 +176,7 @@ impl Drop for OpenSocket {
             ret => {
                 let errno = sys::errno();
                 let error = Error::Close(fd, ret, errno);
-                eprintln!("Error: {:?}", error);
+                log::error!("{:?}", error);
             }
         };
     }
@@ -208,6 +232,7 @@ mod tests {
     use libc::c_int;
     use mockdown::{mockdown, Mock};
 
+    use crate::error::{ErrorKind, Operation};
     use crate::ifname::IfName;
     use crate::lladdr::LinkLevelAddress;
     use crate::Result;
@@ -266,6 +291,7 @@ mod tests {
 
         assert_eq!(format!("{}", error), expected_error);
         assert_eq!(format!("{:?}", error), expected_error);
+        assert_eq!(error.errno, Some(libc::EPERM));
     }
 
     #[test]
@@ -389,6 +415,10 @@ mod tests {
         assert_eq!(format!("{}", error), expected_error);
         assert_eq!(format!("{:?}", error), expected_error);
 
+        assert_eq!(error.operation, Operation::SetLinkLevelAddress);
+        assert_eq!((error.ifname, error.lladdr), (Some(*IFNAME), Some(*LLADDR)));
+        assert_eq!(error.kind(), ErrorKind::InvalidInput);
+
         Ok(())
     }
 
@@ -27,6 +27,18 @@ pub(crate) fn close(fd: c_int) -> c_int {
     unsafe { libc::close(fd) }
 }
 
+#[cfg(not(test))]
+#[cfg(not(tarpaulin_include))]
+pub(crate) fn getifaddrs(ifap: *mut *mut libc::ifaddrs) -> c_int {
+    unsafe { libc::getifaddrs(ifap) }
+}
+
+#[cfg(not(test))]
+#[cfg(not(tarpaulin_include))]
+pub(crate) fn freeifaddrs(ifa: *mut libc::ifaddrs) {
+    unsafe { libc::freeifaddrs(ifa) }
+}
+
 #[cfg(not(test))]
 #[cfg(not(tarpaulin_include))]
 pub(crate) fn errno() -> c_int {
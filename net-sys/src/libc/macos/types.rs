@@ -1,5 +1,6 @@
-pub(crate) mod ifreq;
-pub(crate) mod sockaddrdl;
 pub(crate) mod ifmamsghdr;
+pub(crate) mod ifmsghdr;
+pub(crate) mod ifreq;
 pub(crate) mod rtbuf;
 pub(crate) mod rtmsghdr;
+pub(crate) mod sockaddrdl;
@@ -1,54 +1,59 @@
 use core::fmt::Debug;
 use std::ops::Deref;
-use std::mem;
+use std::slice;
 
 use libc::{c_char, c_int};
 
+use crate::rtax::{self, Rtax, RtaxIter, Sockaddr, SockaddrDl, SA_ALIGN_DARWIN};
+
 use super::super::defs::rtm::Rtm;
-use super::rtbuf::RtBuf;
+use super::rtbuf::Error;
 
-pub(crate) struct IfMaMsgHdr<'a>(pub(crate) &'a RtBuf);
+/// Message of `rtm_msglen` bytes from an `RtBuf`, at least as long as its
+/// header.
+pub(crate) struct IfMaMsgHdr<'a>(&'a [c_char]);
 
 impl<'a> Deref for IfMaMsgHdr<'a> {
     type Target = libc::ifma_msghdr;
 
     fn deref(&self) -> &Self::Target {
-        unsafe { mem::transmute(self.0) }
+        unsafe { &*self.0.as_ptr().cast() }
     }
 }
 
 impl<'a> IfMaMsgHdr<'a> {
-    pub fn ifmam_type(&self) -> Rtm {
-        Rtm::from(self.ifmam_type as c_int)
+    pub(crate) fn new(msg: &'a [c_char]) -> Result<Self, Error> {
+        if msg.len() < Self::HDR_SIZE {
+            return Err(Error::Truncated(msg.len(), Self::HDR_SIZE));
+        }
+        Ok(Self(msg))
     }
 
-    /// Maps `rta` to its corresponding index in the ifma_msg's address array,
-    /// if it is set in `ifmam_addrs` bitmask.
-    ///
-    /// # Returns
-    /// `Some(index)` if `rta` is set in `ifmam_addrs`, `None` otherwise.
-    fn get_rta_index(&self, rta: c_int) -> Option<usize> {
-        (libc::RTAX_DST..libc::RTAX_MAX)
-            .map(|rtax| 1 << rtax)
-            .filter(|bitmask| self.ifmam_addrs & *bitmask != 0)
-            .position(|bitmask| rta & bitmask != 0)
+    pub fn ifmam_type(&self) -> Rtm {
+        Rtm::from(self.ifmam_type as c_int)
     }
 
     const HDR_SIZE: usize = size_of::<libc::ifma_msghdr>();
-    const SDL_SIZE: usize = size_of::<libc::sockaddr_dl>();
 
-    fn get_rta_buf(&self, rta: c_int) -> Option<&[c_char]> {
-        let index = self.get_rta_index(rta)?;
-        let data = &(self.0);
-        let (_hdr, data) = data.split_at(Self::HDR_SIZE);
-        let (_start, data) = data.split_at(Self::SDL_SIZE * index);
-        let (buf, _end) = data.split_at(Self::SDL_SIZE);
-        Some(buf)
+    /// Addresses following the header, as `ifmam_addrs` lists them.
+    pub fn addrs(&self) -> RtaxIter<'a> {
+        let addrs = &self.0[Self::HDR_SIZE..];
+        let addrs = unsafe { slice::from_raw_parts(addrs.as_ptr().cast::<u8>(), addrs.len()) };
+        rtax::iter(addrs, self.ifmam_addrs, SA_ALIGN_DARWIN)
     }
 
-    pub fn get_ifp(&self) -> Option<&libc::sockaddr_dl> {
-        let buf = self.get_rta_buf(libc::RTA_IFP)?;
-        Some(unsafe { mem::transmute(buf.as_ptr()) })
+    /// The RTA_IFP address, `None` if the message has none or it is not an
+    /// AF_LINK address.
+    pub fn get_ifp(&self) -> Result<Option<SockaddrDl<'a>>, Error> {
+        for addr in self.addrs() {
+            if let (Rtax::Ifp, sockaddr) = addr? {
+                return match sockaddr {
+                    Sockaddr::Link(sdl) => Ok(Some(sdl)),
+                    _ => Ok(None),
+                };
+            }
+        }
+        Ok(None)
     }
 }
 
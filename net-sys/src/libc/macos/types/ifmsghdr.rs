@@ -0,0 +1,64 @@
+use core::fmt::Debug;
+use std::ops::Deref;
+
+use libc::{c_char, c_int};
+
+use super::super::defs::ift::Ift;
+use super::super::defs::rtm::Rtm;
+use super::rtbuf::Error;
+
+// https://github.com/apple/darwin-xnu/blob/xnu-7195.121.3/bsd/net/if.h#L174
+
+/// RTM_IFINFO message of `rtm_msglen` bytes from an `RtBuf`, at least as
+/// long as its header. The kernel sends it when the flags of an interface
+/// change, with no address following it.
+pub(crate) struct IfMsgHdr<'a>(&'a [c_char]);
+
+impl<'a> Deref for IfMsgHdr<'a> {
+    type Target = libc::if_msghdr;
+
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.0.as_ptr().cast() }
+    }
+}
+
+impl<'a> IfMsgHdr<'a> {
+    pub(crate) fn new(msg: &'a [c_char]) -> Result<Self, Error> {
+        if msg.len() < Self::HDR_SIZE {
+            return Err(Error::Truncated(msg.len(), Self::HDR_SIZE));
+        }
+        Ok(Self(msg))
+    }
+
+    const HDR_SIZE: usize = size_of::<libc::if_msghdr>();
+
+    pub fn ifm_type(&self) -> Rtm {
+        Rtm::from(self.ifm_type as c_int)
+    }
+
+    pub fn ifi_type(&self) -> Ift {
+        Ift::from(self.ifm_data.ifi_type as c_int)
+    }
+
+    /// Whether the link can carry packets. Darwin's `if_data` has no
+    /// `ifi_link_state`, unlike FreeBSD's, so this is IFF_UP and IFF_RUNNING
+    /// as `ifm_flags` report them.
+    pub fn is_link_up(&self) -> bool {
+        let up = libc::IFF_UP | libc::IFF_RUNNING;
+        self.ifm_flags & up == up
+    }
+}
+
+impl<'a> Debug for IfMsgHdr<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("if_msghdr")
+            .field("ifm_msglen", &self.ifm_msglen)
+            .field("ifm_version", &self.ifm_version)
+            .field("ifm_type", &self.ifm_type())
+            .field("ifm_addrs", &format!("0x{:x}", &self.ifm_addrs))
+            .field("ifm_flags", &format!("0x{:x}", &self.ifm_flags))
+            .field("ifm_index", &self.ifm_index)
+            .field("ifi_type", &self.ifi_type())
+            .finish()
+    }
+}
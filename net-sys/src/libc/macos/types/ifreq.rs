@@ -34,7 +34,11 @@ pub(crate) trait IfReqMut {
 impl IfReqMut for libc::ifreq {
     fn change_name(&mut self, ifname: &IfName) {
         unsafe {
-            ptr::copy_nonoverlapping(ifname.as_signed_ptr(), self.ifr_name.as_mut_ptr(), ifname.len());
+            ptr::copy_nonoverlapping(
+                ifname.as_signed_ptr(),
+                self.ifr_name.as_mut_ptr(),
+                ifname.len(),
+            );
         }
     }
 
@@ -184,7 +188,11 @@ pub(crate) mod tests {
         let mut ifreq = new();
 
         unsafe {
-            std::ptr::copy_nonoverlapping(NAME.as_bytes_ptr(), ifreq.ifr_name.as_mut_ptr(), NAME.len());
+            std::ptr::copy_nonoverlapping(
+                NAME.as_bytes_ptr(),
+                ifreq.ifr_name.as_mut_ptr(),
+                NAME.len(),
+            );
         }
 
         let ifname = ifreq.name();
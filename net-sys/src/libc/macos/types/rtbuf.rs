@@ -1,26 +1,170 @@
+use core::fmt::Debug;
+use std::mem;
+use std::ops::{Deref, DerefMut};
+
 use libc::c_char;
 
-use super::{ifmamsghdr::IfMaMsgHdr, rtmsghdr::RtMsgHdr};
+use crate::rtax;
+
+use super::{ifmamsghdr::IfMaMsgHdr, ifmsghdr::IfMsgHdr, rtmsghdr::RtMsgHdr};
 
 const RT_BUF_SIZE: usize = 2048;
 
-pub(crate) type RtBuf = [c_char; RT_BUF_SIZE];
+/// Aligned like the `m_rtmsg` union of route.c, so that the headers and
+/// the addresses following them can be read in place.
+#[repr(C, align(8))]
+pub(crate) struct RtBuf([c_char; RT_BUF_SIZE]);
+
+impl Deref for RtBuf {
+    type Target = [c_char; RT_BUF_SIZE];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for RtBuf {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+pub(crate) fn new() -> RtBuf {
+    RtBuf([0; RT_BUF_SIZE])
+}
+
+// rtm_msglen, rtm_version and rtm_type lead every routing message.
+const RTM_PREFIX_SIZE: usize = mem::offset_of!(libc::rt_msghdr, rtm_type) + 1;
+
+/// Why a routing message could not be decoded, the kernel or whoever wrote
+/// to the socket having sent fewer bytes than its lengths claim.
+pub(crate) enum Error {
+    Truncated(usize, usize),
+    MessageLength(usize, usize),
+    Rtax(rtax::Error),
+}
 
-pub fn new() -> RtBuf {
-    [0; RT_BUF_SIZE]
+impl From<rtax::Error> for Error {
+    fn from(error: rtax::Error) -> Self {
+        Error::Rtax(error)
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Truncated(read, needed) => f
+                .debug_struct("RtBuf::TruncatedError")
+                .field("read", read)
+                .field("needed", needed)
+                .finish(),
+            Error::MessageLength(msglen, read) => f
+                .debug_struct("RtBuf::MessageLengthError")
+                .field("rtm_msglen", msglen)
+                .field("read", read)
+                .finish(),
+            Error::Rtax(error) => write!(f, "{:?}", error),
+        }
+    }
+}
+
+/// Length of the message the kernel wrote at the start of `rt_buf`, checked
+/// against the `read` bytes actually received.
+pub(crate) fn msglen(rt_buf: &RtBuf, read: isize) -> Result<usize, Error> {
+    let read = usize::try_from(read).unwrap_or(0).min(RT_BUF_SIZE);
+    if read < RTM_PREFIX_SIZE {
+        return Err(Error::Truncated(read, RTM_PREFIX_SIZE));
+    }
+
+    let msglen = u16::from_ne_bytes([rt_buf[0] as u8, rt_buf[1] as u8]) as usize;
+    if msglen < RTM_PREFIX_SIZE || msglen > read {
+        return Err(Error::MessageLength(msglen, read));
+    }
+
+    Ok(msglen)
 }
 
 pub(crate) trait AsMsgHdr {
     fn as_rt_msghdr(&self) -> RtMsgHdr;
-    fn as_ifma_msghdr(&self) -> IfMaMsgHdr;
+    fn as_ifma_msghdr(&self, msglen: usize) -> Result<IfMaMsgHdr, Error>;
+    fn as_if_msghdr(&self, msglen: usize) -> Result<IfMsgHdr, Error>;
 }
 
 impl AsMsgHdr for RtBuf {
+    /// Header of any message, only its leading `rtm_msglen`, `rtm_version`
+    /// and `rtm_type` being known to be part of it.
     fn as_rt_msghdr(&self) -> RtMsgHdr {
         RtMsgHdr(self)
     }
 
-    fn as_ifma_msghdr(&self) -> IfMaMsgHdr {
-        IfMaMsgHdr(self)
+    /// Header of an RTM_NEWMADDR or RTM_DELMADDR message `msglen` long.
+    fn as_ifma_msghdr(&self, msglen: usize) -> Result<IfMaMsgHdr, Error> {
+        IfMaMsgHdr::new(&self[..msglen.min(RT_BUF_SIZE)])
+    }
+
+    /// Header of an RTM_IFINFO message `msglen` long.
+    fn as_if_msghdr(&self, msglen: usize) -> Result<IfMsgHdr, Error> {
+        IfMsgHdr::new(&self[..msglen.min(RT_BUF_SIZE)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{msglen, new, AsMsgHdr, Error};
+
+    #[test]
+    fn test_rtbuf_msglen() {
+        let mut rt_buf = new();
+        rt_buf[..2].copy_from_slice(&[40, 0].map(|b| b as _));
+
+        assert!(matches!(msglen(&rt_buf, 40), Ok(40)));
+        assert!(matches!(msglen(&rt_buf, 64), Ok(40)));
+    }
+
+    #[test]
+    fn test_rtbuf_msglen_truncated() {
+        let mut rt_buf = new();
+        rt_buf[..2].copy_from_slice(&[40, 0].map(|b| b as _));
+
+        assert_eq!(
+            format!("{:?}", msglen(&rt_buf, 3).unwrap_err()),
+            "RtBuf::TruncatedError { read: 3, needed: 4 }"
+        );
+        assert_eq!(
+            format!("{:?}", msglen(&rt_buf, 39).unwrap_err()),
+            "RtBuf::MessageLengthError { rtm_msglen: 40, read: 39 }"
+        );
+        assert!(matches!(msglen(&rt_buf, -1), Err(Error::Truncated(0, 4))));
+    }
+
+    #[test]
+    fn test_rtbuf_msglen_too_short() {
+        let mut rt_buf = new();
+        rt_buf[..2].copy_from_slice(&[2, 0].map(|b| b as _));
+
+        assert!(matches!(
+            msglen(&rt_buf, 40),
+            Err(Error::MessageLength(2, 40))
+        ));
+    }
+
+    #[test]
+    fn test_rtbuf_as_ifma_msghdr_truncated() {
+        let rt_buf = new();
+
+        assert!(matches!(
+            rt_buf.as_ifma_msghdr(8),
+            Err(Error::Truncated(8, _))
+        ));
+    }
+
+    #[test]
+    fn test_rtbuf_as_if_msghdr_truncated() {
+        let rt_buf = new();
+
+        assert!(matches!(
+            rt_buf.as_if_msghdr(16),
+            Err(Error::Truncated(16, 112))
+        ));
     }
 }
@@ -1,9 +1,11 @@
 use core::fmt::Debug;
+use std::mem;
 use std::ops::Deref;
 
-use libc::{c_char, c_int, c_ushort};
+use libc::{c_char, c_int};
 
 use crate::ifname::IfName;
+use crate::info::{NicInfo, NicType};
 use crate::lladdr::LinkLevelAddress;
 
 use super::super::defs::af::Af;
@@ -12,7 +14,7 @@ use super::super::defs::ift::Ift;
 trait SockaddrDl {
     fn sdl_family(&self) -> Af;
     fn sdl_type(&self) -> Ift;
-    fn get_data(&self) -> (&[c_char], &[c_char], &[c_char]);
+    fn get_addr(&self) -> Option<&[c_char]>;
 }
 
 impl SockaddrDl for libc::sockaddr_dl {
@@ -24,43 +26,49 @@ impl SockaddrDl for libc::sockaddr_dl {
         Ift::from(self.sdl_type as c_int)
     }
 
-    fn get_data(&self) -> (&[c_char], &[c_char], &[c_char]) {
-        let data = &self.sdl_data;
-        let (name, data) = data.split_at(self.sdl_nlen as usize); // Name
-        let (addr, data) = data.split_at(self.sdl_alen as usize); // Address
-        let (sel, _data) = data.split_at(self.sdl_slen as usize); // Selector
-        (name, addr, sel)
+    fn get_addr(&self) -> Option<&[c_char]> {
+        // sdl_data is declared with 12 bytes but the kernel sizes the structure
+        // by sdl_len, so the address of a long name lies past the declared array.
+        let start = mem::offset_of!(libc::sockaddr_dl, sdl_data) + self.sdl_nlen as usize;
+        let len = self.sdl_alen as usize;
+        if start + len > self.sdl_len as usize {
+            return None;
+        }
+
+        let base = (self as *const libc::sockaddr_dl).cast::<c_char>();
+        Some(unsafe { std::slice::from_raw_parts(base.add(start), len) })
     }
 }
 
-pub(crate) trait LinkEther {
-    fn get_link_ether(&self) -> Option<(c_ushort, IfName, LinkLevelAddress)>;
+pub(crate) trait LinkInfo {
+    fn get_link_info(&self, ifname: IfName, flags: u32, mtu: u32) -> Option<NicInfo>;
 }
 
-impl LinkEther for libc::sockaddr_dl {
-    fn get_link_ether(&self) -> Option<(c_ushort, IfName, LinkLevelAddress)> {
-        if self.sdl_family() != Af::AfLink || self.sdl_type() != Ift::IftEther {
+impl LinkInfo for libc::sockaddr_dl {
+    fn get_link_info(&self, ifname: IfName, flags: u32, mtu: u32) -> Option<NicInfo> {
+        if self.sdl_family() != Af::AfLink {
             return None;
         }
 
-        let (name, addr, _sel) = self.get_data();
-
-        let ifname = match IfName::try_from(name) {
-            Ok(ifname) => ifname,
-            Err(_) => match IfName::try_from(format!("index{}", self.sdl_index)) {
-                Ok(ifname) => ifname,
-                Err(_) => return None,
-            },
+        let nic_type = match self.sdl_type() {
+            Ift::IftEther => NicType::Ether,
+            Ift::IftLoop => NicType::Loopback,
+            Ift::IftInvalid(value) => NicType::Other(value as u32),
         };
 
-        let lladdr = match LinkLevelAddress::try_from(addr) {
-            Ok(lladdr) => lladdr,
-            Err(_) => return None,
+        let lladdr = match self.get_addr() {
+            Some(addr) => LinkLevelAddress::try_from(addr).ok(),
+            None => None,
         };
 
-        let (_, _) = (name, addr);
-
-        Some((self.sdl_index, ifname, lladdr))
+        Some(NicInfo {
+            index: self.sdl_index.into(),
+            ifname,
+            lladdr,
+            nic_type,
+            flags,
+            mtu,
+        })
     }
 }
 
@@ -85,13 +93,8 @@ impl<'a> Debug for SockaddrDlDebug<'a> {
         write!(f, "sdl_alen: {:?}, ", &self.sdl_alen)?;
         write!(f, "sdl_slen: {:?}, ", &self.sdl_slen)?;
 
-        match self.get_link_ether() {
-            Some(nic) => {
-                let (_, ifname, lladdr) = nic;
-                write!(f, "sdl_ifname: {:?}, ", ifname)?;
-                write!(f, "sdl_lladdr: {:?}, ", lladdr)?;
-            }
-            None => (),
+        if let Some(Ok(lladdr)) = self.get_addr().map(LinkLevelAddress::try_from) {
+            write!(f, "sdl_lladdr: {:?}, ", lladdr)?;
         }
 
         write!(f, "sdl_data: {:?} }}", &self.sdl_data)?;
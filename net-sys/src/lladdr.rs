@@ -4,6 +4,7 @@ use std::ops::Deref;
 use std::result::Result;
 use std::str::FromStr;
 
+use crate::error::{ErrorKind, Operation};
 use crate::format::{AsBytes, AsHexColon};
 
 const OCTETS_SIZE: usize = 6;
@@ -17,14 +18,6 @@ enum Error {
     InvalidOctet(String, String, String),
 }
 
-impl std::error::Error for Error {}
-
-impl Display for Error {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self)
-    }
-}
-
 impl Debug for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -44,6 +37,13 @@ impl Debug for Error {
     }
 }
 
+impl From<Error> for crate::Error {
+    fn from(error: Error) -> Self {
+        crate::Error::detailed(Operation::ParseLinkLevelAddress, None, &error)
+            .with_kind(ErrorKind::InvalidInput)
+    }
+}
+
 pub type LLAddr = LinkLevelAddress;
 
 #[derive(Copy, Clone, Hash, PartialEq, Eq)]
@@ -78,6 +78,24 @@ impl Display for LinkLevelAddress {
         write!(fmt, "{}", self.as_hex_colon())
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for LinkLevelAddress {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Colon separated hex octets, validated like `FromStr`, with its error as the
+/// message.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LinkLevelAddress {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = <String as serde::Deserialize>::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl From<&OctetsType> for LinkLevelAddress {
     fn from(octets: &OctetsType) -> Self {
         Self(*octets)
@@ -118,7 +136,7 @@ impl TryFrom<&str> for OctetsVec {
 }
 
 impl FromStr for LinkLevelAddress {
-    type Err = Box<dyn std::error::Error>;
+    type Err = crate::Error;
 
     fn from_str(value: &str) -> Result<Self, Self::Err> {
         let octets = OctetsVec::try_from(value)?;
@@ -134,7 +152,7 @@ impl FromStr for LinkLevelAddress {
 }
 
 impl TryFrom<&[i8]> for LinkLevelAddress {
-    type Error = Box<dyn std::error::Error>;
+    type Error = crate::Error;
 
     fn try_from(value: &[i8]) -> Result<Self, Self::Error> {
         if value.len() != OCTETS_SIZE {
@@ -258,6 +276,37 @@ mod tests {
         assert_eq!(addr_str, "01:02:03:04:05:06");
     }
 
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_link_level_address_serialize() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let addr = LinkLevelAddress(OCTETS);
+
+        assert_eq!(serde_json::to_string(&addr)?, "\"01:02:03:04:05:06\"");
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_link_level_address_deserialize() -> std::result::Result<(), Box<dyn std::error::Error>>
+    {
+        let addr: LinkLevelAddress = serde_json::from_str("\"01:02:03:04:05:06\"")?;
+
+        assert_eq!(addr, LinkLevelAddress(OCTETS));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_link_level_address_deserialize_invalid_octet() {
+        let expected_error = "LinkLevelAddress::InvalidOctetError { value: \"01:02:XX:04:05:06\", octet: \"XX\", error: \"invalid digit found in string\" }";
+
+        let error = serde_json::from_str::<LinkLevelAddress>("\"01:02:XX:04:05:06\"").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
     #[test]
     fn test_link_level_address_debug() {
         let addr = LinkLevelAddress(OCTETS);
@@ -0,0 +1,34 @@
+/// Deterministic xorshift for the decoding fuzz tests, so that a failing
+/// buffer can be found again from its seed.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// Passes 10_000 buffers to `decode`, each one of `fixtures` with a few
+/// bytes overwritten, then cut at a random length. Starting from real
+/// messages keeps most lengths plausible, so that decoding gets past them.
+pub(crate) fn fuzz(seed: u64, fixtures: &[&[u8]], mut decode: impl FnMut(&[u8])) {
+    let mut random = Random(seed);
+
+    for _ in 0..10_000 {
+        let mut buf = fixtures[random.below(fixtures.len())].to_vec();
+        for _ in 0..1 + random.below(4) {
+            let i = random.below(buf.len());
+            buf[i] = random.next() as u8;
+        }
+        buf.truncate(random.below(buf.len() + 1));
+
+        decode(&buf);
+    }
+}
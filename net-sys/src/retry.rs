@@ -0,0 +1,74 @@
+use std::iter;
+use std::time::Duration;
+
+/// How often a change is tried before giving up, `backoff` being the wait
+/// before the first retry and doubling before each next one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Retry {
+    pub attempts: u32,
+    pub backoff: Duration,
+}
+
+impl Retry {
+    /// A single attempt.
+    pub const NONE: Retry = Retry {
+        attempts: 1,
+        backoff: Duration::ZERO,
+    };
+
+    /// Waits before each attempt after the first, e.g. 100, 200 and 400 ms
+    /// for 4 attempts from 100 ms.
+    pub fn delays(&self) -> impl Iterator<Item = Duration> {
+        let backoff = self.backoff;
+        iter::successors(Some(backoff), |delay| delay.checked_mul(2))
+            .take(self.attempts.saturating_sub(1) as usize)
+    }
+}
+
+/// 3 attempts, 100 then 200 ms apart, enough for drivers applying a new
+/// address asynchronously.
+impl Default for Retry {
+    fn default() -> Self {
+        Retry {
+            attempts: 3,
+            backoff: Duration::from_millis(100),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Retry;
+
+    #[test]
+    fn test_retry_delays() {
+        let retry = Retry {
+            attempts: 4,
+            backoff: Duration::from_millis(100),
+        };
+
+        let expected = [100, 200, 400].map(Duration::from_millis).to_vec();
+
+        assert_eq!(retry.delays().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn test_retry_single_attempt() {
+        let zero = Retry {
+            attempts: 0,
+            ..Retry::default()
+        };
+
+        assert_eq!(Retry::NONE.delays().count(), 0);
+        assert_eq!(zero.delays().count(), 0);
+    }
+
+    #[test]
+    fn test_retry_default() {
+        let expected = [100, 200].map(Duration::from_millis).to_vec();
+
+        assert_eq!(Retry::default().delays().collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,447 @@
+use core::fmt::Debug;
+use std::ffi::{c_int, c_ushort};
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use crate::ifname::IfName;
+use crate::lladdr::LinkLevelAddress;
+
+// Addresses following the header of a BSD routing message, one per bit set
+// in its `rtm_addrs`, walked the way rt_xaddrs of route.c does. Kept apart
+// from the macOS bindings so that it builds and is tested on every system.
+//
+// https://github.com/apple/darwin-xnu/blob/xnu-7195.121.3/bsd/net/route.h#L359
+// https://github.com/apple/darwin-xnu/blob/xnu-7195.121.3/bsd/net/rtsock.c#L136
+// https://github.com/freebsd/freebsd-src/blob/main/sbin/route/route.c
+
+/// Padding of each address on Darwin, ROUNDUP32 of rtsock.c, where the
+/// SA_SIZE of FreeBSD pads to `sizeof(long)`.
+pub(crate) const SA_ALIGN_DARWIN: usize = 4;
+
+// Families as Darwin numbers them, e.g. AF_INET6 is 28 on FreeBSD.
+const AF_INET: u8 = 2;
+const AF_INET6: u8 = 30;
+const AF_LINK: u8 = 18;
+
+const IFT_ETHER: u8 = 0x06;
+
+// sa_len and sa_family.
+const SA_HDR_SIZE: usize = 2;
+// sin_addr follows sin_port, sin6_addr follows sin6_port and sin6_flowinfo.
+const SIN_ADDR_OFFSET: usize = 4;
+const SIN6_ADDR_OFFSET: usize = 8;
+// sdl_data follows sdl_index, sdl_type, sdl_nlen, sdl_alen and sdl_slen.
+const SDL_DATA_OFFSET: usize = 8;
+
+/// Slot of an address, set as `1 << rtax` in `rtm_addrs`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Rtax {
+    Dst,
+    Gateway,
+    Netmask,
+    Genmask,
+    Ifp,
+    Ifa,
+    Author,
+    Brd,
+}
+
+const RTAX: [Rtax; 8] = [
+    Rtax::Dst,
+    Rtax::Gateway,
+    Rtax::Netmask,
+    Rtax::Genmask,
+    Rtax::Ifp,
+    Rtax::Ifa,
+    Rtax::Author,
+    Rtax::Brd,
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Error {
+    Truncated(Rtax, usize, usize),
+    SockaddrDlLength(Rtax, usize),
+    SockaddrDlData(Rtax, usize, usize, usize, usize),
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Truncated(rtax, sa_len, left) => f
+                .debug_struct("Rtax::TruncatedError")
+                .field("rtax", rtax)
+                .field("sa_len", sa_len)
+                .field("left", left)
+                .finish(),
+            Error::SockaddrDlLength(rtax, sa_len) => f
+                .debug_struct("Rtax::SockaddrDlLengthError")
+                .field("rtax", rtax)
+                .field("sa_len", sa_len)
+                .field("min", &SDL_DATA_OFFSET)
+                .finish(),
+            Error::SockaddrDlData(rtax, nlen, alen, slen, sa_len) => f
+                .debug_struct("Rtax::SockaddrDlDataError")
+                .field("rtax", rtax)
+                .field("sdl_nlen", nlen)
+                .field("sdl_alen", alen)
+                .field("sdl_slen", slen)
+                .field("sa_len", sa_len)
+                .finish(),
+        }
+    }
+}
+
+/// Address of a slot. Netmasks may be shorter than their structure, the
+/// bytes left out being zeros, and an empty address has family 0.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Sockaddr<'a> {
+    Inet(Ipv4Addr),
+    Inet6(Ipv6Addr),
+    Link(SockaddrDl<'a>),
+    Other(u8, &'a [u8]),
+}
+
+/// AF_LINK address, its name and address cut to `sdl_nlen` and `sdl_alen`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct SockaddrDl<'a> {
+    pub(crate) sdl_index: c_ushort,
+    pub(crate) sdl_type: u8,
+    pub(crate) name: &'a [u8],
+    pub(crate) addr: &'a [u8],
+}
+
+impl SockaddrDl<'_> {
+    /// Index, name and address of an Ethernet link, named after its index
+    /// if its name is not valid.
+    pub(crate) fn link_ether(&self) -> Option<(c_int, IfName, LinkLevelAddress)> {
+        if self.sdl_type != IFT_ETHER {
+            return None;
+        }
+
+        let name = self.name.iter().map(|c| *c as i8).collect::<Vec<_>>();
+        let ifname = match IfName::try_from(&name[..]) {
+            Ok(ifname) => ifname,
+            Err(_) => IfName::try_from(format!("index{}", self.sdl_index)).ok()?,
+        };
+
+        let octets: &[u8; 6] = self.addr.try_into().ok()?;
+
+        Some((
+            self.sdl_index.into(),
+            ifname,
+            LinkLevelAddress::from(octets),
+        ))
+    }
+}
+
+/// Walks the addresses of `buf`, which starts after the message header, as
+/// `addrs` lists them, each padded to `align` bytes. Stops after the first
+/// error.
+pub(crate) fn iter(buf: &[u8], addrs: c_int, align: usize) -> RtaxIter<'_> {
+    RtaxIter {
+        buf,
+        addrs,
+        align,
+        rtax: 0,
+    }
+}
+
+pub(crate) struct RtaxIter<'a> {
+    buf: &'a [u8],
+    addrs: c_int,
+    align: usize,
+    rtax: usize,
+}
+
+impl RtaxIter<'_> {
+    /// `len` rounded up to `align`, a zero length still taking `align` bytes.
+    fn roundup(&self, len: usize) -> usize {
+        match len {
+            0 => self.align,
+            len => len.div_ceil(self.align) * self.align,
+        }
+    }
+}
+
+impl<'a> Iterator for RtaxIter<'a> {
+    type Item = Result<(Rtax, Sockaddr<'a>), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = (self.rtax..RTAX.len()).find(|rtax| self.addrs & (1 << rtax) != 0)?;
+        let rtax = RTAX[index];
+        self.rtax = index + 1;
+
+        let buf = self.buf;
+        let sa_len = buf.first().map_or(0, |len| *len as usize);
+        if buf.is_empty() || sa_len > buf.len() {
+            self.rtax = RTAX.len();
+            return Some(Err(Error::Truncated(rtax, sa_len, buf.len())));
+        }
+        self.buf = &buf[self.roundup(sa_len).min(buf.len())..];
+
+        match parse(rtax, &buf[..sa_len]) {
+            Ok(sockaddr) => Some(Ok((rtax, sockaddr))),
+            Err(error) => {
+                self.rtax = RTAX.len();
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Copies the bytes of `sa` from `offset` into zeros, as route.c does for
+/// netmasks shorter than their structure.
+fn octets<const N: usize>(sa: &[u8], offset: usize) -> [u8; N] {
+    let mut octets = [0; N];
+    let bytes = sa.get(offset..).unwrap_or(&[]);
+    let len = bytes.len().min(N);
+    octets[..len].copy_from_slice(&bytes[..len]);
+    octets
+}
+
+fn parse(rtax: Rtax, sa: &[u8]) -> Result<Sockaddr<'_>, Error> {
+    if sa.len() < SA_HDR_SIZE {
+        return Ok(Sockaddr::Other(0, sa));
+    }
+
+    let sockaddr = match sa[1] {
+        AF_INET => Sockaddr::Inet(Ipv4Addr::from(octets::<4>(sa, SIN_ADDR_OFFSET))),
+        AF_INET6 => Sockaddr::Inet6(Ipv6Addr::from(octets::<16>(sa, SIN6_ADDR_OFFSET))),
+        AF_LINK => Sockaddr::Link(parse_link(rtax, sa)?),
+        family => Sockaddr::Other(family, sa),
+    };
+
+    Ok(sockaddr)
+}
+
+fn parse_link(rtax: Rtax, sa: &[u8]) -> Result<SockaddrDl<'_>, Error> {
+    if sa.len() < SDL_DATA_OFFSET {
+        return Err(Error::SockaddrDlLength(rtax, sa.len()));
+    }
+
+    let (nlen, alen, slen) = (sa[5] as usize, sa[6] as usize, sa[7] as usize);
+    let data = &sa[SDL_DATA_OFFSET..];
+    if nlen + alen + slen > data.len() {
+        return Err(Error::SockaddrDlData(rtax, nlen, alen, slen, sa.len()));
+    }
+
+    Ok(SockaddrDl {
+        sdl_index: c_ushort::from_ne_bytes([sa[2], sa[3]]),
+        sdl_type: sa[4],
+        name: &data[..nlen],
+        addr: &data[nlen..nlen + alen],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use crate::random;
+    use crate::Result;
+
+    use super::{iter, Error, Rtax, Sockaddr, SockaddrDl, SA_ALIGN_DARWIN};
+
+    // RTA_GATEWAY, RTA_IFP and RTA_IFA of an RTM_NEWMADDR for ff02::fb on
+    // "en0" (index 4, 00:11:22:33:44:55), as Darwin lays them out on
+    // little-endian hosts. The 14 bytes of the gateway are padded to 16.
+    const ADDRS_NEWMADDR_EN0: [u8; 64] = [
+        0x0e, 0x12, 0x04, 0x00, 0x06, 0x00, 0x06, 0x00, // sdl_len .. sdl_slen
+        0x33, 0x33, 0x00, 0x00, 0x00, 0xfb, 0x00, 0x00, // 33:33:00:00:00:fb
+        0x14, 0x12, 0x04, 0x00, 0x06, 0x03, 0x06, 0x00, // sdl_len .. sdl_slen
+        0x65, 0x6e, 0x30, 0x00, 0x11, 0x22, 0x33, 0x44, // "en0", 00:11:22:33:44
+        0x55, 0x00, 0x00, 0x00, 0x1c, 0x1e, 0x00, 0x00, // 55, sin6_len .. sin6_port
+        0x00, 0x00, 0x00, 0x00, 0xff, 0x02, 0x00, 0x00, // sin6_flowinfo, ff02:0
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // :0:0:0
+        0x00, 0x00, 0x00, 0xfb, 0x00, 0x00, 0x00, 0x00, // :0:fb, sin6_scope_id
+    ];
+    const RTA_NEWMADDR: i32 = 0x2 | 0x10 | 0x20;
+
+    // RTA_DST, RTA_GATEWAY and RTA_NETMASK of a route to 192.168.1.0/24 via
+    // 192.168.1.1, padded to 8 bytes like FreeBSD does. The netmask is cut
+    // after its last non-zero byte.
+    const ADDRS_ROUTE: [u8; 40] = [
+        0x10, 0x02, 0x00, 0x00, 0xc0, 0xa8, 0x01, 0x00, // 192.168.1.0
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // sin_zero
+        0x10, 0x02, 0x00, 0x00, 0xc0, 0xa8, 0x01, 0x01, // 192.168.1.1
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // sin_zero
+        0x07, 0x02, 0x00, 0x00, 0xff, 0xff, 0xff, 0x00, // 255.255.255, padding
+    ];
+    const RTA_ROUTE: i32 = 0x1 | 0x2 | 0x4;
+
+    const SA_ALIGN_LONG: usize = 8;
+
+    #[test]
+    fn test_rtax_iter() {
+        let expected_addrs = vec![
+            (
+                Rtax::Gateway,
+                Sockaddr::Link(SockaddrDl {
+                    sdl_index: 4,
+                    sdl_type: 6,
+                    name: &[],
+                    addr: &[0x33, 0x33, 0x00, 0x00, 0x00, 0xfb],
+                }),
+            ),
+            (
+                Rtax::Ifp,
+                Sockaddr::Link(SockaddrDl {
+                    sdl_index: 4,
+                    sdl_type: 6,
+                    name: b"en0",
+                    addr: &[0x00, 0x11, 0x22, 0x33, 0x44, 0x55],
+                }),
+            ),
+            (
+                Rtax::Ifa,
+                Sockaddr::Inet6(Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0, 0xfb)),
+            ),
+        ];
+
+        let addrs = iter(&ADDRS_NEWMADDR_EN0, RTA_NEWMADDR, SA_ALIGN_DARWIN)
+            .collect::<std::result::Result<Vec<_>, _>>();
+
+        assert_eq!(addrs, Ok(expected_addrs));
+    }
+
+    #[test]
+    fn test_rtax_iter_align_long() {
+        let expected_addrs = vec![
+            (Rtax::Dst, Sockaddr::Inet(Ipv4Addr::new(192, 168, 1, 0))),
+            (Rtax::Gateway, Sockaddr::Inet(Ipv4Addr::new(192, 168, 1, 1))),
+            (
+                Rtax::Netmask,
+                Sockaddr::Inet(Ipv4Addr::new(255, 255, 255, 0)),
+            ),
+        ];
+
+        let addrs = iter(&ADDRS_ROUTE, RTA_ROUTE, SA_ALIGN_LONG)
+            .collect::<std::result::Result<Vec<_>, _>>();
+
+        assert_eq!(addrs, Ok(expected_addrs));
+    }
+
+    #[test]
+    fn test_rtax_iter_empty_sockaddr() {
+        let buf = [0, 0, 0, 0, 0x08, 0x02, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x01];
+
+        let addrs =
+            iter(&buf, 0x1 | 0x2, SA_ALIGN_DARWIN).collect::<std::result::Result<Vec<_>, _>>();
+
+        assert_eq!(
+            addrs,
+            Ok(vec![
+                (Rtax::Dst, Sockaddr::Other(0, &[])),
+                (Rtax::Gateway, Sockaddr::Inet(Ipv4Addr::new(10, 0, 0, 1))),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_rtax_iter_unknown_family() {
+        let buf = [0x04, 0x7f, 0xaa, 0xbb];
+
+        let addrs = iter(&buf, 0x20, SA_ALIGN_DARWIN).collect::<Vec<_>>();
+
+        assert_eq!(
+            addrs,
+            vec![Ok((Rtax::Ifa, Sockaddr::Other(0x7f, &buf[..])))]
+        );
+    }
+
+    #[test]
+    fn test_rtax_iter_truncated() {
+        let expected_error = "Rtax::TruncatedError { rtax: Ifa, sa_len: 28, left: 4 }";
+
+        let addrs =
+            iter(&ADDRS_NEWMADDR_EN0[..40], RTA_NEWMADDR, SA_ALIGN_DARWIN).collect::<Vec<_>>();
+
+        assert_eq!(addrs.len(), 3);
+        assert!(addrs[..2].iter().all(|addr| addr.is_ok()));
+        assert_eq!(format!("{:?}", addrs[2].unwrap_err()), expected_error);
+    }
+
+    #[test]
+    fn test_rtax_iter_missing_sockaddr() {
+        let addrs =
+            iter(&ADDRS_NEWMADDR_EN0[..36], RTA_NEWMADDR, SA_ALIGN_DARWIN).collect::<Vec<_>>();
+
+        assert_eq!(addrs.last(), Some(&Err(Error::Truncated(Rtax::Ifa, 0, 0))));
+    }
+
+    #[test]
+    fn test_rtax_iter_sockaddr_dl_data() {
+        let expected_error = "Rtax::SockaddrDlDataError { rtax: Ifp, sdl_nlen: 9, sdl_alen: 6, sdl_slen: 0, sa_len: 20 }";
+        let mut buf = ADDRS_NEWMADDR_EN0;
+        buf[21] = 9;
+
+        let addrs = iter(&buf, RTA_NEWMADDR, SA_ALIGN_DARWIN).collect::<Vec<_>>();
+
+        assert_eq!(addrs.len(), 2);
+        assert_eq!(format!("{:?}", addrs[1].unwrap_err()), expected_error);
+    }
+
+    #[test]
+    fn test_rtax_iter_sockaddr_dl_length() {
+        let expected_error = "Rtax::SockaddrDlLengthError { rtax: Gateway, sa_len: 6, min: 8 }";
+        let mut buf = ADDRS_NEWMADDR_EN0;
+        buf[0] = 6;
+
+        let error = iter(&buf, RTA_NEWMADDR, SA_ALIGN_DARWIN).next().unwrap();
+
+        assert_eq!(format!("{:?}", error.unwrap_err()), expected_error);
+    }
+
+    #[test]
+    fn test_rtax_link_ether() -> Result<()> {
+        let expected_nic = (4, "en0".try_into()?, "00:11:22:33:44:55".parse()?);
+
+        let nic = match iter(&ADDRS_NEWMADDR_EN0, RTA_NEWMADDR, SA_ALIGN_DARWIN).nth(1) {
+            Some(Ok((Rtax::Ifp, Sockaddr::Link(sdl)))) => sdl.link_ether(),
+            _ => None,
+        };
+
+        assert_eq!(nic, Some(expected_nic));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rtax_link_ether_not_ether() {
+        let sdl = SockaddrDl {
+            sdl_index: 1,
+            sdl_type: 0x18,
+            name: b"lo0",
+            addr: &[],
+        };
+
+        assert_eq!(sdl.link_ether(), None);
+    }
+
+    #[test]
+    fn test_rtax_iter_fuzz() {
+        random::fuzz(
+            0x5851f42d4c957f2d,
+            &[&ADDRS_NEWMADDR_EN0, &ADDRS_ROUTE],
+            |buf| {
+                for (addrs, align) in [
+                    (RTA_NEWMADDR, SA_ALIGN_DARWIN),
+                    (RTA_ROUTE, SA_ALIGN_LONG),
+                    (-1, SA_ALIGN_DARWIN),
+                ] {
+                    let walk = iter(buf, addrs, align).collect::<Vec<_>>();
+
+                    assert!(walk.len() <= addrs.count_ones() as usize);
+                    // Nothing follows an error.
+                    if let Some(i) = walk.iter().position(|addr| addr.is_err()) {
+                        assert_eq!(i, walk.len() - 1);
+                    }
+                    for addr in &walk {
+                        if let Ok((_, Sockaddr::Link(sdl))) = addr {
+                            sdl.link_ether();
+                        }
+                    }
+                }
+            },
+        );
+    }
+}
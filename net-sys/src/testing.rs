@@ -0,0 +1,500 @@
+use std::collections::HashMap;
+use std::ffi::c_int;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use crate::backend::{Monitor, NicBackend};
+use crate::error::Operation;
+use crate::event::NicEvent;
+use crate::ifname::IfName;
+use crate::info::{NicInfo, NicType};
+use crate::lladdr::LinkLevelAddress;
+use crate::{Error, Result};
+
+const FLAGS: u32 =
+    (libc::IFF_UP | libc::IFF_BROADCAST | libc::IFF_RUNNING | libc::IFF_MULTICAST) as u32;
+const MTU: u32 = 1500;
+
+/// Call made to a `FakeKernel` through `NicBackend`, failed or not.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Call {
+    GetLLAddr(IfName),
+    SetLLAddr(IfName, LinkLevelAddress),
+    GetPermLLAddr(IfName),
+    List,
+    Monitor,
+}
+
+#[derive(Debug)]
+struct Failure {
+    operation: Operation,
+    ifname: IfName,
+    errno: c_int,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    nics: Vec<NicInfo>,
+    permanent: HashMap<IfName, LinkLevelAddress>,
+    next_index: c_int,
+    /// Changes of an interface its driver reports done but does not make.
+    ignored: Vec<IfName>,
+    failures: Vec<Failure>,
+    monitor_failures: Vec<c_int>,
+    calls: Vec<Call>,
+    monitors: Vec<Sender<Result<NicEvent>>>,
+}
+
+impl State {
+    /// Position of `ifname` in `nics`, unless `operation` is to fail.
+    fn find(&mut self, operation: Operation, ifname: &IfName) -> std::result::Result<usize, Error> {
+        if let Some(at) = self
+            .failures
+            .iter()
+            .position(|failure| failure.operation == operation && failure.ifname == *ifname)
+        {
+            let failure = self.failures.remove(at);
+            return Err(Error::from_errno(operation, failure.errno).with_ifname(*ifname));
+        }
+
+        self.nics
+            .iter()
+            .position(|nic| nic.ifname == *ifname)
+            .ok_or_else(|| Error::from_errno(operation, libc::ENODEV).with_ifname(*ifname))
+    }
+
+    /// Reports `event` to every monitor still read.
+    fn report(&mut self, event: NicEvent) {
+        self.monitors
+            .retain(|monitor| monitor.send(Ok(event.clone())).is_ok());
+    }
+}
+
+/// In-memory interfaces standing in for the kernel's, for tests of code
+/// taking a `NicBackend`.
+///
+/// Interfaces come and go with `plug` and `unplug`, which report `NicNew`
+/// and `NicDel` to every `monitor`, as does a changed address, and their
+/// link goes up and down with `set_link`. Calls fail with `ENODEV` for an
+/// unknown interface, or with the errno given to `fail`, and are all
+/// recorded for `calls`. Monitors fail to open as `fail_monitor` says, and
+/// read the errors given to `report_error`. Clones share the same
+/// interfaces.
+#[derive(Clone, Debug, Default)]
+pub struct FakeKernel {
+    state: Arc<Mutex<State>>,
+}
+
+impl FakeKernel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Adds an Ethernet interface with the next free index, which is
+    /// returned, `lladdr` being its permanent address too.
+    pub fn plug(&self, ifname: &IfName, lladdr: &LinkLevelAddress) -> c_int {
+        let index = self.plug_virtual(ifname, lladdr);
+        self.state().permanent.insert(*ifname, *lladdr);
+        index
+    }
+
+    /// Like `plug`, for an interface with no permanent address, like a veth
+    /// or a bridge.
+    pub fn plug_virtual(&self, ifname: &IfName, lladdr: &LinkLevelAddress) -> c_int {
+        let mut state = self.state();
+        state.permanent.remove(ifname);
+        state.next_index += 1;
+        let index = state.next_index;
+        state.nics.push(NicInfo {
+            index,
+            ifname: *ifname,
+            lladdr: Some(*lladdr),
+            nic_type: NicType::Ether,
+            flags: FLAGS,
+            mtu: MTU,
+        });
+        state.report(NicEvent::NicNew((index, *ifname, *lladdr)));
+        index
+    }
+
+    /// Removes an interface, `false` if there is none with that name.
+    pub fn unplug(&self, ifname: &IfName) -> bool {
+        let mut state = self.state();
+        let Some(at) = state.nics.iter().position(|nic| nic.ifname == *ifname) else {
+            return false;
+        };
+
+        let nic = state.nics.remove(at);
+        if let Some(lladdr) = nic.lladdr {
+            state.report(NicEvent::NicDel((nic.index, nic.ifname, lladdr)));
+        }
+        true
+    }
+
+    /// Sets the link of `ifname` up or down, like a cable plugged in or out,
+    /// reporting `NicUp` or `NicDown` if it changed. `false` if there is no
+    /// interface with that name.
+    pub fn set_link(&self, ifname: &IfName, up: bool) -> bool {
+        let mut state = self.state();
+        let Some(nic) = state.nics.iter_mut().find(|nic| nic.ifname == *ifname) else {
+            return false;
+        };
+
+        let flags = match up {
+            true => nic.flags | libc::IFF_RUNNING as u32,
+            false => nic.flags & !(libc::IFF_RUNNING as u32),
+        };
+        if flags != nic.flags {
+            nic.flags = flags;
+            let link = (nic.index, nic.ifname);
+            state.report(match up {
+                true => NicEvent::NicUp(link),
+                false => NicEvent::NicDown(link),
+            });
+        }
+        true
+    }
+
+    /// Makes the next `operation` on `ifname` fail with `errno`, e.g.
+    /// `Operation::SetLinkLevelAddress` with `EBUSY`. Failures for the same
+    /// call are used up in the order given.
+    pub fn fail(&self, operation: Operation, ifname: &IfName, errno: c_int) {
+        self.state().failures.push(Failure {
+            operation,
+            ifname: *ifname,
+            errno,
+        });
+    }
+
+    /// Makes the next `monitor` fail with `errno`, like a socket that cannot
+    /// be opened.
+    pub fn fail_monitor(&self, errno: c_int) {
+        self.state().monitor_failures.push(errno);
+    }
+
+    /// Reports `error` to every monitor, e.g. one of kind `Decode`, like a
+    /// read that failed.
+    pub fn report_error(&self, error: Error) {
+        self.state()
+            .monitors
+            .retain(|monitor| monitor.send(Err(error.clone())).is_ok());
+    }
+
+    /// Makes the next address change of `ifname` succeed without taking
+    /// effect, like a driver ignoring it.
+    pub fn ignore_next_set(&self, ifname: &IfName) {
+        self.state().ignored.push(*ifname);
+    }
+
+    /// Current address of `ifname`, `None` if it is not plugged.
+    pub fn lladdr(&self, ifname: &IfName) -> Option<LinkLevelAddress> {
+        let state = self.state();
+        let nic = state.nics.iter().find(|nic| nic.ifname == *ifname)?;
+        nic.lladdr
+    }
+
+    /// Every call so far, in order.
+    pub fn calls(&self) -> Vec<Call> {
+        self.state().calls.clone()
+    }
+
+    /// Ends every monitor once the events reported so far are read.
+    pub fn close_monitors(&self) {
+        self.state().monitors.clear();
+    }
+}
+
+impl NicBackend for FakeKernel {
+    fn get_lladdr(&self, ifname: &IfName) -> Result<LinkLevelAddress> {
+        let mut state = self.state();
+        state.calls.push(Call::GetLLAddr(*ifname));
+
+        let at = state.find(Operation::GetLinkLevelAddress, ifname)?;
+        match state.nics[at].lladdr {
+            Some(lladdr) => Ok(lladdr),
+            None => Err(
+                Error::from_errno(Operation::GetLinkLevelAddress, libc::EINVAL)
+                    .with_ifname(*ifname),
+            ),
+        }
+    }
+
+    fn set_lladdr(&self, ifname: &IfName, lladdr: &LinkLevelAddress) -> Result<()> {
+        let mut state = self.state();
+        state.calls.push(Call::SetLLAddr(*ifname, *lladdr));
+
+        let at = state
+            .find(Operation::SetLinkLevelAddress, ifname)
+            .map_err(|error| error.with_lladdr(*lladdr))?;
+        if let Some(ignored) = state.ignored.iter().position(|ignored| ignored == ifname) {
+            state.ignored.remove(ignored);
+            return Ok(());
+        }
+
+        let nic = &mut state.nics[at];
+        if nic.lladdr != Some(*lladdr) {
+            nic.lladdr = Some(*lladdr);
+            let event = NicEvent::NicNew((nic.index, *ifname, *lladdr));
+            state.report(event);
+        }
+        Ok(())
+    }
+
+    fn get_perm_lladdr(&self, ifname: &IfName) -> Result<Option<LinkLevelAddress>> {
+        let mut state = self.state();
+        state.calls.push(Call::GetPermLLAddr(*ifname));
+
+        state.find(Operation::GetPermLinkLevelAddress, ifname)?;
+        Ok(state.permanent.get(ifname).copied())
+    }
+
+    fn list(&self) -> Result<Vec<NicInfo>> {
+        let mut state = self.state();
+        state.calls.push(Call::List);
+
+        Ok(state.nics.clone())
+    }
+
+    /// Reports the events from now on, until `close_monitors`.
+    fn monitor(&self) -> Result<Monitor> {
+        let mut state = self.state();
+        state.calls.push(Call::Monitor);
+
+        if !state.monitor_failures.is_empty() {
+            let errno = state.monitor_failures.remove(0);
+            return Err(Error::from_errno(Operation::OpenSocket, errno));
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        state.monitors.push(sender);
+        Ok(Box::new(FakeMonitor { receiver }))
+    }
+}
+
+struct FakeMonitor {
+    receiver: Receiver<Result<NicEvent>>,
+}
+
+impl Iterator for FakeMonitor {
+    type Item = Result<NicEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.recv().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::LazyLock;
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::backend::NicBackend;
+    use crate::error::{ErrorKind, Operation};
+    use crate::event::NicEvent::{NicDel, NicDown, NicNew, NicUp};
+    use crate::ifname::IfName;
+    use crate::lladdr::LinkLevelAddress;
+    use crate::retry::Retry;
+    use crate::{Error, Result};
+
+    use super::{Call, FakeKernel};
+
+    static ENX: LazyLock<IfName> = LazyLock::new(|| "en7".try_into().unwrap());
+    static WIFI: LazyLock<IfName> = LazyLock::new(|| "en0".try_into().unwrap());
+    static LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "00:e0:4c:68:00:01".parse().unwrap());
+    static NEW_LLADDR: LazyLock<LinkLevelAddress> =
+        LazyLock::new(|| "02:11:22:33:44:55".parse().unwrap());
+
+    #[test]
+    fn test_fake_kernel_plug_and_unplug() -> Result<()> {
+        let kernel = FakeKernel::new();
+        let monitor = kernel.monitor()?;
+
+        assert_eq!(kernel.plug(&WIFI, &NEW_LLADDR), 1);
+        assert_eq!(kernel.plug(&ENX, &LLADDR), 2);
+        assert!(kernel.unplug(&ENX));
+        assert!(!kernel.unplug(&ENX));
+        kernel.close_monitors();
+
+        let expected_events = vec![
+            NicNew((1, *WIFI, *NEW_LLADDR)),
+            NicNew((2, *ENX, *LLADDR)),
+            NicDel((2, *ENX, *LLADDR)),
+        ];
+
+        assert_eq!(monitor.collect::<Result<Vec<_>>>()?, expected_events);
+        assert_eq!(kernel.lladdr(&ENX), None);
+        assert_eq!(
+            kernel
+                .list()?
+                .iter()
+                .map(|nic| nic.ifname)
+                .collect::<Vec<_>>(),
+            vec![*WIFI]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fake_kernel_set_lladdr() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+        let monitor = kernel.monitor()?;
+
+        kernel.set_lladdr(&ENX, &NEW_LLADDR)?;
+        // Unchanged, nothing reported.
+        kernel.set_lladdr(&ENX, &NEW_LLADDR)?;
+        kernel.close_monitors();
+
+        assert_eq!(kernel.get_lladdr(&ENX)?, *NEW_LLADDR);
+        assert_eq!(
+            monitor.collect::<Result<Vec<_>>>()?,
+            vec![NicNew((1, *ENX, *NEW_LLADDR))]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fake_kernel_set_link() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+        let monitor = kernel.monitor()?;
+
+        assert!(kernel.set_link(&ENX, false));
+        // Unchanged, nothing reported.
+        assert!(kernel.set_link(&ENX, false));
+        assert!(kernel.set_link(&ENX, true));
+        assert!(!kernel.set_link(&WIFI, true));
+        kernel.close_monitors();
+
+        assert_eq!(
+            monitor.collect::<Result<Vec<_>>>()?,
+            vec![NicDown((1, *ENX)), NicUp((1, *ENX))]
+        );
+        assert_ne!(kernel.list()?[0].flags & libc::IFF_RUNNING as u32, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fake_kernel_failures() {
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+        kernel.fail(Operation::SetLinkLevelAddress, &ENX, libc::EBUSY);
+        kernel.fail(Operation::SetLinkLevelAddress, &ENX, libc::EPERM);
+
+        let kinds = [*NEW_LLADDR; 3].map(|lladdr| {
+            kernel
+                .set_lladdr(&ENX, &lladdr)
+                .map_err(|error| error.kind())
+        });
+
+        assert_eq!(
+            kinds,
+            [
+                Err(ErrorKind::Busy),
+                Err(ErrorKind::PermissionDenied),
+                Ok(())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fake_kernel_no_such_interface() {
+        let expected_error = "Nic::Error { operation: set_lladdr, ifname: \"en7\", lladdr: \"02:11:22:33:44:55\", errno: 19, kind: NoSuchInterface }";
+
+        let error = FakeKernel::new().set_lladdr(&ENX, &NEW_LLADDR).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_fake_kernel_ignored_set_lladdr() -> Result<()> {
+        let retry = Retry {
+            attempts: 2,
+            backoff: Duration::ZERO,
+        };
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+        kernel.ignore_next_set(&ENX);
+
+        kernel.set_lladdr_verified(&ENX, &NEW_LLADDR, &retry)?;
+
+        let expected_calls = vec![
+            Call::SetLLAddr(*ENX, *NEW_LLADDR),
+            Call::GetLLAddr(*ENX),
+            Call::SetLLAddr(*ENX, *NEW_LLADDR),
+            Call::GetLLAddr(*ENX),
+        ];
+
+        assert_eq!(kernel.calls(), expected_calls);
+        assert_eq!(kernel.lladdr(&ENX), Some(*NEW_LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fake_kernel_get_perm_lladdr() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+        kernel.plug_virtual(&WIFI, &NEW_LLADDR);
+        kernel.set_lladdr(&ENX, &NEW_LLADDR)?;
+
+        assert_eq!(kernel.get_perm_lladdr(&ENX)?, Some(*LLADDR));
+        assert_eq!(kernel.get_perm_lladdr(&WIFI)?, None);
+
+        kernel.unplug(&ENX);
+
+        assert!(kernel.get_perm_lladdr(&ENX).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fake_kernel_monitor_errors() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.fail_monitor(libc::EPERM);
+
+        let error = kernel.monitor().err().unwrap();
+
+        assert_eq!(
+            (error.operation, error.kind()),
+            (Operation::OpenSocket, ErrorKind::PermissionDenied)
+        );
+
+        let mut monitor = kernel.monitor()?;
+        kernel.report_error(Error::new(Operation::Read, ErrorKind::Decode));
+        kernel.plug(&ENX, &LLADDR);
+        kernel.close_monitors();
+
+        let error = monitor.next().unwrap().unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::Decode);
+        assert_eq!(monitor.next().unwrap()?, NicNew((1, *ENX, *LLADDR)));
+        assert!(monitor.next().is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_fake_kernel_monitor_on_another_thread() -> Result<()> {
+        let kernel = FakeKernel::new();
+        let monitor = kernel.monitor()?;
+
+        let reader = thread::spawn(move || monitor.map(|event| event.unwrap()).collect::<Vec<_>>());
+        kernel.plug(&ENX, &LLADDR);
+        kernel.close_monitors();
+
+        assert_eq!(reader.join().unwrap(), vec![NicNew((1, *ENX, *LLADDR))]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,489 @@
+use std::io;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Args, Parser, Subcommand};
+
+use net_sys::ifname::IfName;
+use net_sys::lladdr::LLAddr;
+
+use net_sys::ErrorKind;
+
+use crate::config::{self, Options, DEFAULT_DEBOUNCE};
+use crate::control::{Request, CONTROL_SOCKET};
+use crate::handover::Alternate;
+use crate::logger::{Filter, Output};
+use crate::output::Format;
+use crate::state::STATE_FILE;
+
+/// Uses a single MAC address when switching between Wi-Fi and USB Ethernet
+/// connections.
+#[derive(Debug, Parser)]
+#[command(name = "nicr", version, about)]
+pub(crate) struct Cli {
+    /// Output format
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub(crate) format: Format,
+    /// State file remembering the original addresses
+    #[arg(long, global = true, value_name = "FILE", default_value = STATE_FILE)]
+    pub(crate) state: PathBuf,
+    /// Control socket of `roam`, which `ctl` talks to
+    #[arg(long, global = true, value_name = "SOCKET", default_value = CONTROL_SOCKET)]
+    pub(crate) control: PathBuf,
+    /// Where to log messages
+    #[arg(long, global = true, value_enum, default_value_t)]
+    pub(crate) log: Output,
+    /// Most verbose level logged, overridden per target as in
+    /// `info,net_sys=debug`
+    #[arg(long, global = true, value_name = "FILTER", value_parser = filter, default_value = "info")]
+    pub(crate) log_level: Filter,
+    #[command(subcommand)]
+    pub(crate) command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum Command {
+    /// List network interfaces
+    List,
+    /// Print the link-level address of an interface
+    Get {
+        #[arg(value_parser = ifname)]
+        ifname: IfName,
+    },
+    /// Set the link-level address of an interface
+    Set {
+        #[arg(value_parser = ifname)]
+        ifname: IfName,
+        #[arg(value_parser = lladdr)]
+        lladdr: LLAddr,
+        /// Print the change instead of making it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Print interfaces as they come and go
+    Monitor {
+        /// Quiet period in milliseconds before an interface's final state
+        /// is printed
+        #[arg(long, value_name = "MS", default_value_t = DEFAULT_DEBOUNCE)]
+        debounce: u64,
+    },
+    /// Keep a shared link-level address on the interface in use
+    Roam(RoamArgs),
+    /// Put back the link-level address an interface had before nicr changed it
+    Restore {
+        #[arg(value_parser = ifname, required_unless_present = "all")]
+        ifname: Option<IfName>,
+        /// Restore every interface of the state file
+        #[arg(long, conflicts_with = "ifname")]
+        all: bool,
+        /// Configuration file whose `[retry]` applies, instead of
+        /// $XDG_CONFIG_HOME/nicr/config.toml or /etc/nicr.toml
+        #[arg(long, value_name = "FILE")]
+        config: Option<PathBuf>,
+    },
+    /// Query or steer a running `nicr roam` through its control socket
+    Ctl {
+        #[arg(value_enum)]
+        request: Request,
+    },
+}
+
+#[derive(Debug, Args)]
+pub(crate) struct RoamArgs {
+    /// Configuration file, instead of $XDG_CONFIG_HOME/nicr/config.toml or
+    /// /etc/nicr.toml
+    #[arg(long, value_name = "FILE")]
+    pub(crate) config: Option<PathBuf>,
+    /// Profile of the configuration file to use
+    #[arg(long, value_name = "NAME")]
+    pub(crate) profile: Option<String>,
+    /// Wi-Fi interface
+    #[arg(value_parser = ifname)]
+    pub(crate) wifi: Option<IfName>,
+    /// Shared link-level address
+    #[arg(value_parser = lladdr)]
+    pub(crate) lladdr: Option<LLAddr>,
+    /// Ethernet interface name pattern, with `*` and `?` wildcards
+    #[arg(long = "pattern", value_name = "PATTERN")]
+    pub(crate) patterns: Vec<String>,
+    /// Wi-Fi address while Ethernet holds the shared one, or `random`
+    #[arg(long, value_parser = alternate)]
+    pub(crate) alternate: Option<Alternate>,
+    /// Quiet period in milliseconds before acting on an interface's final
+    /// state
+    #[arg(long, value_name = "MS")]
+    pub(crate) debounce: Option<u64>,
+    /// Print the changes instead of making them
+    #[arg(long)]
+    pub(crate) dry_run: bool,
+}
+
+impl RoamArgs {
+    pub(crate) fn options(&self) -> Options {
+        Options {
+            wifi: self.wifi,
+            lladdr: self.lladdr,
+            patterns: match self.patterns.is_empty() {
+                true => None,
+                false => Some(self.patterns.iter().map(|p| p.as_str().into()).collect()),
+            },
+            alternate: self.alternate.clone(),
+            debounce: self.debounce,
+            rules: None,
+        }
+    }
+}
+
+fn ifname(value: &str) -> Result<IfName, String> {
+    IfName::try_from(value).map_err(|error| error.to_string())
+}
+
+fn lladdr(value: &str) -> Result<LLAddr, String> {
+    value
+        .parse()
+        .map_err(|error: net_sys::Error| error.to_string())
+}
+
+fn filter(value: &str) -> Result<Filter, String> {
+    value
+        .parse()
+        .map_err(|error: Box<dyn std::error::Error>| error.to_string())
+}
+
+fn alternate(value: &str) -> Result<Alternate, String> {
+    value
+        .parse()
+        .map_err(|error: Box<dyn std::error::Error>| error.to_string())
+}
+
+/// Exit status of a failed command, after sysexits(3).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Exit {
+    Usage = 64,
+    OsErr = 71,
+    NoPerm = 77,
+    Config = 78,
+}
+
+impl Exit {
+    pub(crate) fn of(error: &(dyn std::error::Error + 'static)) -> Exit {
+        if config::is_usage_error(error) {
+            return Exit::Usage;
+        }
+        if config::is_error(error) {
+            return Exit::Config;
+        }
+
+        // The state file and the control socket fail with an io::Error,
+        // possibly behind another one.
+        let denied =
+            std::iter::successors(Some(error), |error| error.source()).any(|error| {
+                match (
+                    error.downcast_ref::<net_sys::Error>(),
+                    error.downcast_ref::<io::Error>(),
+                ) {
+                    (Some(error), _) => error.kind() == ErrorKind::PermissionDenied,
+                    (None, Some(error)) => error.kind() == io::ErrorKind::PermissionDenied,
+                    (None, None) => false,
+                }
+            });
+
+        match denied {
+            true => Exit::NoPerm,
+            false => Exit::OsErr,
+        }
+    }
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::c_int;
+    use std::io;
+    use std::path::PathBuf;
+
+    use clap::error::ErrorKind;
+    use clap::{CommandFactory, Parser};
+
+    use net_sys::error::Operation;
+
+    use crate::config::Config;
+    use crate::handover::Alternate;
+    use crate::logger::{Filter, Output};
+    use crate::output::Format;
+    use crate::testing::test_path;
+    use crate::Result;
+
+    use super::{Cli, Command, Exit};
+
+    const EPERM: c_int = 1;
+    const EACCES: c_int = 13;
+    const ENODEV: c_int = 19;
+
+    #[test]
+    fn test_cli_debug_assert() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_cli_get() -> Result<()> {
+        let cli = Cli::try_parse_from(["nicr", "get", "en0"])?;
+
+        assert!(matches!(cli.command, Command::Get { ifname } if ifname == "en0".try_into()?));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_format() -> Result<()> {
+        let text = Cli::try_parse_from(["nicr", "list"])?;
+        let json = Cli::try_parse_from(["nicr", "monitor", "--format", "json"])?;
+
+        assert_eq!(text.format, Format::Text);
+        assert_eq!(json.format, Format::Json);
+        assert!(Cli::try_parse_from(["nicr", "--format", "xml", "list"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_set() -> Result<()> {
+        let cli = Cli::try_parse_from(["nicr", "set", "en0", "02:11:22:33:44:55"])?;
+
+        let Command::Set {
+            ifname,
+            lladdr,
+            dry_run,
+        } = cli.command
+        else {
+            panic!("not a set command");
+        };
+        assert_eq!(ifname, "en0".try_into()?);
+        assert_eq!(lladdr, "02:11:22:33:44:55".parse()?);
+        assert!(!dry_run);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_set_dry_run() -> Result<()> {
+        let cli = Cli::try_parse_from(["nicr", "set", "--dry-run", "en0", "02:11:22:33:44:55"])?;
+
+        assert!(matches!(cli.command, Command::Set { dry_run: true, .. }));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_set_invalid_lladdr() {
+        let expected_error = "error: invalid value '01:02:03' for '<LLADDR>': LinkLevelAddress::WrongNumberOfOctetsError { value: \"01:02:03\", value_octets: 3, expected_octets: 6 }";
+
+        let error = Cli::try_parse_from(["nicr", "set", "en0", "01:02:03"]).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::ValueValidation);
+        assert!(error.to_string().starts_with(expected_error));
+    }
+
+    #[test]
+    fn test_cli_get_invalid_ifname() {
+        let expected_error = "error: invalid value 'en' for '<IFNAME>': IfName::TooSmallError { value: \"en\", len: 2, min: 3 }";
+
+        let error = Cli::try_parse_from(["nicr", "get", "en"]).unwrap_err();
+
+        assert_eq!(error.kind(), ErrorKind::ValueValidation);
+        assert!(error.to_string().starts_with(expected_error));
+    }
+
+    #[test]
+    fn test_cli_missing_command() {
+        let error = Cli::try_parse_from(["nicr"]).unwrap_err();
+
+        assert_eq!(
+            error.kind(),
+            ErrorKind::DisplayHelpOnMissingArgumentOrSubcommand
+        );
+        assert!(error.use_stderr());
+    }
+
+    #[test]
+    fn test_cli_help_and_version() {
+        let help = Cli::try_parse_from(["nicr", "--help"]).unwrap_err();
+        let version = Cli::try_parse_from(["nicr", "--version"]).unwrap_err();
+
+        assert_eq!(help.kind(), ErrorKind::DisplayHelp);
+        assert!(!help.use_stderr());
+        assert_eq!(version.kind(), ErrorKind::DisplayVersion);
+        assert!(!version.use_stderr());
+    }
+
+    #[test]
+    fn test_cli_roam() -> Result<()> {
+        let cli = Cli::try_parse_from([
+            "nicr",
+            "roam",
+            "--config",
+            "nicr.toml",
+            "--profile",
+            "office",
+            "--pattern",
+            "enx*",
+            "--pattern",
+            "en7",
+            "--alternate",
+            "random",
+            "--dry-run",
+            "en0",
+        ])?;
+
+        let Command::Roam(args) = cli.command else {
+            panic!("not a roam command");
+        };
+        assert_eq!(args.config, Some(PathBuf::from("nicr.toml")));
+        assert_eq!(args.profile.as_deref(), Some("office"));
+        assert!(args.dry_run);
+
+        let options = args.options();
+        assert_eq!(options.wifi, Some("en0".try_into()?));
+        assert_eq!(options.lladdr, None);
+        assert_eq!(options.patterns, Some(vec!["enx*".into(), "en7".into()]));
+        assert_eq!(options.alternate, Some(Alternate::Random));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_roam_defaults() -> Result<()> {
+        let cli = Cli::try_parse_from(["nicr", "roam"])?;
+
+        let Command::Roam(args) = cli.command else {
+            panic!("not a roam command");
+        };
+        assert_eq!(args.options(), Default::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_state() -> Result<()> {
+        let default = Cli::try_parse_from(["nicr", "restore", "--all"])?;
+        let custom = Cli::try_parse_from([
+            "nicr",
+            "set",
+            "en0",
+            "02:11:22:33:44:55",
+            "--state",
+            "nicr.state",
+        ])?;
+
+        assert_eq!(default.state, PathBuf::from("/var/lib/nicr/state.toml"));
+        assert_eq!(custom.state, PathBuf::from("nicr.state"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_log() -> Result<()> {
+        let default = Cli::try_parse_from(["nicr", "list"])?;
+        let custom = Cli::try_parse_from([
+            "nicr",
+            "roam",
+            "--log",
+            "journald",
+            "--log-level",
+            "warn,net_sys=debug",
+        ])?;
+
+        assert_eq!(default.log, Output::Stderr);
+        assert_eq!(default.log_level, Filter::default());
+        assert_eq!(custom.log, Output::Journald);
+        assert_eq!(custom.log_level, "warn,net_sys=debug".parse()?);
+        assert!(Cli::try_parse_from(["nicr", "--log-level", "loud", "list"]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_restore() -> Result<()> {
+        let one = Cli::try_parse_from(["nicr", "restore", "en7"])?;
+        let all = Cli::try_parse_from(["nicr", "restore", "--all"])?;
+
+        assert!(
+            matches!(one.command, Command::Restore { ifname: Some(ifname), all: false, .. } if ifname == "en7".try_into()?)
+        );
+        assert!(matches!(
+            all.command,
+            Command::Restore {
+                ifname: None,
+                all: true,
+                ..
+            }
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_cli_restore_usage_error() {
+        let missing = Cli::try_parse_from(["nicr", "restore"]).unwrap_err();
+        let both = Cli::try_parse_from(["nicr", "restore", "en7", "--all"]).unwrap_err();
+
+        assert_eq!(missing.kind(), ErrorKind::MissingRequiredArgument);
+        assert_eq!(both.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn test_exit_of_permission_error() {
+        let eperm: Box<dyn std::error::Error> =
+            net_sys::Error::from_errno(Operation::SetLinkLevelAddress, EPERM).into();
+        let eacces: Box<dyn std::error::Error> =
+            net_sys::Error::from_errno(Operation::OpenSocket, EACCES).into();
+        let io: Box<dyn std::error::Error> =
+            io::Error::from(io::ErrorKind::PermissionDenied).into();
+
+        assert_eq!(Exit::of(eperm.as_ref()), Exit::NoPerm);
+        assert_eq!(Exit::of(eacces.as_ref()), Exit::NoPerm);
+        assert_eq!(Exit::of(io.as_ref()), Exit::NoPerm);
+    }
+
+    #[test]
+    fn test_exit_of_system_error() {
+        let enodev: Box<dyn std::error::Error> =
+            net_sys::Error::from_errno(Operation::SetLinkLevelAddress, ENODEV).into();
+        let io: Box<dyn std::error::Error> = io::Error::from(io::ErrorKind::NotFound).into();
+        let other: Box<dyn std::error::Error> = "SetLinkLevelAddressError".into();
+
+        assert_eq!(Exit::of(enodev.as_ref()), Exit::OsErr);
+        assert_eq!(Exit::of(io.as_ref()), Exit::OsErr);
+        assert_eq!(Exit::of(other.as_ref()), Exit::OsErr);
+    }
+
+    #[test]
+    fn test_exit_of_config_error() {
+        let error = Config::load("/nonexistent/nicr.toml".as_ref()).unwrap_err();
+
+        assert_eq!(Exit::of(error.as_ref()), Exit::Config);
+    }
+
+    #[test]
+    fn test_exit_of_patterns_with_rules() -> Result<()> {
+        let path = test_path("cli_patterns_with_rules", "toml");
+        std::fs::write(&path, "[[rules]]\npatterns = [\"enx*\"]\n")?;
+        let cli = Cli::try_parse_from(["nicr", "roam", "--pattern", "en7", "en0"])?;
+        let Command::Roam(args) = cli.command else {
+            panic!("not roam: {:?}", cli.command);
+        };
+
+        let error = Config::load(&path)?
+            .settings(None, args.options())
+            .unwrap_err();
+
+        assert_eq!(Exit::of(error.as_ref()), Exit::Usage);
+
+        Ok(())
+    }
+}
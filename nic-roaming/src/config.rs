@@ -0,0 +1,654 @@
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fmt::{Debug, Display};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer};
+
+use net_sys::ifname::IfName;
+use net_sys::lladdr::LLAddr;
+use net_sys::retry::Retry;
+
+use crate::handover::Alternate;
+use crate::hooks::Hooks;
+use crate::pattern::IfPattern;
+use crate::policy::Rule;
+use crate::Result;
+
+const CONFIG_FILE: &str = "nicr/config.toml";
+const SYSTEM_CONFIG_FILE: &str = "/etc/nicr.toml";
+
+/// Quiet period of link events in milliseconds, long enough for USB
+/// interfaces to settle.
+pub(crate) const DEFAULT_DEBOUNCE: u64 = 250;
+
+#[derive(Clone, PartialEq, Eq)]
+enum Error {
+    Read(PathBuf, String),
+    Parse(PathBuf, String, usize, String),
+    MissingKey(&'static str, Option<String>),
+    UnknownProfile(String),
+    PatternsWithRules(Option<String>, bool),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Read(path, error) => f
+                .debug_struct("Config::ReadError")
+                .field("path", path)
+                .field("error", error)
+                .finish(),
+            Error::Parse(path, key, line, error) => f
+                .debug_struct("Config::ParseError")
+                .field("path", path)
+                .field("key", key)
+                .field("line", line)
+                .field("error", error)
+                .finish(),
+            Error::MissingKey(key, profile) => f
+                .debug_struct("Config::MissingKeyError")
+                .field("key", key)
+                .field("profile", profile)
+                .finish(),
+            Error::UnknownProfile(profile) => f
+                .debug_struct("Config::UnknownProfileError")
+                .field("profile", profile)
+                .finish(),
+            Error::PatternsWithRules(profile, args) => f
+                .debug_struct("Config::PatternsWithRulesError")
+                .field("profile", profile)
+                .field("args", args)
+                .finish(),
+        }
+    }
+}
+
+pub(crate) fn is_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    error.is::<Error>()
+}
+
+/// Whether `error` comes from arguments that do not go with the file, rather
+/// than from the file itself.
+pub(crate) fn is_usage_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    matches!(
+        error.downcast_ref::<Error>(),
+        Some(Error::PatternsWithRules(_, true))
+    )
+}
+
+/// Roaming options, either at the top level of the file or overridden in a
+/// `[profile.<name>]` table.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Options {
+    #[serde(default)]
+    pub(crate) wifi: Option<IfName>,
+    #[serde(default)]
+    pub(crate) lladdr: Option<LLAddr>,
+    #[serde(default, deserialize_with = "patterns")]
+    pub(crate) patterns: Option<Vec<IfPattern>>,
+    #[serde(default, deserialize_with = "alternate")]
+    pub(crate) alternate: Option<Alternate>,
+    #[serde(default)]
+    pub(crate) debounce: Option<u64>,
+    #[serde(default)]
+    pub(crate) rules: Option<Vec<Rule>>,
+}
+
+impl Options {
+    fn or(self, other: Options) -> Options {
+        Options {
+            wifi: self.wifi.or(other.wifi),
+            lladdr: self.lladdr.or(other.lladdr),
+            patterns: self.patterns.or(other.patterns),
+            alternate: self.alternate.or(other.alternate),
+            debounce: self.debounce.or(other.debounce),
+            rules: self.rules.or(other.rules),
+        }
+    }
+
+    /// Whether patterns of `self` would be ignored in favor of the rules of
+    /// `other` once merged over them.
+    fn patterns_over_rules(&self, other: &Options) -> bool {
+        self.patterns.is_some() && self.rules.is_none() && other.rules.is_some()
+    }
+}
+
+/// nicr configuration file, e.g.
+///
+/// ```toml
+/// wifi = "en0"
+/// lladdr = "02:11:22:33:44:55"
+/// patterns = ["en*"]
+/// debounce = 500
+///
+/// [profile.office]
+/// patterns = ["enx*"]
+/// alternate = "02:aa:bb:cc:dd:ee"
+///
+/// [[profile.dock.rules]]
+/// patterns = ["enx*"]
+///
+/// [[profile.dock.rules]]
+/// type = "wifi"
+/// link = "up"
+///
+/// [hooks]
+/// applied = "networkctl renew $NICR_IFNAME"
+///
+/// [retry]
+/// attempts = 5
+/// backoff = 50
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Config {
+    #[serde(default)]
+    wifi: Option<IfName>,
+    #[serde(default)]
+    lladdr: Option<LLAddr>,
+    #[serde(default, deserialize_with = "patterns")]
+    patterns: Option<Vec<IfPattern>>,
+    #[serde(default, deserialize_with = "alternate")]
+    alternate: Option<Alternate>,
+    #[serde(default)]
+    debounce: Option<u64>,
+    #[serde(default)]
+    rules: Option<Vec<Rule>>,
+    #[serde(default)]
+    profile: BTreeMap<String, Options>,
+    #[serde(default)]
+    hooks: Hooks,
+    #[serde(default, deserialize_with = "retry")]
+    retry: Retry,
+}
+
+/// Fully resolved options for `nicr roam`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Settings {
+    pub(crate) wifi: IfName,
+    pub(crate) lladdr: LLAddr,
+    pub(crate) alternate: Alternate,
+    pub(crate) debounce: Duration,
+    /// The `rules` given, or else a single one for `patterns`, one of them
+    /// being required.
+    pub(crate) rules: Vec<Rule>,
+    pub(crate) hooks: Hooks,
+    /// How often an address change is read back before giving up.
+    pub(crate) retry: Retry,
+}
+
+impl Config {
+    pub(crate) fn load(path: &Path) -> Result<Config> {
+        let content = fs::read_to_string(path)
+            .map_err(|error| Error::Read(path.into(), error.to_string()))?;
+        Self::parse(path, &content)
+    }
+
+    fn parse(path: &Path, content: &str) -> Result<Config> {
+        serde_path_to_error::deserialize(toml::Deserializer::new(content)).map_err(|error| {
+            let key = error.path().to_string();
+            let error = error.into_inner();
+            let line = error.span().map_or(0, |span| line_of(content, span.start));
+            Error::Parse(path.into(), key, line, error.message().to_string()).into()
+        })
+    }
+
+    /// First existing file of `$XDG_CONFIG_HOME/nicr/config.toml` (or
+    /// `~/.config/nicr/config.toml`) and `/etc/nicr.toml`.
+    pub(crate) fn find() -> Option<PathBuf> {
+        candidates(
+            std::env::var_os("XDG_CONFIG_HOME"),
+            std::env::var_os("HOME"),
+        )
+        .into_iter()
+        .find(|path| path.is_file())
+    }
+
+    /// How often an address change is read back before giving up, for the
+    /// commands that take no other setting.
+    pub(crate) fn retry(&self) -> Retry {
+        self.retry
+    }
+
+    /// Resolves `profile` over the top-level options, then `args` over both.
+    /// Patterns of the profile or of `args` would be ignored in favor of the
+    /// rules beneath them, they are rejected instead.
+    pub(crate) fn settings(&self, profile: Option<&str>, args: Options) -> Result<Settings> {
+        let options = Options {
+            wifi: self.wifi,
+            lladdr: self.lladdr,
+            patterns: self.patterns.clone(),
+            alternate: self.alternate.clone(),
+            debounce: self.debounce,
+            rules: self.rules.clone(),
+        };
+        let options = match profile {
+            Some(name) => match self.profile.get(name) {
+                Some(profile) if profile.patterns_over_rules(&options) => {
+                    return Err(Error::PatternsWithRules(Some(name.into()), false).into())
+                }
+                Some(profile) => profile.clone().or(options),
+                None => return Err(Error::UnknownProfile(name.into()).into()),
+            },
+            None => options,
+        };
+        if args.patterns_over_rules(&options) {
+            return Err(Error::PatternsWithRules(profile.map(str::to_string), true).into());
+        }
+        let options = args.or(options);
+
+        let missing = |key| Error::MissingKey(key, profile.map(str::to_string));
+
+        let wifi = options.wifi.ok_or_else(|| missing("wifi"))?;
+        let lladdr = options.lladdr.ok_or_else(|| missing("lladdr"))?;
+        // No default, any virtual Ethernet interface would match it.
+        let rules = match (options.patterns, options.rules) {
+            (_, Some(rules)) => rules,
+            (Some(patterns), None) => vec![Rule::wired(patterns)],
+            (None, None) => return Err(missing("patterns").into()),
+        };
+        Ok(Settings {
+            wifi,
+            lladdr,
+            rules,
+            alternate: options.alternate.unwrap_or(Alternate::Random),
+            debounce: Duration::from_millis(options.debounce.unwrap_or(DEFAULT_DEBOUNCE)),
+            hooks: self.hooks.clone(),
+            retry: self.retry,
+        })
+    }
+}
+
+fn candidates(xdg_config_home: Option<OsString>, home: Option<OsString>) -> Vec<PathBuf> {
+    let config_home = match (xdg_config_home, home) {
+        (Some(dir), _) if !dir.is_empty() => Some(PathBuf::from(dir)),
+        (_, Some(home)) if !home.is_empty() => Some(PathBuf::from(home).join(".config")),
+        _ => None,
+    };
+
+    config_home
+        .map(|dir| dir.join(CONFIG_FILE))
+        .into_iter()
+        .chain([PathBuf::from(SYSTEM_CONFIG_FILE)])
+        .collect()
+}
+
+fn line_of(content: &str, offset: usize) -> usize {
+    content.as_bytes()[..offset.min(content.len())]
+        .iter()
+        .filter(|&&c| c == b'\n')
+        .count()
+        + 1
+}
+
+fn patterns<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Vec<IfPattern>>, D::Error> {
+    let values = Vec::<String>::deserialize(deserializer)?;
+    Ok(Some(
+        values.iter().map(|value| value.as_str().into()).collect(),
+    ))
+}
+
+fn alternate<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Alternate>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(D::Error::custom)
+}
+
+/// `[retry]` table of `attempts` and a `backoff` in milliseconds, each
+/// defaulting to `Retry::default()`.
+fn retry<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Retry, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Table {
+        #[serde(default)]
+        attempts: Option<u32>,
+        #[serde(default)]
+        backoff: Option<u64>,
+    }
+
+    let table = Table::deserialize(deserializer)?;
+    let default = Retry::default();
+    Ok(Retry {
+        attempts: table.attempts.unwrap_or(default.attempts),
+        backoff: table.backoff.map_or(default.backoff, Duration::from_millis),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::time::Duration;
+
+    use net_sys::retry::Retry;
+
+    use crate::handover::Alternate;
+    use crate::hooks::Hooks;
+    use crate::policy::{Kind, LinkState, Rule};
+    use crate::Result;
+
+    use super::{candidates, is_usage_error, Config, Options, Settings};
+
+    const CONFIG: &str = r#"
+wifi = "en0"
+lladdr = "02:11:22:33:44:55"
+patterns = ["en*"]
+
+[profile.office]
+patterns = ["enx*", "en7"]
+alternate = "02:aa:bb:cc:dd:ee"
+
+[profile.lab]
+wifi = "wlan0"
+debounce = 1000
+
+[[profile.dock.rules]]
+patterns = ["enx*"]
+
+[[profile.dock.rules]]
+type = "wifi"
+link = "up"
+
+[[profile.dock.rules]]
+patterns = ["en*"]
+type = "wired"
+
+[hooks]
+applied = "networkctl renew $NICR_IFNAME"
+timeout = 30
+
+[retry]
+backoff = 50
+"#;
+
+    fn parse(content: &str) -> Result<Config> {
+        Config::parse(Path::new("nicr.toml"), content)
+    }
+
+    #[test]
+    fn test_config_settings() -> Result<()> {
+        let settings = parse(CONFIG)?.settings(None, Options::default())?;
+
+        let expected = Settings {
+            wifi: "en0".try_into()?,
+            lladdr: "02:11:22:33:44:55".parse()?,
+            alternate: Alternate::Random,
+            debounce: Duration::from_millis(250),
+            rules: vec![Rule::wired(vec!["en*".into()])],
+            hooks: Hooks {
+                applied: Some("networkctl renew $NICR_IFNAME".into()),
+                timeout: Duration::from_secs(30),
+                ..Hooks::default()
+            },
+            retry: Retry {
+                attempts: 3,
+                backoff: Duration::from_millis(50),
+            },
+        };
+
+        assert_eq!(settings, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_settings_profile() -> Result<()> {
+        let settings = parse(CONFIG)?.settings(Some("office"), Options::default())?;
+
+        let expected = Settings {
+            wifi: "en0".try_into()?,
+            lladdr: "02:11:22:33:44:55".parse()?,
+            alternate: Alternate::Fixed("02:aa:bb:cc:dd:ee".parse()?),
+            debounce: Duration::from_millis(250),
+            rules: vec![Rule::wired(vec!["enx*".into(), "en7".into()])],
+            hooks: settings.hooks.clone(),
+            retry: settings.retry,
+        };
+
+        assert_eq!(settings, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_settings_rules() -> Result<()> {
+        let settings = parse(CONFIG)?.settings(Some("dock"), Options::default())?;
+
+        let expected = vec![
+            Rule {
+                patterns: vec!["enx*".into()],
+                kind: None,
+                link: None,
+            },
+            Rule {
+                patterns: vec!["*".into()],
+                kind: Some(Kind::Wifi),
+                link: Some(LinkState::Up),
+            },
+            Rule::wired(vec!["en*".into()]),
+        ];
+
+        assert_eq!(settings.rules, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_parse_rule_error() {
+        let expected_error = "Config::ParseError { path: \"nicr.toml\", key: \"rules[0].link\", line: 2, error: \"unknown variant `running`, expected `up` or `down`\" }";
+
+        let error = parse("[[rules]]\nlink = \"running\"\n").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_config_settings_args_override() -> Result<()> {
+        let args = Options {
+            wifi: Some("en1".try_into()?),
+            ..Options::default()
+        };
+
+        let settings = parse(CONFIG)?.settings(Some("lab"), args)?;
+
+        assert_eq!(settings.wifi, "en1".try_into()?);
+        assert_eq!(settings.rules, vec![Rule::wired(vec!["en*".into()])]);
+        assert_eq!(settings.debounce, Duration::from_secs(1));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_settings_args_patterns_with_rules() {
+        let expected_error =
+            "Config::PatternsWithRulesError { profile: Some(\"dock\"), args: true }";
+
+        let args = Options {
+            patterns: Some(vec!["en7".into()]),
+            ..Options::default()
+        };
+
+        let config = parse(CONFIG).unwrap();
+        let error = config.settings(Some("dock"), args.clone()).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert!(is_usage_error(error.as_ref()));
+        // Without rules, the patterns given replace those of the file.
+        assert_eq!(
+            config.settings(Some("office"), args).unwrap().rules,
+            vec![Rule::wired(vec!["en7".into()])]
+        );
+    }
+
+    #[test]
+    fn test_config_settings_profile_patterns_with_rules() {
+        let expected_error =
+            "Config::PatternsWithRulesError { profile: Some(\"office\"), args: false }";
+
+        let config = parse(
+            "wifi = \"en0\"\nlladdr = \"02:11:22:33:44:55\"\n\
+             [[rules]]\npatterns = [\"en*\"]\n\
+             [profile.office]\npatterns = [\"enx*\"]\n",
+        )
+        .unwrap();
+        let error = config
+            .settings(Some("office"), Options::default())
+            .unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert!(!is_usage_error(error.as_ref()));
+        // The top-level rules alone are fine.
+        assert!(config.settings(None, Options::default()).is_ok());
+    }
+
+    #[test]
+    fn test_config_settings_defaults() -> Result<()> {
+        let settings =
+            parse("wifi = \"en0\"\nlladdr = \"02:11:22:33:44:55\"\npatterns = [\"en*\"]\n")?
+                .settings(None, Options::default())?;
+
+        assert_eq!(settings.alternate, Alternate::Random);
+        assert_eq!(settings.hooks, Hooks::default());
+        assert_eq!(settings.retry, Retry::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_settings_missing_key() {
+        let expected_error = "Config::MissingKeyError { key: \"lladdr\", profile: Some(\"lab\") }";
+
+        let error = parse("[profile.lab]\nwifi = \"en0\"\n")
+            .unwrap()
+            .settings(Some("lab"), Options::default())
+            .unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(format!("{:?}", error), expected_error);
+    }
+
+    #[test]
+    fn test_config_settings_missing_patterns() -> Result<()> {
+        let expected_error = "Config::MissingKeyError { key: \"patterns\", profile: None }";
+
+        let config = parse("wifi = \"en0\"\nlladdr = \"02:11:22:33:44:55\"\n")?;
+        let error = config.settings(None, Options::default()).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+
+        let args = Options {
+            patterns: Some(vec!["enx*".into()]),
+            ..Options::default()
+        };
+
+        assert_eq!(
+            config.settings(None, args)?.rules,
+            vec![Rule::wired(vec!["enx*".into()])]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_parse_rule_missing_patterns() {
+        let expected_error = "Config::ParseError { path: \"nicr.toml\", key: \"rules[0]\", line: 1, error: \"missing field `patterns`, only optional with type = \\\"wifi\\\"\" }";
+
+        let error = parse("[[rules]]\ntype = \"wired\"\n").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_config_settings_unknown_profile() {
+        let expected_error = "Config::UnknownProfileError { profile: \"home\" }";
+
+        let error = parse(CONFIG)
+            .unwrap()
+            .settings(Some("home"), Options::default())
+            .unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_config_parse_ifname_error() {
+        let expected_error = "Config::ParseError { path: \"nicr.toml\", key: \"profile.lab.wifi\", line: 3, error: \"IfName::TooSmallError { value: \\\"en\\\", len: 2, min: 3 }\" }";
+
+        let error = parse("wifi = \"en0\"\n[profile.lab]\nwifi = \"en\"\n").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_config_parse_lladdr_error() {
+        let expected_error = "Config::ParseError { path: \"nicr.toml\", key: \"lladdr\", line: 1, error: \"LinkLevelAddress::WrongNumberOfOctetsError { value: \\\"01:02:03\\\", value_octets: 3, expected_octets: 6 }\" }";
+
+        let error = parse("lladdr = \"01:02:03\"\n").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_config_parse_unknown_key() {
+        let expected_error = "Config::ParseError { path: \"nicr.toml\", key: \"profile.office.patern\", line: 3, error: \"unknown field `patern`, expected one of `wifi`, `lladdr`, `patterns`, `alternate`, `debounce`, `rules`\" }";
+
+        let error = parse("wifi = \"en0\"\n[profile.office]\npatern = [\"en*\"]\n").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_config_parse_type_error() {
+        let expected_error = "Config::ParseError { path: \"nicr.toml\", key: \"patterns\", line: 1, error: \"invalid type: string \\\"en*\\\", expected a sequence\" }";
+
+        let error = parse("patterns = \"en*\"\n").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_config_load_error() {
+        let expected_error = "Config::ReadError { path: \"/nonexistent/nicr.toml\", error: \"No such file or directory (os error 2)\" }";
+
+        let error = Config::load(Path::new("/nonexistent/nicr.toml")).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_config_candidates() {
+        assert_eq!(
+            candidates(Some("/xdg".into()), Some("/home/user".into())),
+            vec![
+                PathBuf::from("/xdg/nicr/config.toml"),
+                PathBuf::from("/etc/nicr.toml")
+            ]
+        );
+        assert_eq!(
+            candidates(Some("".into()), Some("/home/user".into())),
+            vec![
+                PathBuf::from("/home/user/.config/nicr/config.toml"),
+                PathBuf::from("/etc/nicr.toml")
+            ]
+        );
+        assert_eq!(
+            candidates(None, None),
+            vec![PathBuf::from("/etc/nicr.toml")]
+        );
+    }
+}
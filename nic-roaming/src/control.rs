@@ -0,0 +1,513 @@
+use std::ffi::c_int;
+use std::fmt::{Debug, Display};
+use std::fs::{self, DirBuilder};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use net_sys::ifname::IfName;
+use net_sys::lladdr::LLAddr;
+
+use crate::output::LLAddrRecord;
+use crate::roam::Roam;
+use crate::Result;
+
+#[cfg(target_os = "linux")]
+pub(crate) const CONTROL_SOCKET: &str = "/run/nicr.sock";
+#[cfg(not(target_os = "linux"))]
+pub(crate) const CONTROL_SOCKET: &str = "/var/run/nicr.sock";
+
+/// How long a client may take to send its request.
+const READ_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Clone, PartialEq, Eq)]
+enum Error {
+    Bind(PathBuf, String),
+    InUse(PathBuf),
+    Connect(PathBuf, String),
+    Request(String),
+    Response(String),
+    Failed(String),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Bind(path, error) => f
+                .debug_struct("Control::BindError")
+                .field("path", path)
+                .field("error", error)
+                .finish(),
+            Error::InUse(path) => f
+                .debug_struct("Control::InUseError")
+                .field("path", path)
+                .finish(),
+            Error::Connect(path, error) => f
+                .debug_struct("Control::ConnectError")
+                .field("path", path)
+                .field("error", error)
+                .finish(),
+            Error::Request(request) => f
+                .debug_struct("Control::RequestError")
+                .field("request", request)
+                .finish(),
+            Error::Response(error) => f
+                .debug_struct("Control::ResponseError")
+                .field("error", error)
+                .finish(),
+            Error::Failed(error) => f
+                .debug_struct("Control::FailedError")
+                .field("error", error)
+                .finish(),
+        }
+    }
+}
+
+/// Request to a running `nicr roam`, sent as its name on a single line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Request {
+    /// Shared address, interfaces in use and whether roaming is paused
+    Status,
+    /// Addresses nicr left on each interface
+    Assignments,
+    /// Interfaces seen through link events
+    Interfaces,
+    /// Stop handing over until resumed
+    Pause,
+    /// Hand over again, catching up with the events seen while paused
+    Resume,
+    /// Apply the shared address again where it belongs
+    Reapply,
+}
+
+impl Display for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.to_possible_value() {
+            Some(value) => write!(f, "{}", value.get_name()),
+            None => write!(f, "{:?}", self),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct Status {
+    pub(crate) paused: bool,
+    pub(crate) dry_run: bool,
+    pub(crate) wifi: IfName,
+    pub(crate) lladdr: LLAddr,
+    pub(crate) active: Option<IfName>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct InterfaceRecord {
+    pub(crate) index: c_int,
+    pub(crate) ifname: IfName,
+    pub(crate) lladdr: LLAddr,
+}
+
+/// Answer to a `Request`, sent as a single line of JSON.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum Response {
+    Status(Status),
+    Assignments(Vec<LLAddrRecord>),
+    Interfaces(Vec<InterfaceRecord>),
+    Done,
+    Error(String),
+}
+
+impl Response {
+    fn of(roam: &mut Roam, request: Request) -> Response {
+        let done = |result: Result<()>| match result {
+            Ok(()) => Response::Done,
+            Err(err) => Response::Error(err.to_string()),
+        };
+
+        match request {
+            Request::Status => Response::Status(Status {
+                paused: roam.is_paused(),
+                dry_run: roam.handover().is_dry_run(),
+                wifi: *roam.handover().wifi(),
+                lladdr: *roam.handover().lladdr(),
+                active: roam.active().copied(),
+            }),
+            Request::Assignments => {
+                let mut records: Vec<_> = roam
+                    .handover()
+                    .assignments()
+                    .iter()
+                    .map(|(ifname, lladdr)| LLAddrRecord {
+                        ifname: *ifname,
+                        lladdr: *lladdr,
+                    })
+                    .collect();
+                records.sort_by_key(|record| record.ifname.to_string());
+                Response::Assignments(records)
+            }
+            Request::Interfaces => {
+                let mut records: Vec<_> = roam
+                    .known()
+                    .iter()
+                    .map(|(ifname, link)| InterfaceRecord {
+                        index: link.index,
+                        ifname: *ifname,
+                        lladdr: link.lladdr,
+                    })
+                    .collect();
+                records.sort_by_key(|record| record.index);
+                Response::Interfaces(records)
+            }
+            Request::Pause => {
+                roam.pause();
+                log::info!("roam: paused");
+                Response::Done
+            }
+            Request::Resume => {
+                log::info!("roam: resumed");
+                done(roam.resume())
+            }
+            Request::Reapply => done(roam.reapply()),
+        }
+    }
+}
+
+/// Unix-domain socket answering requests about a running `nicr roam`.
+#[derive(Debug)]
+pub(crate) struct Control {
+    listener: UnixListener,
+}
+
+impl Control {
+    /// Listens on `path`, replacing the socket a previous run left behind
+    /// unless that run still answers on it. Only the owner may connect, as
+    /// requests change addresses, so the socket is made private before it
+    /// shows up at `path`.
+    pub(crate) fn bind(path: &Path) -> Result<Self> {
+        let error = |error: io::Error| Error::Bind(path.into(), error.to_string());
+
+        match fs::symlink_metadata(path) {
+            Ok(metadata) if !metadata.file_type().is_socket() => {
+                return Err(error(ErrorKind::AlreadyExists.into()).into());
+            }
+            Ok(_) if UnixStream::connect(path).is_ok() => {
+                return Err(Error::InUse(path.into()).into());
+            }
+            // A stale socket, which the private one replaces.
+            Ok(_) => (),
+            Err(err) if err.kind() == ErrorKind::NotFound => (),
+            Err(err) => return Err(error(err).into()),
+        }
+
+        let listener = bind_private(path).map_err(error)?;
+
+        Ok(Self { listener })
+    }
+
+    /// Answers connections one at a time, for as long as the process runs.
+    pub(crate) fn serve(&self, roam: &Mutex<Roam>) {
+        loop {
+            if let Err(err) = self.accept(roam) {
+                log::error!("{err}");
+            }
+        }
+    }
+
+    fn accept(&self, roam: &Mutex<Roam>) -> Result<()> {
+        let (stream, _) = self.listener.accept()?;
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+
+        let response = match Request::from_str(line.trim(), false) {
+            Ok(request) => {
+                let mut roam = roam.lock().unwrap_or_else(PoisonError::into_inner);
+                Response::of(&mut roam, request)
+            }
+            Err(_) => Response::Error(Error::Request(line.trim().into()).to_string()),
+        };
+
+        writeln!(&stream, "{}", serde_json::to_string(&response)?)?;
+
+        Ok(())
+    }
+}
+
+/// Socket bound in a directory only the owner may enter, next to `path`,
+/// then made private and moved to `path`.
+fn bind_private(path: &Path) -> io::Result<UnixListener> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let dir = path.with_file_name(format!(".{name}.{}", process::id()));
+    // Left behind by a process of the same id, nobody else can enter it.
+    let _ = fs::remove_dir_all(&dir);
+    DirBuilder::new().mode(0o700).create(&dir)?;
+
+    let socket = dir.join("sock");
+    let result = UnixListener::bind(&socket).and_then(|listener| {
+        fs::set_permissions(&socket, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&socket, path)?;
+        Ok(listener)
+    });
+    let _ = fs::remove_dir_all(&dir);
+
+    result
+}
+
+/// Sends `request` to the `nicr roam` listening on `path`. A response
+/// reporting an error is returned as one.
+pub(crate) fn request(path: &Path, request: Request) -> Result<Response> {
+    let stream =
+        UnixStream::connect(path).map_err(|err| Error::Connect(path.into(), err.to_string()))?;
+    writeln!(&stream, "{request}")?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    match serde_json::from_str(&line).map_err(|err| Error::Response(err.to_string()))? {
+        Response::Error(error) => Err(Error::Failed(error).into()),
+        response => Ok(response),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::net::UnixStream;
+    use std::path::{Path, PathBuf};
+    use std::sync::Mutex;
+    use std::thread;
+
+    use net_sys::nic::NicEvent::NicNew;
+    use net_sys::testing::FakeKernel;
+
+    use crate::handover::tests::{handover, kernel, ALT_LLADDR, ENX, ENX_LLADDR, LLADDR, WIFI};
+    use crate::output::LLAddrRecord;
+    use crate::policy::Rule;
+    use crate::roam::Roam;
+    use crate::testing::test_path;
+    use crate::Result;
+
+    use super::{request, Control, InterfaceRecord, Request, Response, Status};
+
+    /// Socket path unique to the calling test.
+    fn control_path(test: &str) -> PathBuf {
+        test_path(test, "ctl")
+    }
+
+    fn roam(kernel: &FakeKernel) -> Mutex<Roam> {
+        Mutex::new(Roam::new(
+            handover(kernel),
+            vec![Rule::wired(vec!["en7".into()])],
+        ))
+    }
+
+    /// Serves a single connection on this thread while `client` talks to
+    /// `path` from another one.
+    fn serve_one<T: Send + 'static>(
+        path: &Path,
+        roam: &Mutex<Roam>,
+        client: fn(PathBuf) -> T,
+    ) -> Result<T> {
+        let control = Control::bind(path)?;
+        let path = path.to_path_buf();
+        let client = thread::spawn(move || client(path));
+
+        control.accept(roam)?;
+
+        Ok(client.join().unwrap())
+    }
+
+    #[test]
+    fn test_request_display() {
+        assert_eq!(Request::Status.to_string(), "status");
+        assert_eq!(Request::Reapply.to_string(), "reapply");
+    }
+
+    #[test]
+    fn test_response_serialize() -> Result<()> {
+        let response = Response::Assignments(vec![LLAddrRecord {
+            ifname: *ENX,
+            lladdr: *LLADDR,
+        }]);
+
+        assert_eq!(
+            serde_json::to_string(&response)?,
+            r#"{"assignments":[{"ifname":"en7","lladdr":"02:11:22:33:44:55"}]}"#
+        );
+        assert_eq!(serde_json::to_string(&Response::Done)?, r#""done""#);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_status() -> Result<()> {
+        let roam = roam(&kernel());
+
+        let response = serve_one(&control_path("status"), &roam, |path| {
+            request(&path, Request::Status).unwrap()
+        })?;
+
+        let expected = Response::Status(Status {
+            paused: false,
+            dry_run: false,
+            wifi: *WIFI,
+            lladdr: *LLADDR,
+            active: None,
+        });
+
+        assert_eq!(response, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_interfaces_and_assignments() -> Result<()> {
+        let roam = roam(&kernel());
+        let path = control_path("interfaces");
+        roam.lock().unwrap().pause();
+        roam.lock()
+            .unwrap()
+            .handle(&NicNew((2, *ENX, *ENX_LLADDR)))?;
+
+        let interfaces = serve_one(&path, &roam, |path| {
+            request(&path, Request::Interfaces).unwrap()
+        })?;
+        serve_one(&path, &roam, |path| {
+            request(&path, Request::Resume).unwrap()
+        })?;
+        let assignments = serve_one(&path, &roam, |path| {
+            request(&path, Request::Assignments).unwrap()
+        })?;
+
+        let expected_interfaces = Response::Interfaces(vec![InterfaceRecord {
+            index: 2,
+            ifname: *ENX,
+            lladdr: *ENX_LLADDR,
+        }]);
+        let expected_assignments = Response::Assignments(vec![
+            LLAddrRecord {
+                ifname: *WIFI,
+                lladdr: *ALT_LLADDR,
+            },
+            LLAddrRecord {
+                ifname: *ENX,
+                lladdr: *LLADDR,
+            },
+        ]);
+
+        assert_eq!(interfaces, expected_interfaces);
+        assert_eq!(assignments, expected_assignments);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_pause() -> Result<()> {
+        let roam = roam(&kernel());
+        let path = control_path("pause");
+
+        let pause = serve_one(&path, &roam, |path| request(&path, Request::Pause).unwrap())?;
+        let reapply = serve_one(&path, &roam, |path| {
+            request(&path, Request::Reapply).unwrap_err().to_string()
+        })?;
+
+        assert_eq!(pause, Response::Done);
+        assert!(roam.lock().unwrap().is_paused());
+        assert_eq!(
+            reapply,
+            "Control::FailedError { error: \"Roam::PausedError\" }"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_unknown_request() -> Result<()> {
+        let roam = roam(&kernel());
+
+        let line = serve_one(&control_path("unknown"), &roam, |path| {
+            let mut stream = UnixStream::connect(path).unwrap();
+            stream.write_all(b"reboot\n").unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            line
+        })?;
+
+        assert_eq!(
+            line,
+            "{\"error\":\"Control::RequestError { request: \\\"reboot\\\" }\"}\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_bind_replaces_stale_socket() -> Result<()> {
+        let path = control_path("stale");
+        drop(Control::bind(&path)?);
+
+        Control::bind(&path)?;
+
+        assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o600);
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_bind_in_use() -> Result<()> {
+        let path = control_path("in_use");
+        let _control = Control::bind(&path)?;
+
+        let expected_error = format!("Control::InUseError {{ path: {:?} }}", path);
+
+        let error = Control::bind(&path).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        // Still answering.
+        UnixStream::connect(&path)?;
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_control_bind_not_a_socket() -> Result<()> {
+        let path = control_path("not_a_socket");
+        fs::write(&path, "")?;
+
+        let error = Control::bind(&path).unwrap_err();
+
+        assert!(format!("{}", error).starts_with("Control::BindError { path: "));
+        assert!(path.is_file());
+        fs::remove_file(&path)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_request_connect_error() {
+        let path = control_path("missing");
+
+        let error = request(&path, Request::Status).unwrap_err();
+
+        assert!(format!("{}", error).starts_with("Control::ConnectError { path: "));
+    }
+}
@@ -0,0 +1,693 @@
+use std::collections::HashMap;
+use std::ffi::c_int;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::Read;
+use std::str::FromStr;
+
+use net_sys::backend::NicBackend;
+use net_sys::dry_run::Nic;
+use net_sys::ifname::IfName;
+use net_sys::lladdr::LLAddr;
+use net_sys::retry::Retry;
+
+use crate::hooks::{Hook, HookEnv, HookQueue, Hooks};
+use crate::state::State;
+use crate::Result;
+
+/// Interfaces the handovers are made on, and whose monitor `Roam` reads,
+/// e.g. the kernel's with `OsBackend`.
+pub(crate) trait Backend: NicBackend + Debug + Send {}
+
+impl<B: NicBackend + Debug + Send> Backend for B {}
+
+/// Address the Wi-Fi interface moves to while the wired one holds the shared
+/// address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Alternate {
+    Fixed(LLAddr),
+    Random,
+}
+
+impl Alternate {
+    fn lladdr(&self) -> Result<LLAddr> {
+        match self {
+            Alternate::Fixed(lladdr) => Ok(*lladdr),
+            Alternate::Random => random_lladdr(),
+        }
+    }
+}
+
+impl FromStr for Alternate {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(value: &str) -> Result<Self> {
+        match value {
+            "random" => Ok(Alternate::Random),
+            lladdr => Ok(Alternate::Fixed(lladdr.parse()?)),
+        }
+    }
+}
+
+/// Random unicast address with the locally administered bit set.
+fn random_lladdr() -> Result<LLAddr> {
+    let mut octets = [0; 6];
+    File::open("/dev/urandom")?.read_exact(&mut octets)?;
+    octets[0] = (octets[0] & 0xfc) | 0x02;
+    Ok(LLAddr::from(&octets))
+}
+
+/// Moves the shared address between the Wi-Fi interface and a wired one so
+/// that both never hold it at the same time. Every change is read back with
+/// `get_lladdr` and retried as `retry` allows, and a failed step undoes the
+/// ones before it. With a `State`, the address an interface had before its
+/// first change is persisted first, and nothing changes if that fails.
+#[derive(Debug)]
+pub(crate) struct Handover {
+    wifi: IfName,
+    lladdr: LLAddr,
+    alternate: Alternate,
+    assigned: HashMap<IfName, LLAddr>,
+    originals: HashMap<IfName, LLAddr>,
+    state: Option<State>,
+    hooks: HookQueue,
+    retry: Retry,
+    nic: Nic<Box<dyn Backend>>,
+}
+
+impl Handover {
+    pub(crate) fn new(
+        backend: impl Backend + 'static,
+        wifi: IfName,
+        lladdr: LLAddr,
+        alternate: Alternate,
+    ) -> Self {
+        let backend: Box<dyn Backend> = Box::new(backend);
+        Self {
+            wifi,
+            lladdr,
+            alternate,
+            assigned: HashMap::new(),
+            originals: HashMap::new(),
+            state: None,
+            hooks: HookQueue::default(),
+            retry: Retry::default(),
+            nic: Nic::default().with_backend(backend),
+        }
+    }
+
+    /// Makes the same decisions, only logging the changes instead of making
+    /// them.
+    pub(crate) fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.nic = Nic::new(dry_run).with_backend(self.nic.into_backend());
+        self
+    }
+
+    pub(crate) fn with_state(mut self, state: State) -> Self {
+        self.state = Some(state);
+        self
+    }
+
+    pub(crate) fn with_hooks(mut self, hooks: Hooks) -> Self {
+        self.hooks = HookQueue::new(hooks);
+        self
+    }
+
+    pub(crate) fn with_retry(mut self, retry: Retry) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    pub(crate) fn wifi(&self) -> &IfName {
+        &self.wifi
+    }
+
+    pub(crate) fn lladdr(&self) -> &LLAddr {
+        &self.lladdr
+    }
+
+    pub(crate) fn backend(&self) -> &dyn Backend {
+        self.nic.backend().as_ref()
+    }
+
+    pub(crate) fn is_dry_run(&self) -> bool {
+        self.nic.is_dry_run()
+    }
+
+    /// Addresses the last handovers left on the Wi-Fi interface and the
+    /// wired one.
+    pub(crate) fn assignments(&self) -> &HashMap<IfName, LLAddr> {
+        &self.assigned
+    }
+
+    /// Queues the `hook` command, only logged in a dry run.
+    pub(crate) fn hook(&self, hook: Hook, env: &HookEnv) {
+        self.hooks.push(hook, env, self.nic.is_dry_run());
+    }
+
+    /// Wi-Fi to the alternate address first, then the wired interface at
+    /// `index` to the shared one.
+    pub(crate) fn switch_to_wired(
+        &mut self,
+        index: c_int,
+        wired: &IfName,
+        wired_lladdr: &LLAddr,
+    ) -> Result<()> {
+        let wifi_lladdr = self.nic.get_lladdr(&self.wifi)?;
+        let wifi_moved = wifi_lladdr == self.lladdr;
+
+        if wifi_moved {
+            let wifi = self.wifi;
+            self.remember(&wifi, &wifi_lladdr)?;
+        }
+        self.remember(wired, wired_lladdr)?;
+
+        let mut alternate = None;
+        if wifi_moved {
+            let lladdr = self.alternate.lladdr()?;
+            let reason = format!("alternate while {wired} holds the shared address");
+            if let Err(err) = self.apply(&self.wifi, None, &wifi_lladdr, &lladdr, &reason) {
+                self.rollback(&self.wifi, None, &lladdr, &wifi_lladdr);
+                return Err(err);
+            }
+            log::info!("handover: {} {wifi_lladdr} -> {lladdr}", self.wifi);
+            self.assigned.insert(self.wifi, lladdr);
+            alternate = Some(lladdr);
+        }
+
+        let index = Some(index);
+        if let Err(err) = self.apply(wired, index, wired_lladdr, &self.lladdr, "shared address") {
+            // The shared address only goes back to Wi-Fi once it is known to
+            // be off the wired interface.
+            if self.rollback(wired, index, &self.lladdr, wired_lladdr) {
+                if let Some(alternate) = alternate {
+                    if self.rollback(&self.wifi, None, &alternate, &wifi_lladdr) {
+                        self.assigned.insert(self.wifi, wifi_lladdr);
+                    }
+                }
+            }
+            return Err(err);
+        }
+
+        log::info!("handover: {wired} {wired_lladdr} -> {}", self.lladdr);
+        self.assigned.insert(*wired, self.lladdr);
+        if *wired_lladdr != self.lladdr {
+            self.originals.insert(*wired, *wired_lladdr);
+        }
+
+        Ok(())
+    }
+
+    /// Shared address off the wired interface at `index`, which stays, back
+    /// to the address it had before the handover, or else the one saved in
+    /// the state file, or else a random one.
+    pub(crate) fn release(&mut self, index: c_int, wired: &IfName) -> Result<()> {
+        let original = match self.originals.get(wired).copied() {
+            Some(original) => Some(original),
+            None => self.state.as_ref().and_then(|state| state.original(wired)),
+        };
+        let lladdr = match original {
+            Some(lladdr) => lladdr,
+            None => random_lladdr()?,
+        };
+
+        let index = Some(index);
+        if let Err(err) = self.apply(
+            wired,
+            index,
+            &self.lladdr,
+            &lladdr,
+            "shared address released",
+        ) {
+            self.rollback(wired, index, &lladdr, &self.lladdr);
+            return Err(err);
+        }
+
+        log::info!("handover: {wired} {} -> {lladdr}", self.lladdr);
+        self.assigned.insert(*wired, lladdr);
+
+        Ok(())
+    }
+
+    /// Shared address back to Wi-Fi, once the wired interface is gone.
+    pub(crate) fn switch_to_wifi(&mut self) -> Result<()> {
+        self.assigned.retain(|ifname, _| *ifname == self.wifi);
+
+        let wifi_lladdr = self.nic.get_lladdr(&self.wifi)?;
+        if wifi_lladdr == self.lladdr {
+            return Ok(());
+        }
+
+        let wifi = self.wifi;
+        self.remember(&wifi, &wifi_lladdr)?;
+
+        if let Err(err) = self.apply(
+            &self.wifi,
+            None,
+            &wifi_lladdr,
+            &self.lladdr,
+            "shared address back",
+        ) {
+            self.rollback(&self.wifi, None, &self.lladdr, &wifi_lladdr);
+            return Err(err);
+        }
+
+        log::info!("handover: {} {wifi_lladdr} -> {}", self.wifi, self.lladdr);
+        self.assigned.insert(self.wifi, self.lladdr);
+
+        Ok(())
+    }
+
+    fn remember(&mut self, ifname: &IfName, lladdr: &LLAddr) -> Result<()> {
+        match &mut self.state {
+            Some(state) => state.remember(ifname, lladdr),
+            None => Ok(()),
+        }
+    }
+
+    /// Changes `ifname` from `old` to `lladdr`, then runs the applied or
+    /// failed hook.
+    fn apply(
+        &self,
+        ifname: &IfName,
+        index: Option<c_int>,
+        old: &LLAddr,
+        lladdr: &LLAddr,
+        reason: &str,
+    ) -> Result<()> {
+        let result = self.set_lladdr(ifname, lladdr, reason);
+
+        let env = HookEnv {
+            ifname: *ifname,
+            index,
+            lladdr: *lladdr,
+            old_lladdr: Some(*old),
+        };
+        match result {
+            Ok(()) => self.hook(Hook::Applied, &env),
+            Err(_) => self.hook(Hook::Failed, &env),
+        }
+
+        result
+    }
+
+    fn set_lladdr(&self, ifname: &IfName, lladdr: &LLAddr, reason: &str) -> Result<()> {
+        self.nic
+            .set_lladdr_verified(ifname, lladdr, &self.retry, reason)?;
+        Ok(())
+    }
+
+    fn rollback(
+        &self,
+        ifname: &IfName,
+        index: Option<c_int>,
+        from: &LLAddr,
+        lladdr: &LLAddr,
+    ) -> bool {
+        match self.apply(ifname, index, from, lladdr, "rollback") {
+            Ok(()) => true,
+            Err(err) => {
+                log::error!("rollback of {ifname} to {lladdr} failed: {err}");
+                false
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::HashMap;
+    use std::ffi::c_int;
+    use std::sync::LazyLock;
+    use std::time::Duration;
+
+    use net_sys::error::{ErrorKind, Operation};
+    use net_sys::ifname::IfName;
+    use net_sys::lladdr::LLAddr;
+    use net_sys::retry::Retry;
+    use net_sys::testing::{Call, FakeKernel};
+    use net_sys::Error;
+
+    use crate::hooks::tests::recording_hooks;
+    use crate::state::tests::state_path;
+    use crate::state::State;
+    use crate::Result;
+
+    use super::{random_lladdr, Alternate, Handover};
+
+    pub(crate) static WIFI: LazyLock<IfName> = LazyLock::new(|| "en0".try_into().unwrap());
+    pub(crate) static ENX: LazyLock<IfName> = LazyLock::new(|| "en7".try_into().unwrap());
+    pub(crate) static LLADDR: LazyLock<LLAddr> =
+        LazyLock::new(|| "02:11:22:33:44:55".parse().unwrap());
+    pub(crate) static ALT_LLADDR: LazyLock<LLAddr> =
+        LazyLock::new(|| "02:aa:bb:cc:dd:ee".parse().unwrap());
+    pub(crate) static ENX_LLADDR: LazyLock<LLAddr> =
+        LazyLock::new(|| "00:e0:4c:68:00:01".parse().unwrap());
+
+    pub(crate) const EBUSY: c_int = 16;
+
+    /// Wi-Fi holding the shared address, and en7, at index 2, its own.
+    pub(crate) fn kernel() -> FakeKernel {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &LLADDR);
+        kernel.plug(&ENX, &ENX_LLADDR);
+        kernel
+    }
+
+    pub(crate) fn handover(kernel: &FakeKernel) -> Handover {
+        let alternate = Alternate::Fixed(*ALT_LLADDR);
+        // The fake kernel applies changes at once, there is nothing to wait
+        // for.
+        Handover::new(kernel.clone(), *WIFI, *LLADDR, alternate).with_retry(Retry::NONE)
+    }
+
+    pub(crate) fn kind(error: &(dyn std::error::Error + 'static)) -> Option<ErrorKind> {
+        error.downcast_ref().map(Error::kind)
+    }
+
+    #[test]
+    fn test_alternate_from_str() -> Result<()> {
+        assert_eq!("random".parse::<Alternate>()?, Alternate::Random);
+        assert_eq!(
+            "02:aa:bb:cc:dd:ee".parse::<Alternate>()?,
+            Alternate::Fixed(*ALT_LLADDR)
+        );
+        assert!("02:aa".parse::<Alternate>().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_lladdr_is_local_unicast() -> Result<()> {
+        for _ in 0..32 {
+            let lladdr = random_lladdr()?;
+            assert_eq!(lladdr[0] & 0x03, 0x02);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_to_wired() -> Result<()> {
+        let kernel = kernel();
+        let mut handover = handover(&kernel);
+
+        handover.switch_to_wired(2, &ENX, &ENX_LLADDR)?;
+
+        let expected_calls = vec![
+            Call::GetLLAddr(*WIFI),
+            Call::SetLLAddr(*WIFI, *ALT_LLADDR),
+            Call::GetLLAddr(*WIFI),
+            Call::SetLLAddr(*ENX, *LLADDR),
+            Call::GetLLAddr(*ENX),
+        ];
+        let expected = HashMap::from([(*WIFI, *ALT_LLADDR), (*ENX, *LLADDR)]);
+
+        assert_eq!(kernel.calls(), expected_calls);
+        assert_eq!(handover.assignments(), &expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_to_wired_remembers_originals() -> Result<()> {
+        let path = state_path("handover_to_wired");
+        let kernel = kernel();
+        let mut handover = handover(&kernel).with_state(State::load(&path)?);
+
+        handover.switch_to_wired(2, &ENX, &ENX_LLADDR)?;
+
+        let state = State::load(&path)?;
+        assert_eq!(state.original(&WIFI), Some(*LLADDR));
+        assert_eq!(state.original(&ENX), Some(*ENX_LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_to_wired_state_error() -> Result<()> {
+        let path = state_path("handover_state_error");
+        let kernel = kernel();
+        let mut handover = handover(&kernel).with_state(State::load(&path)?);
+        // A file where the state directory should be, nothing may change.
+        std::fs::write(path.parent().unwrap(), "")?;
+
+        let error = handover.switch_to_wired(2, &ENX, &ENX_LLADDR).unwrap_err();
+
+        assert!(format!("{}", error).starts_with("State::WriteError"));
+        assert_eq!(kernel.calls(), vec![Call::GetLLAddr(*WIFI)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_to_wired_random_alternate() -> Result<()> {
+        let kernel = kernel();
+        let mut handover = Handover::new(kernel.clone(), *WIFI, *LLADDR, Alternate::Random);
+
+        handover.switch_to_wired(2, &ENX, &ENX_LLADDR)?;
+
+        let alternate = kernel.lladdr(&WIFI).unwrap();
+        assert_ne!(alternate, *LLADDR);
+        assert_eq!(alternate[0] & 0x03, 0x02);
+        assert_eq!(kernel.lladdr(&ENX), Some(*LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_to_wired_wifi_not_shared() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &ALT_LLADDR);
+        kernel.plug(&ENX, &ENX_LLADDR);
+
+        handover(&kernel).switch_to_wired(2, &ENX, &ENX_LLADDR)?;
+
+        let expected_calls = vec![
+            Call::GetLLAddr(*WIFI),
+            Call::SetLLAddr(*ENX, *LLADDR),
+            Call::GetLLAddr(*ENX),
+        ];
+
+        assert_eq!(kernel.calls(), expected_calls);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_to_wired_wifi_error() {
+        let kernel = kernel();
+        kernel.fail(Operation::SetLinkLevelAddress, &WIFI, EBUSY);
+
+        let error = handover(&kernel)
+            .switch_to_wired(2, &ENX, &ENX_LLADDR)
+            .unwrap_err();
+
+        assert_eq!(kind(error.as_ref()), Some(ErrorKind::Busy));
+        assert_eq!(kernel.lladdr(&WIFI), Some(*LLADDR));
+        assert_eq!(kernel.lladdr(&ENX), Some(*ENX_LLADDR));
+    }
+
+    #[test]
+    fn test_handover_to_wired_wired_not_applied() {
+        let kernel = kernel();
+        kernel.ignore_next_set(&ENX);
+
+        let expected_error = "NicBackend::LinkLevelAddressNotAppliedError { ifname: \"en7\", requested: \"02:11:22:33:44:55\", actual: \"00:e0:4c:68:00:01\" }";
+
+        let error = handover(&kernel)
+            .switch_to_wired(2, &ENX, &ENX_LLADDR)
+            .unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        // Rolled back, Wi-Fi holds the shared address again.
+        assert_eq!(kernel.lladdr(&WIFI), Some(*LLADDR));
+        assert_eq!(kernel.lladdr(&ENX), Some(*ENX_LLADDR));
+    }
+
+    #[test]
+    fn test_handover_to_wired_wired_rollback_error() {
+        let kernel = kernel();
+        kernel.fail(Operation::GetLinkLevelAddress, &ENX, EBUSY);
+        kernel.fail(Operation::GetLinkLevelAddress, &ENX, EBUSY);
+
+        let mut handover = handover(&kernel);
+        let error = handover.switch_to_wired(2, &ENX, &ENX_LLADDR).unwrap_err();
+
+        // The wired rollback is not known to have worked, so Wi-Fi keeps the
+        // alternate address.
+        assert_eq!(kind(error.as_ref()), Some(ErrorKind::Busy));
+        assert_eq!(kernel.lladdr(&WIFI), Some(*ALT_LLADDR));
+        assert_eq!(
+            handover.assignments(),
+            &HashMap::from([(*WIFI, *ALT_LLADDR)])
+        );
+    }
+
+    #[test]
+    fn test_handover_to_wifi() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &ALT_LLADDR);
+        let mut handover = handover(&kernel);
+        handover.assigned.insert(*ENX, *LLADDR);
+
+        handover.switch_to_wifi()?;
+
+        assert_eq!(kernel.lladdr(&WIFI), Some(*LLADDR));
+        assert_eq!(handover.assignments(), &HashMap::from([(*WIFI, *LLADDR)]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_to_wifi_remembers_original() -> Result<()> {
+        let path = state_path("handover_to_wifi");
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &ALT_LLADDR);
+
+        let mut handover = handover(&kernel).with_state(State::load(&path)?);
+
+        handover.switch_to_wifi()?;
+
+        assert_eq!(State::load(&path)?.original(&WIFI), Some(*ALT_LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_to_wifi_failed_hook() -> Result<()> {
+        let (hooks, path) = recording_hooks("handover_failed_hook");
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &ALT_LLADDR);
+        kernel.fail(Operation::SetLinkLevelAddress, &WIFI, EBUSY);
+
+        let mut handover = handover(&kernel).with_hooks(hooks);
+
+        assert!(handover.switch_to_wifi().is_err());
+        // Waits for the hooks queued.
+        drop(handover);
+
+        assert_eq!(
+            std::fs::read_to_string(&path)?,
+            [
+                "failed en0  02:11:22:33:44:55 02:aa:bb:cc:dd:ee\n",
+                "applied en0  02:aa:bb:cc:dd:ee 02:11:22:33:44:55\n",
+            ]
+            .concat()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_to_wifi_already_shared() -> Result<()> {
+        let kernel = kernel();
+
+        handover(&kernel).switch_to_wifi()?;
+
+        assert_eq!(kernel.calls(), vec![Call::GetLLAddr(*WIFI)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_to_wifi_not_applied() {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &ALT_LLADDR);
+        kernel.ignore_next_set(&WIFI);
+
+        let error = handover(&kernel).switch_to_wifi().unwrap_err();
+
+        assert_eq!(kind(error.as_ref()), Some(ErrorKind::NotApplied));
+        assert_eq!(kernel.lladdr(&WIFI), Some(*ALT_LLADDR));
+    }
+
+    #[test]
+    fn test_handover_to_wifi_retried() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &ALT_LLADDR);
+        kernel.ignore_next_set(&WIFI);
+
+        let retry = Retry {
+            attempts: 2,
+            backoff: Duration::ZERO,
+        };
+        handover(&kernel).with_retry(retry).switch_to_wifi()?;
+
+        let expected_calls = vec![
+            Call::GetLLAddr(*WIFI),
+            Call::SetLLAddr(*WIFI, *LLADDR),
+            Call::GetLLAddr(*WIFI),
+            Call::SetLLAddr(*WIFI, *LLADDR),
+            Call::GetLLAddr(*WIFI),
+        ];
+
+        assert_eq!(kernel.calls(), expected_calls);
+        assert_eq!(kernel.lladdr(&WIFI), Some(*LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_release() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+        let mut handover = handover(&kernel);
+        handover.originals.insert(*ENX, *ENX_LLADDR);
+
+        handover.release(1, &ENX)?;
+
+        assert_eq!(kernel.lladdr(&ENX), Some(*ENX_LLADDR));
+        assert_eq!(
+            handover.assignments(),
+            &HashMap::from([(*ENX, *ENX_LLADDR)])
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_release_state_original() -> Result<()> {
+        let path = state_path("handover_release");
+        let mut state = State::load(&path)?;
+        state.remember(&ENX, &ENX_LLADDR)?;
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+
+        handover(&kernel).with_state(state).release(1, &ENX)?;
+
+        assert_eq!(kernel.lladdr(&ENX), Some(*ENX_LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_handover_release_not_applied() {
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+        kernel.ignore_next_set(&ENX);
+
+        let mut handover = handover(&kernel);
+        handover.originals.insert(*ENX, *ENX_LLADDR);
+        let error = handover.release(1, &ENX).unwrap_err();
+
+        assert_eq!(kind(error.as_ref()), Some(ErrorKind::NotApplied));
+        assert_eq!(kernel.lladdr(&ENX), Some(*LLADDR));
+        assert_eq!(handover.assignments(), &HashMap::new());
+    }
+
+    #[test]
+    fn test_handover_dry_run_keeps_backend() -> Result<()> {
+        let kernel = kernel();
+        let mut handover = handover(&kernel).with_dry_run(true);
+
+        handover.switch_to_wired(2, &ENX, &ENX_LLADDR)?;
+
+        assert!(handover.is_dry_run());
+        assert_eq!(kernel.lladdr(&WIFI), Some(*LLADDR));
+        assert_eq!(kernel.lladdr(&ENX), Some(*ENX_LLADDR));
+        assert!(kernel.calls().contains(&Call::GetLLAddr(*WIFI)));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,388 @@
+use std::ffi::c_int;
+use std::fmt::Display;
+use std::io;
+use std::os::unix::process::CommandExt;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Deserializer};
+
+use net_sys::ifname::IfName;
+use net_sys::lladdr::LLAddr;
+
+use crate::Result;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Hook {
+    /// A roaming interface appeared.
+    New,
+    /// A roaming interface went away.
+    Del,
+    /// An address change was read back as applied.
+    Applied,
+    /// An address change failed.
+    Failed,
+}
+
+impl Display for Hook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hook::New => write!(f, "new"),
+            Hook::Del => write!(f, "del"),
+            Hook::Applied => write!(f, "applied"),
+            Hook::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// What a hook is told through `NICR_IFNAME`, `NICR_INDEX`, `NICR_LLADDR`
+/// and `NICR_OLD_LLADDR`, unknown values being empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct HookEnv {
+    pub(crate) ifname: IfName,
+    pub(crate) index: Option<c_int>,
+    pub(crate) lladdr: LLAddr,
+    pub(crate) old_lladdr: Option<LLAddr>,
+}
+
+impl HookEnv {
+    fn vars(&self) -> [(&'static str, String); 4] {
+        let index = self.index.map_or(String::new(), |index| index.to_string());
+        let old_lladdr = self.old_lladdr.map_or(String::new(), |old| old.to_string());
+        [
+            ("NICR_IFNAME", self.ifname.to_string()),
+            ("NICR_INDEX", index),
+            ("NICR_LLADDR", self.lladdr.to_string()),
+            ("NICR_OLD_LLADDR", old_lladdr),
+        ]
+    }
+}
+
+/// Shell commands run on roaming events, one at a time and killed along with
+/// their children after `timeout`, e.g.
+///
+/// ```toml
+/// [hooks]
+/// applied = "networkctl renew $NICR_IFNAME"
+/// timeout = 30
+/// ```
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct Hooks {
+    #[serde(default)]
+    pub(crate) new: Option<String>,
+    #[serde(default)]
+    pub(crate) del: Option<String>,
+    #[serde(default)]
+    pub(crate) applied: Option<String>,
+    #[serde(default)]
+    pub(crate) failed: Option<String>,
+    #[serde(default = "default_timeout", deserialize_with = "seconds")]
+    pub(crate) timeout: Duration,
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        Self {
+            new: None,
+            del: None,
+            applied: None,
+            failed: None,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl Hooks {
+    fn is_empty(&self) -> bool {
+        self.new.is_none() && self.del.is_none() && self.applied.is_none() && self.failed.is_none()
+    }
+
+    fn command(&self, hook: Hook) -> Option<&str> {
+        match hook {
+            Hook::New => self.new.as_deref(),
+            Hook::Del => self.del.as_deref(),
+            Hook::Applied => self.applied.as_deref(),
+            Hook::Failed => self.failed.as_deref(),
+        }
+    }
+
+    /// Runs the command of `hook`, if any, and logs how it ended. A dry run
+    /// only logs the command.
+    pub(crate) fn run(&self, hook: Hook, env: &HookEnv, dry_run: bool) {
+        let Some(command) = self.command(hook) else {
+            return;
+        };
+
+        if dry_run {
+            log::info!("dry-run: hook {hook} for {}: {command}", env.ifname);
+            return;
+        }
+
+        match self.execute(command, env) {
+            Ok(Some(status)) if status.success() => {
+                log::info!("hook {hook} for {}: {status}", env.ifname);
+            }
+            Ok(Some(status)) => {
+                log::error!("hook {hook} for {}: {status}", env.ifname);
+            }
+            Ok(None) => log::error!(
+                "hook {hook} for {}: killed after {}s",
+                env.ifname,
+                self.timeout.as_secs_f64()
+            ),
+            Err(err) => log::error!("hook {hook} for {}: {err}", env.ifname),
+        }
+    }
+
+    /// Exit status of `command`, `None` if it was killed on timeout. It runs
+    /// in a process group of its own, killed as a whole so that nothing it
+    /// started outlives it.
+    fn execute(&self, command: &str, env: &HookEnv) -> Result<Option<ExitStatus>> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .envs(env.vars())
+            .stdin(Stdio::null())
+            .process_group(0)
+            .spawn()?;
+
+        let deadline = Instant::now() + self.timeout;
+        loop {
+            if let Some(status) = child.try_wait()? {
+                return Ok(Some(status));
+            }
+            if Instant::now() >= deadline {
+                // The group has the id of its leader, still unreaped.
+                if unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) } < 0 {
+                    return Err(io::Error::last_os_error().into());
+                }
+                child.wait()?;
+                return Ok(None);
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+/// Runs `Hooks` on a thread of its own, in the order they are queued, so
+/// that roaming goes on while they run. Dropping it waits for the hooks
+/// queued already.
+#[derive(Debug, Default)]
+pub(crate) struct HookQueue {
+    sender: Option<Sender<(Hook, HookEnv, bool)>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl HookQueue {
+    /// Queue of `hooks`, with no thread if there is no command to run.
+    pub(crate) fn new(hooks: Hooks) -> Self {
+        if hooks.is_empty() {
+            return Self::default();
+        }
+
+        let (sender, receiver) = mpsc::channel::<(Hook, HookEnv, bool)>();
+        let worker = thread::spawn(move || {
+            for (hook, env, dry_run) in receiver {
+                hooks.run(hook, &env, dry_run);
+            }
+        });
+        Self {
+            sender: Some(sender),
+            worker: Some(worker),
+        }
+    }
+
+    /// Queues `hook`, only to be logged in a dry run.
+    pub(crate) fn push(&self, hook: Hook, env: &HookEnv, dry_run: bool) {
+        if let Some(sender) = &self.sender {
+            // Only fails if the worker panicked.
+            let _ = sender.send((hook, env.clone(), dry_run));
+        }
+    }
+}
+
+impl Drop for HookQueue {
+    fn drop(&mut self) {
+        self.sender = None;
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+fn default_timeout() -> Duration {
+    DEFAULT_TIMEOUT
+}
+
+fn seconds<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Duration, D::Error> {
+    Ok(Duration::from_secs(u64::deserialize(deserializer)?))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use crate::Result;
+
+    use crate::handover::tests::{ENX, ENX_LLADDR, LLADDR};
+    use crate::testing::test_path;
+
+    use super::{Hook, HookEnv, HookQueue, Hooks};
+
+    /// Hooks writing what they are told into a file unique to the calling
+    /// test.
+    pub(crate) fn recording_hooks(test: &str) -> (Hooks, PathBuf) {
+        let path = test_path(test, "env");
+        let record = format!(
+            "echo \"$NICR_IFNAME $NICR_INDEX $NICR_LLADDR $NICR_OLD_LLADDR\" >> {}",
+            path.display()
+        );
+        let hooks = Hooks {
+            new: Some(format!("printf 'new ' >> {}; {record}", path.display())),
+            del: Some(format!("printf 'del ' >> {}; {record}", path.display())),
+            applied: Some(format!("printf 'applied ' >> {}; {record}", path.display())),
+            failed: Some(format!("printf 'failed ' >> {}; {record}", path.display())),
+            ..Default::default()
+        };
+        (hooks, path)
+    }
+
+    fn env() -> HookEnv {
+        HookEnv {
+            ifname: *ENX,
+            index: Some(7),
+            lladdr: *LLADDR,
+            old_lladdr: Some(*ENX_LLADDR),
+        }
+    }
+
+    #[test]
+    fn test_hooks_run_env() -> Result<()> {
+        let (hooks, path) = recording_hooks("run_env");
+
+        hooks.run(Hook::Applied, &env(), false);
+        hooks.run(
+            Hook::Del,
+            &HookEnv {
+                index: None,
+                old_lladdr: None,
+                ..env()
+            },
+            false,
+        );
+
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "applied en7 7 02:11:22:33:44:55 00:e0:4c:68:00:01\ndel en7  02:11:22:33:44:55 \n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hooks_run_dry_run() {
+        let (hooks, path) = recording_hooks("run_dry_run");
+
+        hooks.run(Hook::New, &env(), true);
+
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_hooks_run_none() {
+        Hooks::default().run(Hook::Failed, &env(), false);
+    }
+
+    #[test]
+    fn test_hooks_execute_exit_status() -> Result<()> {
+        let status = Hooks::default().execute("exit 3", &env())?;
+
+        assert_eq!(status.and_then(|status| status.code()), Some(3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hooks_execute_timeout() -> Result<()> {
+        let hooks = Hooks {
+            timeout: Duration::from_millis(100),
+            ..Default::default()
+        };
+        let start = Instant::now();
+
+        let status = hooks.execute("sleep 5", &env())?;
+
+        assert_eq!(status, None);
+        assert!(start.elapsed() < Duration::from_secs(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hooks_execute_timeout_kills_group() -> Result<()> {
+        let path = test_path("execute_timeout_kills_group", "env");
+        let hooks = Hooks {
+            timeout: Duration::from_millis(100),
+            ..Default::default()
+        };
+
+        let command = format!("(sleep 0.5; echo late > {}) & wait", path.display());
+        let status = hooks.execute(&command, &env())?;
+        thread::sleep(Duration::from_secs(1));
+
+        assert_eq!(status, None);
+        assert!(!path.exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hook_queue_does_not_wait() -> Result<()> {
+        let (hooks, path) = recording_hooks("queue_does_not_wait");
+        let hooks = Hooks {
+            applied: Some(format!("sleep 0.5; {}", hooks.applied.unwrap())),
+            ..hooks
+        };
+        let start = Instant::now();
+
+        let queue = HookQueue::new(hooks);
+        queue.push(Hook::Applied, &env(), false);
+        queue.push(Hook::Failed, &env(), false);
+
+        assert!(start.elapsed() < Duration::from_millis(500));
+
+        drop(queue);
+
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            [
+                "applied en7 7 02:11:22:33:44:55 00:e0:4c:68:00:01\n",
+                "failed en7 7 02:11:22:33:44:55 00:e0:4c:68:00:01\n",
+            ]
+            .concat()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hooks_deserialize() -> Result<()> {
+        let hooks: Hooks = toml::from_str("applied = \"true\"\ntimeout = 3\n")?;
+
+        assert_eq!(hooks.applied.as_deref(), Some("true"));
+        assert_eq!(hooks.new, None);
+        assert_eq!(hooks.timeout, Duration::from_secs(3));
+        assert_eq!(toml::from_str::<Hooks>("")?, Hooks::default());
+
+        Ok(())
+    }
+}
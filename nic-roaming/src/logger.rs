@@ -0,0 +1,423 @@
+use std::fmt::{Debug, Display};
+use std::io::Write;
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use clap::ValueEnum;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::Result;
+
+#[cfg(not(target_os = "macos"))]
+pub(crate) const SYSLOG_SOCKET: &str = "/dev/log";
+#[cfg(target_os = "macos")]
+pub(crate) const SYSLOG_SOCKET: &str = "/var/run/syslog";
+pub(crate) const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+const IDENTIFIER: &str = "nicr";
+/// LOG_DAEMON, from syslog(3).
+const FACILITY: u8 = 3;
+
+#[derive(Clone, PartialEq, Eq)]
+enum Error {
+    Connect(PathBuf, String),
+    Filter(String),
+    SetLogger(String),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Connect(path, error) => f
+                .debug_struct("Logger::ConnectError")
+                .field("path", path)
+                .field("error", error)
+                .finish(),
+            Error::Filter(directive) => f
+                .debug_struct("Logger::FilterError")
+                .field("directive", directive)
+                .finish(),
+            Error::SetLogger(error) => f
+                .debug_struct("Logger::SetLoggerError")
+                .field("error", error)
+                .finish(),
+        }
+    }
+}
+
+/// Where log messages go.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Output {
+    /// Standard error
+    #[default]
+    Stderr,
+    /// The local syslog daemon, through /dev/log (/var/run/syslog on macOS)
+    Syslog,
+    /// The systemd journal, through its native protocol
+    Journald,
+}
+
+/// Most verbose level per target, the longest matching target prefix
+/// winning over `default`, e.g. `info,net_sys=debug`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Filter {
+    default: LevelFilter,
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Default for Filter {
+    fn default() -> Self {
+        Self {
+            default: LevelFilter::Info,
+            targets: Vec::new(),
+        }
+    }
+}
+
+impl FromStr for Filter {
+    type Err = Box<dyn std::error::Error>;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let mut filter = Filter::default();
+        for directive in value.split(',').filter(|directive| !directive.is_empty()) {
+            let error = || Error::Filter(directive.to_string());
+            match directive.split_once('=') {
+                Some((target, level)) if !target.is_empty() => {
+                    let level = level.parse().map_err(|_| error())?;
+                    filter.targets.push((target.to_string(), level));
+                }
+                Some(_) => return Err(error().into()),
+                None => filter.default = directive.parse().map_err(|_| error())?,
+            }
+        }
+
+        Ok(filter)
+    }
+}
+
+impl Filter {
+    fn level(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .filter(|(prefix, _)| {
+                target == prefix
+                    || target
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default, |(_, level)| *level)
+    }
+
+    fn max(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+#[derive(Debug)]
+enum Sink {
+    Stderr,
+    Syslog(UnixDatagram),
+    Journald(UnixDatagram),
+}
+
+/// Backend of the `log` facade for nicr and net-sys.
+#[derive(Debug)]
+pub(crate) struct Logger {
+    filter: Filter,
+    sink: Sink,
+}
+
+impl Logger {
+    pub(crate) fn new(output: Output, filter: Filter) -> Result<Self> {
+        let sink = match output {
+            Output::Stderr => Sink::Stderr,
+            Output::Syslog => Sink::Syslog(connect(Path::new(SYSLOG_SOCKET))?),
+            Output::Journald => Sink::Journald(connect(Path::new(JOURNAL_SOCKET))?),
+        };
+
+        Ok(Self { filter, sink })
+    }
+
+    /// Installs the logger for the whole process.
+    pub(crate) fn init(self) -> Result<()> {
+        let max = self.filter.max();
+        log::set_boxed_logger(Box::new(self)).map_err(|err| Error::SetLogger(err.to_string()))?;
+        log::set_max_level(max);
+
+        Ok(())
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        // There is nowhere left to report a message that could not be sent.
+        let _ = match &self.sink {
+            Sink::Stderr => writeln!(std::io::stderr(), "{}", stderr_line(record)),
+            Sink::Syslog(socket) => socket.send(syslog_line(record).as_bytes()).map(|_| ()),
+            Sink::Journald(socket) => socket.send(&journal_entry(record)).map(|_| ()),
+        };
+    }
+
+    fn flush(&self) {}
+}
+
+fn connect(path: &Path) -> Result<UnixDatagram> {
+    let error = |error: std::io::Error| Error::Connect(path.into(), error.to_string());
+
+    let socket = UnixDatagram::unbound().map_err(error)?;
+    socket.connect(path).map_err(error)?;
+
+    Ok(socket)
+}
+
+/// Errors and warnings are prefixed like the messages nicr always printed,
+/// debugging messages name their target.
+fn stderr_line(record: &Record) -> String {
+    match record.level() {
+        Level::Error => format!("Error: {}", record.args()),
+        Level::Warn => format!("Warning: {}", record.args()),
+        Level::Info => format!("{}", record.args()),
+        Level::Debug | Level::Trace => format!("{}: {}", record.target(), record.args()),
+    }
+}
+
+/// Severity of syslog(3) for `level`.
+fn severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// RFC 3164 message as accepted on /dev/log, e.g.
+/// `<30>nicr[1234]: handover: en7 00:e0:4c:68:00:01 -> 02:11:22:33:44:55`.
+fn syslog_line(record: &Record) -> String {
+    format!(
+        "<{}>{IDENTIFIER}[{}]: {}",
+        FACILITY * 8 + severity(record.level()),
+        std::process::id(),
+        record.args()
+    )
+}
+
+/// Entry of the journal native protocol, see systemd-journald.service(8).
+fn journal_entry(record: &Record) -> Vec<u8> {
+    let mut entry = Vec::new();
+    journal_field(&mut entry, "MESSAGE", &record.args().to_string());
+    journal_field(
+        &mut entry,
+        "PRIORITY",
+        &severity(record.level()).to_string(),
+    );
+    journal_field(&mut entry, "SYSLOG_IDENTIFIER", IDENTIFIER);
+    journal_field(&mut entry, "SYSLOG_PID", &std::process::id().to_string());
+    journal_field(&mut entry, "TARGET", record.target());
+    if let Some(file) = record.file() {
+        journal_field(&mut entry, "CODE_FILE", file);
+    }
+    if let Some(line) = record.line() {
+        journal_field(&mut entry, "CODE_LINE", &line.to_string());
+    }
+    entry
+}
+
+/// `KEY=value`, or the key followed by the length of the value when the
+/// value spans several lines.
+fn journal_field(entry: &mut Vec<u8>, key: &str, value: &str) {
+    entry.extend_from_slice(key.as_bytes());
+    match value.contains('\n') {
+        true => {
+            entry.push(b'\n');
+            entry.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        }
+        false => entry.push(b'='),
+    }
+    entry.extend_from_slice(value.as_bytes());
+    entry.push(b'\n');
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixDatagram;
+    use std::path::PathBuf;
+
+    use log::{Level, LevelFilter, Log, Record};
+
+    use crate::Result;
+
+    use crate::testing::test_path;
+
+    use super::{connect, journal_entry, stderr_line, syslog_line, Filter, Logger, Sink};
+
+    /// Datagram socket standing in for /dev/log or the journal, at a path
+    /// unique to the calling test.
+    fn listen(test: &str) -> Result<(UnixDatagram, PathBuf)> {
+        let path = test_path(test, "sock");
+        Ok((UnixDatagram::bind(&path)?, path))
+    }
+
+    fn receive(socket: &UnixDatagram) -> Result<Vec<u8>> {
+        let mut buf = [0; 1024];
+        let len = socket.recv(&mut buf)?;
+        Ok(buf[..len].to_vec())
+    }
+
+    #[test]
+    fn test_filter_parse() -> Result<()> {
+        let filter: Filter = "warn,net_sys=debug,net_sys::nic=off".parse()?;
+
+        assert_eq!(filter.level("nicr::roam"), LevelFilter::Warn);
+        assert_eq!(filter.level("net_sys"), LevelFilter::Debug);
+        assert_eq!(filter.level("net_sys::socket"), LevelFilter::Debug);
+        assert_eq!(filter.level("net_sys::nic"), LevelFilter::Off);
+        assert_eq!(filter.level("net_sys_other"), LevelFilter::Warn);
+        assert_eq!(filter.max(), LevelFilter::Debug);
+        assert_eq!("".parse::<Filter>()?, Filter::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_parse_error() {
+        let expected_error = "Logger::FilterError { directive: \"net_sys=loud\" }";
+
+        let error = "info,net_sys=loud".parse::<Filter>().unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert!("=debug".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn test_stderr_line() {
+        let line = |level| {
+            stderr_line(
+                &Record::builder()
+                    .level(level)
+                    .target("net_sys::nic")
+                    .args(format_args!("message"))
+                    .build(),
+            )
+        };
+
+        assert_eq!(line(Level::Error), "Error: message");
+        assert_eq!(line(Level::Warn), "Warning: message");
+        assert_eq!(line(Level::Info), "message");
+        assert_eq!(line(Level::Debug), "net_sys::nic: message");
+    }
+
+    #[test]
+    fn test_syslog_line() {
+        let record = Record::builder()
+            .level(Level::Warn)
+            .args(format_args!("RtmInvalid(42)"))
+            .build();
+
+        let expected = format!("<28>nicr[{}]: RtmInvalid(42)", std::process::id());
+
+        assert_eq!(syslog_line(&record), expected);
+    }
+
+    #[test]
+    fn test_journal_entry() {
+        let record = Record::builder()
+            .level(Level::Error)
+            .target("net_sys::socket")
+            .args(format_args!("two\nlines"))
+            .build();
+
+        let expected = [
+            b"MESSAGE\n".as_slice(),
+            &9u64.to_le_bytes(),
+            b"two\nlines\n",
+            b"PRIORITY=3\n",
+            b"SYSLOG_IDENTIFIER=nicr\n",
+            format!("SYSLOG_PID={}\n", std::process::id()).as_bytes(),
+            b"TARGET=net_sys::socket\n",
+        ]
+        .concat();
+
+        assert_eq!(journal_entry(&record), expected);
+    }
+
+    #[test]
+    fn test_logger_syslog() -> Result<()> {
+        let (listener, path) = listen("logger_syslog")?;
+        let logger = Logger {
+            filter: "info,net_sys=error".parse()?,
+            sink: Sink::Syslog(connect(&path)?),
+        };
+
+        logger.log(
+            &Record::builder()
+                .level(Level::Warn)
+                .target("net_sys::nic")
+                .args(format_args!("filtered"))
+                .build(),
+        );
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .target("nicr::handover")
+                .args(format_args!("handover"))
+                .build(),
+        );
+
+        let expected = format!("<30>nicr[{}]: handover", std::process::id());
+
+        assert_eq!(receive(&listener)?, expected.as_bytes());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_logger_journald() -> Result<()> {
+        let (listener, path) = listen("logger_journald")?;
+        let logger = Logger {
+            filter: Filter::default(),
+            sink: Sink::Journald(connect(&path)?),
+        };
+
+        logger.log(
+            &Record::builder()
+                .level(Level::Info)
+                .args(format_args!("restore"))
+                .build(),
+        );
+
+        assert!(receive(&listener)?.starts_with(b"MESSAGE=restore\nPRIORITY=6\n"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_connect_error() {
+        let path = test_path("connect_error", "sock");
+
+        let error = connect(&path).unwrap_err();
+
+        assert!(format!("{}", error).starts_with("Logger::ConnectError { path: "));
+    }
+}
@@ -1,30 +1,128 @@
 use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 
-use net_sys::ifname::IfName;
-use net_sys::lladdr::LLAddr;
+use clap::Parser;
+
+use net_sys::backend::OsBackend;
+use net_sys::debounce::debounce;
+use net_sys::dry_run::Nic;
 use net_sys::nic;
-use net_sys::nic::NicEvent::{NicDel, NicNew, NicNoop};
+use net_sys::nic::NicEvent::{self, NicDel, NicDown, NicNew, NicNoop, NicUp};
+use net_sys::retry::Retry;
+use net_sys::ErrorKind;
+
+use cli::{Cli, Command, Exit, RoamArgs};
+use config::Config;
+use control::Control;
+use handover::Handover;
+use logger::Logger;
+use notify::Notify;
+use output::{Format, LLAddrRecord, Timestamped};
+use roam::Roam;
+use state::State;
+
+mod cli;
+mod config;
+mod control;
+mod handover;
+mod hooks;
+mod logger;
+mod notify;
+mod output;
+mod pattern;
+mod policy;
+mod restore;
+mod roam;
+mod state;
+#[cfg(test)]
+mod testing;
+
+/// Result of `nicr`, failing with a `net_sys::Error` as well as with the
+/// errors of its own modules, e.g. the config file or the state file.
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
 
 #[cfg(not(tarpaulin_include))]
-fn main() -> Result<(), Box<dyn Error>> {
-    let action = std::env::args().nth(1);
-    let ifname = std::env::args().nth(2);
-    let lladdr = std::env::args().nth(3);
-
-    match action.ok_or("Missing action param: [get | set]")?.as_str() {
-        "get" => {
-            let ifname: IfName = ifname.ok_or("Missing ifname param")?.try_into()?;
+fn main() -> ExitCode {
+    let cli = match Cli::try_parse() {
+        Ok(cli) => cli,
+        Err(err) => {
+            let _ = err.print();
+            return match err.use_stderr() {
+                true => Exit::Usage.into(),
+                false => ExitCode::SUCCESS,
+            };
+        }
+    };
+
+    if let Err(err) = Logger::new(cli.log, cli.log_level).and_then(Logger::init) {
+        eprintln!("Error: {err}");
+        return Exit::of(err.as_ref()).into();
+    }
+
+    match run(cli.command, cli.format, &cli.state, &cli.control) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            log::error!("{err}");
+            Exit::of(err.as_ref()).into()
+        }
+    }
+}
+
+#[cfg(not(tarpaulin_include))]
+fn run(command: Command, format: Format, state: &Path, control: &Path) -> Result<()> {
+    match command {
+        Command::List if format == Format::Json => {
+            println!("{}", serde_json::to_string(&nic::list()?)?);
+        }
+        Command::List => {
+            for nic in nic::list()? {
+                let lladdr = nic
+                    .lladdr
+                    .map_or("-".to_string(), |lladdr| lladdr.to_string());
+                println!(
+                    "{}\t{}\t{}\t{}\tflags={:#x}\tmtu={}",
+                    nic.index, nic.ifname, lladdr, nic.nic_type, nic.flags, nic.mtu
+                );
+            }
+        }
+        Command::Get { ifname } => {
             let lladdr = nic::get_lladdr(&ifname)?;
-            eprintln!("nic::get_lladdr({ifname}) -> {lladdr}");
+            match format {
+                Format::Json => {
+                    let record = LLAddrRecord { ifname, lladdr };
+                    println!("{}", serde_json::to_string(&record)?);
+                }
+                Format::Text => eprintln!("nic::get_lladdr({ifname}) -> {lladdr}"),
+            }
         }
-        "set" => {
-            let ifname: IfName = ifname.ok_or("Missing ifname param")?.try_into()?;
-            let lladdr: LLAddr = lladdr.ok_or("Missing lladdr param")?.parse()?;
-            nic::set_lladdr(&ifname, &lladdr)?;
-            eprintln!("nic::set_lladdr({ifname}, {lladdr})");
+        Command::Set {
+            ifname,
+            lladdr,
+            dry_run,
+        } => {
+            let nic = Nic::new(dry_run);
+            if !nic.is_dry_run() {
+                State::load(state)?.remember(&ifname, &nic.get_lladdr(&ifname)?)?;
+            }
+            nic.set_lladdr_verified(&ifname, &lladdr, &Retry::default(), "nicr set")?;
+            if !nic.is_dry_run() {
+                eprintln!("nic::set_lladdr({ifname}, {lladdr})");
+            }
         }
-        "monitor" => {
-            for event in nic::monitor()? {
+        Command::Monitor { debounce: quiet } if format == Format::Json => {
+            for event in monitor(quiet)? {
+                match event? {
+                    NicNoop => (),
+                    event => println!("{}", serde_json::to_string(&Timestamped::now(event))?),
+                }
+            }
+        }
+        Command::Monitor { debounce: quiet } => {
+            for event in monitor(quiet)? {
                 match event? {
                     NicNew((link, ifname, lladdr)) => {
                         eprintln!("NicNew -> {link}#{ifname}#{lladdr}");
@@ -32,14 +130,105 @@ fn main() -> Result<(), Box<dyn Error>> {
                     NicDel((link, ifname, lladdr)) => {
                         eprintln!("NicDel -> {link}#{ifname}#{lladdr}");
                     }
-                    NicNoop => (),
+                    NicUp((link, ifname)) => eprintln!("NicUp -> {link}#{ifname}"),
+                    NicDown((link, ifname)) => eprintln!("NicDown -> {link}#{ifname}"),
+                    _ => (),
                 }
             }
         }
-        invalid => {
-            return Err(format!("Invalid action: {invalid}").into());
+        Command::Roam(args) => roam(&args, state, control)?,
+        Command::Restore {
+            ifname: Some(ifname),
+            config,
+            ..
+        } => {
+            let retry = load_config(config)?.retry();
+            restore::restore(&OsBackend, &mut State::load(state)?, &ifname, &retry)?;
+        }
+        Command::Restore {
+            ifname: None,
+            config,
+            ..
+        } => {
+            let retry = load_config(config)?.retry();
+            restore::restore_all(&OsBackend, &mut State::load(state)?, &retry)?
+        }
+        Command::Ctl { request } => {
+            let response = control::request(control, request)?;
+            match format {
+                Format::Json => println!("{}", serde_json::to_string(&response)?),
+                Format::Text => println!("{}", serde_json::to_string_pretty(&response)?),
+            }
         }
     }
 
     Ok(())
 }
+
+/// Debounced events of the monitor, a malformed message being logged and
+/// skipped.
+#[cfg(not(tarpaulin_include))]
+fn monitor(quiet: u64) -> net_sys::Result<impl Iterator<Item = net_sys::Result<NicEvent>>> {
+    let events = debounce(nic::monitor()?, Duration::from_millis(quiet));
+    Ok(events.filter(|event| match event {
+        Err(err) if err.kind() == ErrorKind::Decode => {
+            log::warn!("{err}");
+            false
+        }
+        _ => true,
+    }))
+}
+
+#[cfg(not(tarpaulin_include))]
+/// The file given, or else the first one found, defaults without any.
+fn load_config(path: Option<PathBuf>) -> Result<Config> {
+    match path.or_else(Config::find) {
+        Some(path) => Config::load(&path),
+        None => Ok(Config::default()),
+    }
+}
+
+fn roam(args: &RoamArgs, state: &Path, control: &Path) -> Result<()> {
+    let config = load_config(args.config.clone())?;
+    let settings = config.settings(args.profile.as_deref(), args.options())?;
+
+    let rules: Vec<String> = settings.rules.iter().map(ToString::to_string).collect();
+    log::info!(
+        "nicr roam: {} on {}, {} otherwise",
+        settings.lladdr,
+        rules.join("; "),
+        settings.wifi
+    );
+
+    let mut handover = Handover::new(
+        OsBackend,
+        settings.wifi,
+        settings.lladdr,
+        settings.alternate,
+    )
+    .with_dry_run(args.dry_run)
+    .with_hooks(settings.hooks)
+    .with_retry(settings.retry);
+    // A dry run leaves the state file alone like everything else.
+    if !args.dry_run {
+        handover = handover.with_state(State::load(state)?);
+    }
+    let control = Control::bind(control)?;
+    let roam = Roam::new(handover, settings.rules)
+        .with_debounce(settings.debounce)
+        .with_notify(Notify::from_env()?);
+    let roam = Arc::new(Mutex::new(roam));
+    {
+        let roam = Arc::clone(&roam);
+        thread::spawn(move || control.serve(&roam));
+    }
+    if let Some(interval) = notify::watchdog_interval() {
+        let roam = Arc::clone(&roam);
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            Roam::watchdog(&roam);
+        });
+    }
+
+    Roam::run(&roam)
+}
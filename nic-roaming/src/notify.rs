@@ -0,0 +1,204 @@
+use std::fmt::{Debug, Display};
+use std::os::unix::net::UnixDatagram;
+use std::time::Duration;
+
+use crate::Result;
+
+#[derive(Clone, PartialEq, Eq)]
+enum Error {
+    Connect(String, String),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Connect(socket, error) => f
+                .debug_struct("Notify::ConnectError")
+                .field("socket", socket)
+                .field("error", error)
+                .finish(),
+        }
+    }
+}
+
+/// Client of the sd_notify(3) protocol, doing nothing unless started by
+/// systemd with `$NOTIFY_SOCKET` set.
+#[derive(Debug, Default)]
+pub(crate) struct Notify {
+    socket: Option<UnixDatagram>,
+}
+
+impl Notify {
+    pub(crate) fn from_env() -> Result<Self> {
+        match std::env::var("NOTIFY_SOCKET") {
+            Ok(socket) => Self::connect(&socket),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Connects to `socket`, a path or, on Linux, an abstract name starting
+    /// with `@`.
+    pub(crate) fn connect(socket: &str) -> Result<Self> {
+        let error = |error: std::io::Error| Error::Connect(socket.into(), error.to_string());
+
+        let datagram = UnixDatagram::unbound().map_err(error)?;
+        match socket.strip_prefix('@') {
+            #[cfg(target_os = "linux")]
+            Some(name) => {
+                use std::os::linux::net::SocketAddrExt;
+                use std::os::unix::net::SocketAddr;
+
+                let addr = SocketAddr::from_abstract_name(name).map_err(error)?;
+                datagram.connect_addr(&addr).map_err(error)?;
+            }
+            _ => datagram.connect(socket).map_err(error)?,
+        }
+
+        Ok(Self {
+            socket: Some(datagram),
+        })
+    }
+
+    /// Startup is over, with `status` as the first status line.
+    pub(crate) fn ready(&self, status: &str) {
+        self.send(&format!("READY=1\nSTATUS={status}"));
+    }
+
+    pub(crate) fn status(&self, status: &str) {
+        self.send(&format!("STATUS={status}"));
+    }
+
+    pub(crate) fn watchdog(&self) {
+        self.send("WATCHDOG=1");
+    }
+
+    /// Failures are only logged, systemd acts on the missing messages.
+    fn send(&self, state: &str) {
+        if let Some(socket) = &self.socket {
+            if let Err(err) = socket.send(state.as_bytes()) {
+                log::warn!("sd_notify {state:?}: {err}");
+            }
+        }
+    }
+}
+
+/// How often to ping the watchdog, half the `WATCHDOG_USEC` systemd expects
+/// pings within, if the watchdog is enabled for this process.
+pub(crate) fn watchdog_interval() -> Option<Duration> {
+    let usec = std::env::var("WATCHDOG_USEC").ok();
+    let pid = std::env::var("WATCHDOG_PID").ok();
+    interval(usec.as_deref(), pid.as_deref(), std::process::id())
+}
+
+fn interval(usec: Option<&str>, pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    if pid.is_some_and(|pid| pid.parse() != Ok(own_pid)) {
+        return None;
+    }
+
+    match usec?.parse() {
+        Ok(0) | Err(_) => None,
+        Ok(usec) => Some(Duration::from_micros(usec) / 2),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::os::unix::net::UnixDatagram;
+    use std::time::Duration;
+
+    use crate::Result;
+
+    use crate::testing::test_path;
+
+    use super::{interval, Notify};
+
+    /// Datagram socket standing in for systemd's, at a path unique to the
+    /// calling test, and a `Notify` connected to it.
+    pub(crate) fn notify_socket(test: &str) -> Result<(UnixDatagram, Notify)> {
+        let path = test_path(test, "notify");
+        let socket = UnixDatagram::bind(&path)?;
+        socket.set_read_timeout(Some(Duration::from_secs(1)))?;
+        let notify = Notify::connect(path.to_str().unwrap())?;
+        Ok((socket, notify))
+    }
+
+    pub(crate) fn receive(socket: &UnixDatagram) -> Result<String> {
+        let mut buf = [0; 1024];
+        let len = socket.recv(&mut buf)?;
+        Ok(String::from_utf8(buf[..len].to_vec())?)
+    }
+
+    #[test]
+    fn test_notify_messages() -> Result<()> {
+        let (socket, notify) = notify_socket("messages")?;
+
+        notify.ready("02:11:22:33:44:55 on en0");
+        notify.status("paused");
+        notify.watchdog();
+
+        assert_eq!(
+            receive(&socket)?,
+            "READY=1\nSTATUS=02:11:22:33:44:55 on en0"
+        );
+        assert_eq!(receive(&socket)?, "STATUS=paused");
+        assert_eq!(receive(&socket)?, "WATCHDOG=1");
+
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_notify_abstract_socket() -> Result<()> {
+        use std::os::linux::net::SocketAddrExt;
+        use std::os::unix::net::SocketAddr;
+
+        let name = format!("nicr-{}-notify", std::process::id());
+        let socket = UnixDatagram::bind_addr(&SocketAddr::from_abstract_name(&name)?)?;
+
+        Notify::connect(&format!("@{name}"))?.watchdog();
+
+        assert_eq!(receive(&socket)?, "WATCHDOG=1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_notify_default_is_silent() {
+        Notify::default().ready("ignored");
+    }
+
+    #[test]
+    fn test_notify_connect_error() {
+        let expected_error = "Notify::ConnectError { socket: \"/nonexistent/notify\", error: \"No such file or directory (os error 2)\" }";
+
+        let error = Notify::connect("/nonexistent/notify").unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_watchdog_interval() {
+        let interval_of = |usec, pid| interval(usec, pid, 42);
+
+        assert_eq!(
+            interval_of(Some("30000000"), None),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(
+            interval_of(Some("30000000"), Some("42")),
+            Some(Duration::from_secs(15))
+        );
+        assert_eq!(interval_of(Some("30000000"), Some("7")), None);
+        assert_eq!(interval_of(Some("0"), None), None);
+        assert_eq!(interval_of(Some("soon"), None), None);
+        assert_eq!(interval_of(None, None), None);
+    }
+}
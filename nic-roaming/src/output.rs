@@ -0,0 +1,87 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use net_sys::ifname::IfName;
+use net_sys::lladdr::LLAddr;
+
+/// Output format of `get`, `list` and `monitor`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Format {
+    /// Human readable text
+    #[default]
+    Text,
+    /// JSON on stdout, one object per line for `monitor`
+    Json,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub(crate) struct LLAddrRecord {
+    pub(crate) ifname: IfName,
+    pub(crate) lladdr: LLAddr,
+}
+
+/// `value` along with the time it was seen, in seconds since the Unix epoch.
+#[derive(Clone, Debug, Serialize)]
+pub(crate) struct Timestamped<T> {
+    #[serde(flatten)]
+    pub(crate) value: T,
+    pub(crate) timestamp: f64,
+}
+
+impl<T> Timestamped<T> {
+    pub(crate) fn now(value: T) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0.0, |elapsed| elapsed.as_secs_f64());
+        Self { value, timestamp }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use net_sys::nic::NicEvent::NicNew;
+
+    use crate::Result;
+
+    use super::{LLAddrRecord, Timestamped};
+
+    #[test]
+    fn test_lladdr_record_serialize() -> Result<()> {
+        let record = LLAddrRecord {
+            ifname: "en0".try_into()?,
+            lladdr: "02:11:22:33:44:55".parse()?,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&record)?,
+            r#"{"ifname":"en0","lladdr":"02:11:22:33:44:55"}"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_timestamped_serialize() -> Result<()> {
+        let event = NicNew((7, "en7".try_into()?, "00:e0:4c:68:00:01".parse()?));
+        let timestamped = Timestamped {
+            value: event,
+            timestamp: 1760000000.5,
+        };
+
+        assert_eq!(
+            serde_json::to_string(&timestamped)?,
+            r#"{"event":"new","index":7,"ifname":"en7","lladdr":"00:e0:4c:68:00:01","timestamp":1760000000.5}"#
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_timestamped_now() {
+        let timestamped = Timestamped::now(());
+
+        assert!(timestamped.timestamp > 1_600_000_000.0);
+    }
+}
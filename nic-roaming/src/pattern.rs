@@ -0,0 +1,87 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Deserializer};
+
+use net_sys::ifname::IfName;
+
+/// Interface name pattern, where `*` matches any run of characters and `?`
+/// matches a single one, e.g. `en*` or `enx*`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct IfPattern(String);
+
+impl IfPattern {
+    pub(crate) fn matches(&self, ifname: &IfName) -> bool {
+        glob(self.0.as_bytes(), ifname.to_string().as_bytes())
+    }
+}
+
+fn glob(pattern: &[u8], name: &[u8]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            glob(&pattern[1..], name) || (!name.is_empty() && glob(pattern, &name[1..]))
+        }
+        (Some(b'?'), Some(_)) => glob(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) if p == n => glob(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+impl From<&str> for IfPattern {
+    fn from(value: &str) -> Self {
+        Self(value.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for IfPattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(String::deserialize(deserializer)?.as_str().into())
+    }
+}
+
+impl Display for IfPattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use net_sys::ifname::IfName;
+
+    use super::IfPattern;
+
+    fn matches(pattern: &str, ifname: &str) -> bool {
+        let ifname: IfName = ifname.try_into().unwrap();
+        IfPattern::from(pattern).matches(&ifname)
+    }
+
+    #[test]
+    fn test_pattern_exact() {
+        assert!(matches("en7", "en7"));
+        assert!(!matches("en7", "en70"));
+        assert!(!matches("en70", "en7"));
+    }
+
+    #[test]
+    fn test_pattern_star() {
+        assert!(matches("*", "en0"));
+        assert!(matches("en*", "en0"));
+        assert!(matches("en*", "enx001122334455"));
+        assert!(matches("*55", "enx001122334455"));
+        assert!(matches("e*1*5", "enx001122334455"));
+        assert!(!matches("en*", "eth0"));
+    }
+
+    #[test]
+    fn test_pattern_question_mark() {
+        assert!(matches("en?", "en7"));
+        assert!(!matches("en?", "en10"));
+        assert!(matches("eth??", "eth10"));
+    }
+
+    #[test]
+    fn test_pattern_display() {
+        assert_eq!(format!("{}", IfPattern::from("en*")), "en*");
+    }
+}
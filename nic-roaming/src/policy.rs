@@ -0,0 +1,558 @@
+use std::collections::HashMap;
+use std::ffi::c_int;
+use std::fmt::Display;
+use std::iter;
+
+use serde::Deserialize;
+
+use net_sys::ifname::IfName;
+use net_sys::lladdr::LLAddr;
+use net_sys::nic::NicType;
+
+use crate::pattern::IfPattern;
+
+/// Whether a link is up, as last reported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum LinkState {
+    Up,
+    Down,
+    /// Not reported yet, matching no `link` of a rule.
+    #[serde(skip)]
+    Unknown,
+}
+
+impl LinkState {
+    /// State of a link with the `flags` of `NicInfo`, up if IFF_UP and
+    /// IFF_RUNNING are both set.
+    pub(crate) fn of_flags(flags: u32) -> Self {
+        let up = (libc::IFF_UP | libc::IFF_RUNNING) as u32;
+        match flags & up == up {
+            true => LinkState::Up,
+            false => LinkState::Down,
+        }
+    }
+}
+
+/// The Wi-Fi interface roaming falls back on, or any other one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Kind {
+    Wifi,
+    Wired,
+}
+
+impl Display for Kind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Kind::Wifi => write!(f, "wifi"),
+            Kind::Wired => write!(f, "wired"),
+        }
+    }
+}
+
+impl Display for LinkState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LinkState::Up => write!(f, "up"),
+            LinkState::Down => write!(f, "down"),
+            LinkState::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// What the policy knows about an interface present.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Link {
+    pub(crate) index: c_int,
+    pub(crate) lladdr: LLAddr,
+    pub(crate) nic_type: NicType,
+    pub(crate) state: LinkState,
+}
+
+/// Interfaces matching one of `patterns`, and `type` and `link` if given,
+/// e.g.
+///
+/// ```toml
+/// [[rules]]
+/// patterns = ["enx*"]
+/// type = "wired"
+/// link = "up"
+/// ```
+///
+/// Only a Wi-Fi rule may leave out `patterns`, so that no veth, bridge or
+/// other virtual Ethernet interface roams unless named.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RuleRepr")]
+pub(crate) struct Rule {
+    pub(crate) patterns: Vec<IfPattern>,
+    pub(crate) kind: Option<Kind>,
+    pub(crate) link: Option<LinkState>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleRepr {
+    #[serde(default)]
+    patterns: Option<Vec<IfPattern>>,
+    #[serde(default, rename = "type")]
+    kind: Option<Kind>,
+    #[serde(default)]
+    link: Option<LinkState>,
+}
+
+impl TryFrom<RuleRepr> for Rule {
+    type Error = &'static str;
+
+    fn try_from(repr: RuleRepr) -> Result<Self, Self::Error> {
+        let patterns = match (repr.patterns, repr.kind) {
+            (Some(patterns), _) => patterns,
+            (None, Some(Kind::Wifi)) => vec!["*".into()],
+            (None, _) => {
+                return Err("missing field `patterns`, only optional with type = \"wifi\"")
+            }
+        };
+        Ok(Self {
+            patterns,
+            kind: repr.kind,
+            link: repr.link,
+        })
+    }
+}
+
+impl Rule {
+    /// Wired interfaces matching one of `patterns`, all ranking the same, as
+    /// given with `--pattern`.
+    pub(crate) fn wired(patterns: Vec<IfPattern>) -> Self {
+        Self {
+            patterns,
+            kind: Some(Kind::Wired),
+            link: None,
+        }
+    }
+
+    fn matches(&self, ifname: &IfName, kind: Option<Kind>, state: LinkState) -> bool {
+        self.matches_name(ifname, kind) && self.link.is_none_or(|link| link == state)
+    }
+
+    fn matches_name(&self, ifname: &IfName, kind: Option<Kind>) -> bool {
+        self.kind.is_none_or(|rule_kind| Some(rule_kind) == kind)
+            && self.patterns.iter().any(|pattern| pattern.matches(ifname))
+    }
+}
+
+/// E.g. `enx*,en7 type=wired link=up`.
+impl Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let patterns: Vec<String> = self.patterns.iter().map(ToString::to_string).collect();
+        write!(f, "{}", patterns.join(","))?;
+        if let Some(kind) = self.kind {
+            write!(f, " type={kind}")?;
+        }
+        if let Some(link) = self.link {
+            write!(f, " link={link}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Address an interface should have.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Target {
+    Shared,
+    /// Anything but the shared address.
+    Released,
+}
+
+/// Decides which interface holds the shared address, from the interfaces
+/// present only, so that the same links always give the same answer.
+///
+/// The interfaces matching the first rule any of them matches compete for
+/// it: the one holding it already wins, then wired interfaces over Wi-Fi,
+/// then the lowest index. Wi-Fi, present and up unless reported otherwise,
+/// holds it if no rule matches at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Policy {
+    wifi: IfName,
+    rules: Vec<Rule>,
+}
+
+impl Policy {
+    pub(crate) fn new(wifi: IfName, rules: Vec<Rule>) -> Self {
+        Self { wifi, rules }
+    }
+
+    pub(crate) fn wifi(&self) -> &IfName {
+        &self.wifi
+    }
+
+    /// Kind of `ifname`, of type `nic_type`, `None` for a link that is
+    /// neither. Wi-Fi is told apart by its name, as the kernel reports it as
+    /// Ethernet too.
+    fn kind(&self, ifname: &IfName, nic_type: NicType) -> Option<Kind> {
+        match nic_type {
+            _ if *ifname == self.wifi => Some(Kind::Wifi),
+            NicType::Ether => Some(Kind::Wired),
+            _ => None,
+        }
+    }
+
+    /// Whether a rule names the wired interface `ifname`, whatever its link
+    /// state.
+    pub(crate) fn roams(&self, ifname: &IfName, nic_type: NicType) -> bool {
+        let kind = self.kind(ifname, nic_type);
+        kind == Some(Kind::Wired)
+            && self
+                .rules
+                .iter()
+                .any(|rule| rule.matches_name(ifname, kind))
+    }
+
+    /// Interface that should hold the shared address, `holder` being the one
+    /// holding it now.
+    pub(crate) fn owner(&self, links: &HashMap<IfName, Link>, holder: &IfName) -> IfName {
+        let wifi_state = links
+            .get(&self.wifi)
+            .map_or(LinkState::Up, |link| link.state);
+        let candidates: Vec<_> = links
+            .iter()
+            .filter(|(ifname, _)| **ifname != self.wifi)
+            .map(|(ifname, link)| {
+                let kind = self.kind(ifname, link.nic_type);
+                (*ifname, kind, link.state, link.index)
+            })
+            .chain(iter::once((self.wifi, Some(Kind::Wifi), wifi_state, 0)))
+            .collect();
+
+        self.rules
+            .iter()
+            .find_map(|rule| {
+                candidates
+                    .iter()
+                    .filter(|(ifname, kind, state, _)| rule.matches(ifname, *kind, *state))
+                    .min_by_key(|(ifname, kind, _, index)| {
+                        (ifname != holder, *kind == Some(Kind::Wifi), *index)
+                    })
+                    .map(|(ifname, ..)| *ifname)
+            })
+            .unwrap_or(self.wifi)
+    }
+
+    /// Target of Wi-Fi and of every interface present, the shared address
+    /// being on `owner` only.
+    pub(crate) fn plan(
+        &self,
+        links: &HashMap<IfName, Link>,
+        owner: &IfName,
+    ) -> HashMap<IfName, Target> {
+        links
+            .keys()
+            .chain(iter::once(&self.wifi))
+            .map(|ifname| match ifname == owner {
+                true => (*ifname, Target::Shared),
+                false => (*ifname, Target::Released),
+            })
+            .collect()
+    }
+}
+
+/// Changes turning the `current` addresses into `plan`, releases first so
+/// that `lladdr` is never on two interfaces, then in name order. An
+/// interface whose address is unknown is taken as not holding `lladdr`.
+pub(crate) fn diff(
+    plan: &HashMap<IfName, Target>,
+    current: &HashMap<IfName, LLAddr>,
+    lladdr: &LLAddr,
+) -> Vec<(IfName, Target)> {
+    let holds = |ifname: &IfName| current.get(ifname) == Some(lladdr);
+
+    let mut changes: Vec<_> = plan
+        .iter()
+        .filter(|(ifname, target)| match target {
+            Target::Shared => !holds(ifname),
+            Target::Released => holds(ifname),
+        })
+        .map(|(ifname, target)| (*ifname, *target))
+        .collect();
+    changes.sort_by_key(|(ifname, target)| (*target == Target::Shared, ifname.to_string()));
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::ffi::c_int;
+    use std::sync::LazyLock;
+
+    use net_sys::ifname::IfName;
+    use net_sys::lladdr::LLAddr;
+    use net_sys::nic::NicType;
+
+    use crate::handover::tests::{ALT_LLADDR, ENX, ENX_LLADDR, LLADDR, WIFI};
+
+    use super::{diff, Kind, Link, LinkState, Policy, Rule, Target};
+
+    static DOCK: LazyLock<IfName> = LazyLock::new(|| "enx001122".try_into().unwrap());
+    static DONGLE: LazyLock<IfName> = LazyLock::new(|| "en8".try_into().unwrap());
+
+    /// Dock over any wired interface whose link is up, over Wi-Fi, over any
+    /// other wired interface.
+    fn policy() -> Policy {
+        Policy::new(
+            *WIFI,
+            vec![
+                Rule {
+                    patterns: vec!["enx*".into()],
+                    kind: None,
+                    link: None,
+                },
+                Rule {
+                    link: Some(LinkState::Up),
+                    ..Rule::wired(vec!["en*".into()])
+                },
+                Rule {
+                    patterns: vec!["*".into()],
+                    kind: Some(Kind::Wifi),
+                    link: Some(LinkState::Up),
+                },
+                Rule::wired(vec!["en*".into()]),
+            ],
+        )
+    }
+
+    fn link(index: c_int, state: LinkState) -> Link {
+        Link {
+            index,
+            lladdr: *ENX_LLADDR,
+            nic_type: NicType::Ether,
+            state,
+        }
+    }
+
+    fn links(links: &[(IfName, Link)]) -> HashMap<IfName, Link> {
+        links.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_policy_owner_wifi_without_links() {
+        assert_eq!(policy().owner(&HashMap::new(), &WIFI), *WIFI);
+        assert_eq!(
+            Policy::new(*WIFI, vec![]).owner(&HashMap::new(), &ENX),
+            *WIFI
+        );
+    }
+
+    #[test]
+    fn test_policy_owner_first_rule_wins() {
+        let links = links(&[
+            (*ENX, link(7, LinkState::Up)),
+            (*DOCK, link(9, LinkState::Up)),
+        ]);
+
+        assert_eq!(policy().owner(&links, &WIFI), *DOCK);
+        assert_eq!(policy().owner(&links, &ENX), *DOCK);
+    }
+
+    #[test]
+    fn test_policy_owner_link_state() {
+        let links = links(&[
+            (*ENX, link(7, LinkState::Down)),
+            (*DONGLE, link(8, LinkState::Up)),
+        ]);
+
+        assert_eq!(policy().owner(&links, &ENX), *DONGLE);
+
+        let down = self::links(&[(*ENX, link(7, LinkState::Down))]);
+
+        // Wi-Fi ranks above a wired interface whose link is down.
+        assert_eq!(policy().owner(&down, &ENX), *WIFI);
+
+        let wifi_down = self::links(&[
+            (*ENX, link(7, LinkState::Down)),
+            (*WIFI, link(2, LinkState::Down)),
+        ]);
+
+        assert_eq!(policy().owner(&wifi_down, &WIFI), *ENX);
+    }
+
+    #[test]
+    fn test_policy_owner_holder_then_index() {
+        let links = links(&[
+            (*ENX, link(7, LinkState::Up)),
+            (*DONGLE, link(8, LinkState::Up)),
+        ]);
+
+        assert_eq!(policy().owner(&links, &WIFI), *ENX);
+        assert_eq!(policy().owner(&links, &DONGLE), *DONGLE);
+    }
+
+    #[test]
+    fn test_policy_owner_wired_over_wifi_in_same_rule() {
+        let policy = Policy::new(
+            *WIFI,
+            vec![Rule {
+                patterns: vec!["en*".into()],
+                kind: None,
+                link: None,
+            }],
+        );
+        let links = links(&[(*ENX, link(7, LinkState::Up))]);
+
+        assert_eq!(policy.owner(&links, &ENX), *ENX);
+        assert_eq!(policy.owner(&links, &DONGLE), *ENX);
+        // Holding it already comes first.
+        assert_eq!(policy.owner(&links, &WIFI), *WIFI);
+    }
+
+    #[test]
+    fn test_policy_roams() {
+        let policy = policy();
+
+        assert!(policy.roams(&ENX, NicType::Ether));
+        assert!(policy.roams(&DOCK, NicType::Ether));
+        assert!(!policy.roams(&WIFI, NicType::Ether));
+        assert!(!policy.roams(&"eth0".try_into().unwrap(), NicType::Ether));
+        assert!(!policy.roams(&DONGLE, NicType::Other(65534)));
+    }
+
+    #[test]
+    fn test_policy_owner_kind_of_type() {
+        let tun = Link {
+            nic_type: NicType::Other(65534),
+            ..link(8, LinkState::Up)
+        };
+        let links = links(&[(*DONGLE, tun)]);
+
+        // Neither wired nor Wi-Fi, only rules without a type take it.
+        assert_eq!(policy().owner(&links, &WIFI), *WIFI);
+
+        let any = Policy::new(
+            *WIFI,
+            vec![Rule {
+                patterns: vec!["en8".into()],
+                kind: None,
+                link: None,
+            }],
+        );
+
+        assert_eq!(any.owner(&links, &WIFI), *DONGLE);
+    }
+
+    #[test]
+    fn test_policy_plan() {
+        let links = links(&[
+            (*ENX, link(7, LinkState::Up)),
+            (*DONGLE, link(8, LinkState::Up)),
+        ]);
+
+        let expected = HashMap::from([
+            (*WIFI, Target::Released),
+            (*ENX, Target::Shared),
+            (*DONGLE, Target::Released),
+        ]);
+
+        assert_eq!(policy().plan(&links, &ENX), expected);
+    }
+
+    #[test]
+    fn test_diff_releases_first() {
+        let plan = HashMap::from([
+            (*WIFI, Target::Released),
+            (*DOCK, Target::Shared),
+            (*ENX, Target::Released),
+            (*DONGLE, Target::Released),
+        ]);
+        let current = HashMap::from([(*WIFI, *ALT_LLADDR), (*DOCK, *ENX_LLADDR), (*ENX, *LLADDR)]);
+
+        assert_eq!(
+            diff(&plan, &current, &LLADDR),
+            vec![(*ENX, Target::Released), (*DOCK, Target::Shared)]
+        );
+    }
+
+    #[test]
+    fn test_diff_nothing_to_do() {
+        let plan = HashMap::from([(*WIFI, Target::Released), (*ENX, Target::Shared)]);
+        let current = HashMap::from([(*ENX, *LLADDR)]);
+
+        assert_eq!(diff(&plan, &current, &LLADDR), vec![]);
+    }
+
+    #[test]
+    fn test_diff_unknown_address_gets_shared() {
+        let plan = HashMap::from([(*WIFI, Target::Shared)]);
+
+        assert_eq!(
+            diff(&plan, &HashMap::new(), &LLADDR),
+            vec![(*WIFI, Target::Shared)]
+        );
+    }
+
+    /// Every combination of Wi-Fi, the dock, `en7` and `en8` being absent,
+    /// down or up, with any of them holding the shared address.
+    #[test]
+    fn test_policy_exhaustive() {
+        let policy = policy();
+        let ifnames = [*WIFI, *DOCK, *ENX, *DONGLE];
+        let states = [None, Some(LinkState::Down), Some(LinkState::Up)];
+        let lladdr: LLAddr = *LLADDR;
+
+        for combination in 0..states.len().pow(ifnames.len() as u32) {
+            let mut links = HashMap::new();
+            let mut rest = combination;
+            for (index, ifname) in ifnames.iter().enumerate() {
+                if let Some(state) = states[rest % states.len()] {
+                    links.insert(*ifname, link(index as c_int + 1, state));
+                }
+                rest /= states.len();
+            }
+
+            for holder in ifnames {
+                let owner = policy.owner(&links, &holder);
+                let plan = policy.plan(&links, &owner);
+
+                assert!(owner == *WIFI || links.contains_key(&owner));
+                assert_eq!(plan.values().filter(|t| **t == Target::Shared).count(), 1);
+                assert_eq!(policy.owner(&links, &owner), owner);
+
+                let present = |ifname: &IfName| links.contains_key(ifname);
+                let up = |ifname: &IfName| {
+                    links
+                        .get(ifname)
+                        .is_some_and(|link| link.state == LinkState::Up)
+                };
+                let wifi_up = links
+                    .get(&WIFI)
+                    .is_none_or(|link| link.state == LinkState::Up);
+                // The holder first among `en7` and `en8`, then the lowest index.
+                let wired = |eligible: &dyn Fn(&IfName) -> bool| {
+                    [holder, *ENX, *DONGLE]
+                        .into_iter()
+                        .find(|ifname| (*ifname == *ENX || *ifname == *DONGLE) && eligible(ifname))
+                };
+
+                let expected = match present(&DOCK) {
+                    true => *DOCK,
+                    false => wired(&up)
+                        .or_else(|| (!wifi_up).then(|| wired(&present)).flatten())
+                        .unwrap_or(*WIFI),
+                };
+                assert_eq!(owner, expected, "{links:?} held by {holder}");
+
+                let current: HashMap<_, _> = [(holder, lladdr)].into();
+                let changes = diff(&plan, &current, &lladdr);
+                match owner == holder {
+                    true => assert_eq!(changes, vec![]),
+                    false => assert_eq!(
+                        changes,
+                        vec![(holder, Target::Released), (owner, Target::Shared)]
+                            .into_iter()
+                            .filter(|(ifname, _)| plan.contains_key(ifname))
+                            .collect::<Vec<_>>()
+                    ),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt::{Debug, Display};
+
+use net_sys::backend::NicBackend;
+use net_sys::ifname::IfName;
+use net_sys::lladdr::LLAddr;
+use net_sys::retry::Retry;
+
+use crate::state::State;
+use crate::Result;
+
+#[derive(Clone, PartialEq, Eq)]
+enum Error {
+    NoOriginal(IfName),
+    Incomplete(Vec<IfName>),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::NoOriginal(ifname) => f
+                .debug_struct("Restore::NoOriginalError")
+                .field("ifname", ifname)
+                .finish(),
+            Error::Incomplete(failed) => f
+                .debug_struct("Restore::IncompleteError")
+                .field("failed", failed)
+                .finish(),
+        }
+    }
+}
+
+/// Puts back the address `ifname` had before nicr first changed it, or else
+/// its permanent address where the system knows it, then forgets it. The
+/// address is read back and set again as `retry` allows.
+pub(crate) fn restore(
+    backend: &impl NicBackend,
+    state: &mut State,
+    ifname: &IfName,
+    retry: &Retry,
+) -> Result<LLAddr> {
+    let lladdr = match state.original(ifname) {
+        Some(lladdr) => lladdr,
+        None => backend
+            .get_perm_lladdr(ifname)?
+            .ok_or(Error::NoOriginal(*ifname))?,
+    };
+
+    backend.set_lladdr_verified(ifname, &lladdr, retry)?;
+    state.forget(ifname)?;
+
+    log::info!("restore: {ifname} -> {lladdr}");
+
+    Ok(lladdr)
+}
+
+/// Restores every interface of `state`, going on past failures.
+pub(crate) fn restore_all(
+    backend: &impl NicBackend,
+    state: &mut State,
+    retry: &Retry,
+) -> Result<()> {
+    let mut failed = Vec::new();
+    for ifname in state.interfaces()? {
+        if let Err(err) = restore(backend, state, &ifname, retry) {
+            log::error!("{err}");
+            failed.push(ifname);
+        }
+    }
+
+    match failed.is_empty() {
+        true => Ok(()),
+        false => Err(Error::Incomplete(failed).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use net_sys::error::Operation;
+    use net_sys::retry::Retry;
+    use net_sys::testing::{Call, FakeKernel};
+
+    use crate::handover::tests::{kernel, EBUSY, ENX, ENX_LLADDR, LLADDR, WIFI};
+    use crate::state::tests::state_path;
+    use crate::state::State;
+    use crate::Result;
+
+    use super::{restore, restore_all};
+
+    #[test]
+    fn test_restore() -> Result<()> {
+        let path = state_path("restore");
+        let mut state = State::load(&path)?;
+        state.remember(&ENX, &ENX_LLADDR)?;
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+
+        assert_eq!(
+            restore(&kernel, &mut state, &ENX, &Retry::NONE)?,
+            *ENX_LLADDR
+        );
+
+        assert_eq!(kernel.lladdr(&ENX), Some(*ENX_LLADDR));
+        assert_eq!(State::load(&path)?.original(&ENX), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_set_error_keeps_original() -> Result<()> {
+        let path = state_path("restore_set_error");
+        let mut state = State::load(&path)?;
+        state.remember(&ENX, &ENX_LLADDR)?;
+        let kernel = kernel();
+        kernel.fail(Operation::SetLinkLevelAddress, &ENX, EBUSY);
+
+        let expected_error = "Nic::Error { operation: set_lladdr, ifname: \"en7\", lladdr: \"00:e0:4c:68:00:01\", errno: 16, kind: Busy }";
+
+        let error = restore(&kernel, &mut state, &ENX, &Retry::NONE).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(State::load(&path)?.original(&ENX), Some(*ENX_LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_retries() -> Result<()> {
+        let path = state_path("restore_retries");
+        let mut state = State::load(&path)?;
+        state.remember(&ENX, &ENX_LLADDR)?;
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+        kernel.ignore_next_set(&ENX);
+
+        let retry = Retry {
+            attempts: 2,
+            backoff: Duration::ZERO,
+        };
+
+        assert_eq!(restore(&kernel, &mut state, &ENX, &retry)?, *ENX_LLADDR);
+
+        assert_eq!(kernel.lladdr(&ENX), Some(*ENX_LLADDR));
+        assert_eq!(State::load(&path)?.original(&ENX), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_permanent() -> Result<()> {
+        let mut state = State::load(&state_path("restore_permanent"))?;
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &ENX_LLADDR);
+        kernel.plug(&WIFI, &LLADDR);
+
+        assert_eq!(
+            restore(&kernel, &mut state, &ENX, &Retry::NONE)?,
+            *ENX_LLADDR
+        );
+
+        let expected_calls = vec![
+            Call::GetPermLLAddr(*ENX),
+            Call::SetLLAddr(*ENX, *ENX_LLADDR),
+            Call::GetLLAddr(*ENX),
+        ];
+
+        assert_eq!(kernel.calls(), expected_calls);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_no_original() -> Result<()> {
+        let mut state = State::load(&state_path("restore_no_original"))?;
+        let kernel = FakeKernel::new();
+        kernel.plug_virtual(&ENX, &LLADDR);
+
+        let expected_error = "Restore::NoOriginalError { ifname: \"en7\" }";
+
+        let error = restore(&kernel, &mut state, &ENX, &Retry::NONE).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(kernel.lladdr(&ENX), Some(*LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_restore_all() -> Result<()> {
+        let path = state_path("restore_all");
+        let mut state = State::load(&path)?;
+        state.remember(&ENX, &ENX_LLADDR)?;
+        state.remember(&WIFI, &LLADDR)?;
+        let kernel = FakeKernel::new();
+        kernel.plug(&ENX, &LLADDR);
+        kernel.plug(&WIFI, &ENX_LLADDR);
+        kernel.fail(Operation::SetLinkLevelAddress, &WIFI, EBUSY);
+
+        let expected_error = "Restore::IncompleteError { failed: [\"en0\"] }";
+
+        let error = restore_all(&kernel, &mut state, &Retry::NONE).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(kernel.lladdr(&ENX), Some(*ENX_LLADDR));
+        assert_eq!(State::load(&path)?.interfaces()?, vec![*WIFI]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,1065 @@
+use std::collections::HashMap;
+use std::ffi::c_int;
+use std::fmt::{Debug, Display};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+use net_sys::debounce::debounce;
+use net_sys::ifname::IfName;
+use net_sys::lladdr::LLAddr;
+use net_sys::nic::NicEvent::{self, NicDel, NicDown, NicNew, NicUp};
+use net_sys::nic::NicType;
+use net_sys::ErrorKind;
+
+use crate::handover::Handover;
+use crate::hooks::{Hook, HookEnv};
+use crate::notify::Notify;
+use crate::policy::{self, Link, LinkState, Policy, Rule, Target};
+use crate::Result;
+
+/// Monitor errors in a row, without an event in between, after which the
+/// monitor is given up on.
+const MONITOR_ERRORS: u32 = 5;
+
+#[derive(Clone, PartialEq, Eq)]
+enum Error {
+    Paused,
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Paused => f.debug_struct("Roam::PausedError").finish(),
+        }
+    }
+}
+
+/// Keeps a single link-level address on whichever interface `rules` rank
+/// first among those present, e.g. a hot-plugged Ethernet interface while it
+/// is present, and the Wi-Fi interface once it goes away. After every event,
+/// only the changes between the addresses known and the plan of the
+/// `Policy` are made.
+///
+/// While paused, events only keep track of the interfaces present, and
+/// resuming catches up with them.
+#[derive(Debug)]
+pub(crate) struct Roam {
+    handover: Handover,
+    policy: Policy,
+    active: Option<IfName>,
+    known: HashMap<IfName, Link>,
+    paused: bool,
+    debounce: Option<Duration>,
+    notify: Notify,
+}
+
+impl Roam {
+    pub(crate) fn new(handover: Handover, rules: Vec<Rule>) -> Self {
+        Self {
+            policy: Policy::new(*handover.wifi(), rules),
+            handover,
+            active: None,
+            known: HashMap::new(),
+            paused: false,
+            debounce: None,
+            notify: Notify::default(),
+        }
+    }
+
+    /// Only handles the final state of an interface once its events stopped
+    /// for `quiet`.
+    pub(crate) fn with_debounce(mut self, quiet: Duration) -> Self {
+        self.debounce = Some(quiet);
+        self
+    }
+
+    /// Tells systemd once the monitor is open, and the state after every
+    /// change.
+    pub(crate) fn with_notify(mut self, notify: Notify) -> Self {
+        self.notify = notify;
+        self
+    }
+
+    /// Handles events until the monitor ends, locking `roam` for each one so
+    /// that the control socket can be served meanwhile. The interfaces
+    /// present are taken from a `rescan` first, and again after a monitor
+    /// error, since events may have been lost, e.g. with ENOBUFS. A failed
+    /// event is reported and skipped, only a denied permission, which every
+    /// later change would run into too, a monitor that cannot work, or one
+    /// failing `MONITOR_ERRORS` times in a row, stops the loop.
+    pub(crate) fn run(roam: &Mutex<Roam>) -> Result<()> {
+        let events: Box<dyn Iterator<Item = net_sys::Result<NicEvent>>> = {
+            let mut roam = roam.lock().unwrap_or_else(PoisonError::into_inner);
+            // Opened first, so that no change after the listing goes unseen.
+            let monitor = roam.handover.backend().monitor()?;
+            Self::check(roam.rescan())?;
+            roam.notify.ready(&roam.status());
+            match roam.debounce {
+                Some(quiet) => Box::new(debounce(monitor, quiet)),
+                None => Box::new(monitor),
+            }
+        };
+
+        let mut errors = 0;
+        for event in events {
+            let mut roam = roam.lock().unwrap_or_else(PoisonError::into_inner);
+            let result = match event {
+                Ok(event) => {
+                    errors = 0;
+                    roam.handle(&event)
+                }
+                Err(err) => match err.kind() {
+                    ErrorKind::Decode => {
+                        log::warn!("{err}");
+                        continue;
+                    }
+                    ErrorKind::PermissionDenied | ErrorKind::Unsupported => return Err(err.into()),
+                    _ if errors + 1 == MONITOR_ERRORS => return Err(err.into()),
+                    _ => {
+                        log::error!("{err}");
+                        errors += 1;
+                        roam.rescan()
+                    }
+                },
+            };
+            Self::check(result)?;
+            roam.report();
+        }
+
+        Ok(())
+    }
+
+    /// Logs the error of a failed step, passing on a denied permission only.
+    fn check(result: Result<()>) -> Result<()> {
+        let Err(err) = result else {
+            return Ok(());
+        };
+        match Self::kind(err.as_ref()) {
+            Some(ErrorKind::PermissionDenied) => return Err(err),
+            // Gone meanwhile, its own event follows.
+            Some(ErrorKind::NoSuchInterface) => log::warn!("{err}"),
+            _ => log::error!("{err}"),
+        }
+        Ok(())
+    }
+
+    /// Takes the Ethernet interfaces present from the backend, with the link
+    /// state of their flags, in place of those known from events, then hands
+    /// over as they require. A wired interface holding the shared address,
+    /// e.g. since an earlier run stopped, is taken as holding it for good
+    /// unless one is already.
+    fn rescan(&mut self) -> Result<()> {
+        let wifi = *self.policy.wifi();
+        let lladdr = *self.handover.lladdr();
+
+        let mut known = HashMap::new();
+        for nic in self.handover.backend().list()? {
+            let Some(nic_lladdr) = nic.lladdr.filter(|_| nic.nic_type == NicType::Ether) else {
+                continue;
+            };
+            let link = Link {
+                index: nic.index,
+                lladdr: nic_lladdr,
+                nic_type: nic.nic_type,
+                state: LinkState::of_flags(nic.flags),
+            };
+            known.insert(nic.ifname, link);
+        }
+        self.known = known;
+
+        if self.active.is_none() {
+            self.active = self
+                .known
+                .iter()
+                .find(|(ifname, link)| **ifname != wifi && link.lladdr == lladdr)
+                .map(|(ifname, _)| *ifname);
+        }
+
+        match self.paused {
+            true => Ok(()),
+            false => self.reconcile(false),
+        }
+    }
+
+    fn kind(err: &(dyn std::error::Error + 'static)) -> Option<ErrorKind> {
+        err.downcast_ref().map(net_sys::Error::kind)
+    }
+
+    /// Pings the watchdog, which only happens while no event is stuck
+    /// holding `roam`.
+    pub(crate) fn watchdog(roam: &Mutex<Roam>) {
+        roam.lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .notify
+            .watchdog();
+    }
+
+    /// Where the shared address is, e.g. `02:11:22:33:44:55 on en7`.
+    pub(crate) fn status(&self) -> String {
+        let holder = self.active.unwrap_or(*self.handover.wifi());
+        let mut status = format!("{} on {holder}", self.handover.lladdr());
+        if self.paused {
+            status.push_str(", paused");
+        }
+        if self.handover.is_dry_run() {
+            status.push_str(", dry run");
+        }
+        status
+    }
+
+    fn report(&self) {
+        self.notify.status(&self.status());
+    }
+
+    pub(crate) fn handover(&self) -> &Handover {
+        &self.handover
+    }
+
+    /// Wired interface holding the shared address.
+    pub(crate) fn active(&self) -> Option<&IfName> {
+        self.active.as_ref()
+    }
+
+    /// Interfaces present with their index and address, as last reported or
+    /// set.
+    pub(crate) fn known(&self) -> &HashMap<IfName, Link> {
+        &self.known
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub(crate) fn pause(&mut self) {
+        self.paused = true;
+        self.report();
+    }
+
+    /// Hands over as the events seen while paused require.
+    pub(crate) fn resume(&mut self) -> Result<()> {
+        self.paused = false;
+        let result = self.reconcile(false);
+        self.report();
+        result
+    }
+
+    /// Applies the shared address again where it belongs, even if it should
+    /// already be there.
+    pub(crate) fn reapply(&mut self) -> Result<()> {
+        match self.paused {
+            true => Err(Error::Paused.into()),
+            false => {
+                let result = self.reconcile(true);
+                self.report();
+                result
+            }
+        }
+    }
+
+    fn reconcile(&mut self, force: bool) -> Result<()> {
+        // Events of the Wi-Fi interface are not acted upon, it is taken as
+        // holding the shared address unless a wired interface took it.
+        let wifi_holds = self.active.is_none();
+        if let Some(active) = self
+            .active
+            .filter(|active| !self.known.contains_key(active))
+        {
+            log::info!("roam: {active} went away");
+            self.active = None;
+        }
+
+        let wifi = *self.policy.wifi();
+        let lladdr = *self.handover.lladdr();
+        let owner = self.policy.owner(&self.known, &self.active.unwrap_or(wifi));
+        let plan = self.policy.plan(&self.known, &owner);
+
+        let mut current: HashMap<_, _> = self
+            .known
+            .iter()
+            .filter(|(ifname, _)| **ifname != wifi)
+            .map(|(ifname, link)| (*ifname, link.lladdr))
+            .collect();
+        if wifi_holds {
+            current.insert(wifi, lladdr);
+        }
+        let mut changes = policy::diff(&plan, &current, &lladdr);
+        if force && !changes.contains(&(owner, Target::Shared)) {
+            changes.push((owner, Target::Shared));
+        }
+
+        for (ifname, target) in changes {
+            match target {
+                // Wi-Fi moves to the alternate address with the handover.
+                Target::Released if ifname == wifi => (),
+                Target::Released => self.handover.release(self.known[&ifname].index, &ifname)?,
+                Target::Shared if ifname == wifi => self.handover.switch_to_wifi()?,
+                Target::Shared => {
+                    let link = self.known[&ifname];
+                    self.handover
+                        .switch_to_wired(link.index, &ifname, &link.lladdr)?;
+                }
+            }
+            // Read back already, so it stands before the kernel reports it.
+            if let (Some(link), Some(lladdr)) = (
+                self.known.get_mut(&ifname),
+                self.handover.assignments().get(&ifname),
+            ) {
+                link.lladdr = *lladdr;
+            }
+        }
+
+        self.active = (owner != wifi).then_some(owner);
+
+        Ok(())
+    }
+
+    pub(crate) fn handle(&mut self, event: &NicEvent) -> Result<()> {
+        match event {
+            NicNew((index, ifname, lladdr)) => {
+                // Any change of a link is reported as NicNew on Linux, the
+                // address set by a handover included, so its state is kept.
+                let link = Link {
+                    index: *index,
+                    lladdr: *lladdr,
+                    // The monitor only reports Ethernet links.
+                    nic_type: NicType::Ether,
+                    state: self
+                        .known
+                        .get(ifname)
+                        .map_or(LinkState::Unknown, |link| link.state),
+                };
+                let new = self.known.insert(*ifname, link).is_none();
+                if new && self.policy.roams(ifname, NicType::Ether) {
+                    self.hook(Hook::New, *index, ifname, lladdr);
+                }
+            }
+            NicDel((index, ifname, lladdr)) => {
+                let removed = self.known.remove(ifname);
+                if removed.is_some_and(|link| self.policy.roams(ifname, link.nic_type)) {
+                    self.hook(Hook::Del, *index, ifname, lladdr);
+                }
+            }
+            NicUp((_, ifname)) | NicDown((_, ifname)) => {
+                let state = match event {
+                    NicUp(_) => LinkState::Up,
+                    _ => LinkState::Down,
+                };
+                // Unknown until its NicNew, which the link state follows.
+                match self.known.get_mut(ifname) {
+                    Some(link) if link.state != state => {
+                        log::info!("roam: {ifname} link {state}");
+                        link.state = state;
+                    }
+                    _ => return Ok(()),
+                }
+            }
+            // NicNoop, or an event nicr does not know of.
+            _ => return Ok(()),
+        }
+
+        match self.paused {
+            true => Ok(()),
+            false => self.reconcile(false),
+        }
+    }
+
+    fn hook(&self, hook: Hook, index: c_int, ifname: &IfName, lladdr: &LLAddr) {
+        let env = HookEnv {
+            ifname: *ifname,
+            index: Some(index),
+            lladdr: *lladdr,
+            old_lladdr: None,
+        };
+        self.handover.hook(hook, &env);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::c_int;
+    use std::fs;
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::Duration;
+
+    use net_sys::error::{ErrorKind, Operation};
+    use net_sys::ifname::IfName;
+    use net_sys::lladdr::LLAddr;
+    use net_sys::nic::NicEvent::{NicDel, NicDown, NicNew, NicUp};
+    use net_sys::nic::NicType;
+    use net_sys::testing::{Call, FakeKernel};
+    use net_sys::Error;
+
+    use crate::handover::tests::{
+        handover, kernel, kind, ALT_LLADDR, EBUSY, ENX, ENX_LLADDR, LLADDR, WIFI,
+    };
+    use crate::hooks::tests::recording_hooks;
+    use crate::notify::tests::{notify_socket, receive};
+    use crate::policy::{Link, LinkState, Rule};
+    use crate::Result;
+
+    use super::{Roam, MONITOR_ERRORS};
+
+    const EPERM: c_int = 1;
+    const EIO: c_int = 5;
+    const ENODEV: c_int = 19;
+    #[cfg(target_os = "linux")]
+    const ENOBUFS: c_int = 105;
+    #[cfg(not(target_os = "linux"))]
+    const ENOBUFS: c_int = 55;
+
+    fn roam(kernel: &FakeKernel) -> Roam {
+        Roam::new(
+            handover(kernel),
+            vec![Rule::wired(vec!["en*".into(), "eth1".into()])],
+        )
+    }
+
+    fn link(index: c_int, lladdr: LLAddr) -> Link {
+        Link {
+            index,
+            lladdr,
+            nic_type: NicType::Ether,
+            state: LinkState::Up,
+        }
+    }
+
+    /// Runs `roam` on another thread until `change`, made once its monitor
+    /// is open, has been read. `roam` is held meanwhile, so that the events
+    /// of its own handovers come after the end of the monitor.
+    fn run(
+        roam: &Mutex<Roam>,
+        kernel: &FakeKernel,
+        change: impl FnOnce(),
+    ) -> std::result::Result<(), String> {
+        thread::scope(|scope| {
+            let run = scope.spawn(|| Roam::run(roam).map_err(|err| err.to_string()));
+            while !kernel.calls().contains(&Call::Monitor) && !run.is_finished() {
+                thread::yield_now();
+            }
+            {
+                let _roam = roam.lock().unwrap();
+                change();
+                kernel.close_monitors();
+            }
+            run.join().unwrap()
+        })
+    }
+
+    /// Whether the shared address is on `wired`, Wi-Fi on the alternate one.
+    fn assert_wired(kernel: &FakeKernel, wired: &IfName) {
+        assert_eq!(kernel.lladdr(&WIFI), Some(*ALT_LLADDR));
+        assert_eq!(kernel.lladdr(wired), Some(*LLADDR));
+    }
+
+    #[test]
+    fn test_roam_nic_new_hands_over() -> Result<()> {
+        let kernel = kernel();
+
+        let mut roam = roam(&kernel);
+        roam.handle(&NicNew((2, *ENX, *ENX_LLADDR)))?;
+
+        assert_eq!(roam.active, Some(*ENX));
+        assert_wired(&kernel, &ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_nic_new_already_set() -> Result<()> {
+        let kernel = kernel();
+
+        roam(&kernel).handle(&NicNew((2, *ENX, *LLADDR)))?;
+
+        assert_eq!(kernel.calls(), vec![]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_nic_new_not_matching() -> Result<()> {
+        let eth: IfName = "eth0".try_into()?;
+        let kernel = kernel();
+
+        roam(&kernel).handle(&NicNew((3, eth, *ENX_LLADDR)))?;
+
+        assert_eq!(kernel.calls(), vec![]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_nic_new_wifi_is_ignored() -> Result<()> {
+        let kernel = kernel();
+
+        roam(&kernel).handle(&NicNew((1, *WIFI, *ENX_LLADDR)))?;
+
+        assert_eq!(kernel.calls(), vec![]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_nic_new_second_wired_is_ignored() -> Result<()> {
+        let en8: IfName = "en8".try_into()?;
+        let kernel = kernel();
+
+        let mut roam = roam(&kernel);
+        roam.handle(&NicNew((2, *ENX, *LLADDR)))?;
+        roam.handle(&NicNew((3, en8, *ENX_LLADDR)))?;
+
+        assert_eq!(roam.active, Some(*ENX));
+        assert_eq!(kernel.calls(), vec![]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_link_down_and_up() -> Result<()> {
+        let kernel = kernel();
+        let wired_up = Rule {
+            link: Some(LinkState::Up),
+            ..Rule::wired(vec!["en*".into()])
+        };
+        let mut roam = Roam::new(handover(&kernel), vec![wired_up]);
+        roam.handle(&NicNew((2, *ENX, *ENX_LLADDR)))?;
+        roam.handle(&NicUp((2, *ENX)))?;
+
+        assert_wired(&kernel, &ENX);
+
+        // Unplugged, en7 stays present but gives the shared address back.
+        roam.handle(&NicDown((2, *ENX)))?;
+
+        assert_eq!(roam.active, None);
+        assert_eq!(roam.known()[&ENX].state, LinkState::Down);
+        assert_eq!(kernel.lladdr(&WIFI), Some(*LLADDR));
+        assert_eq!(kernel.lladdr(&ENX), Some(*ENX_LLADDR));
+
+        roam.handle(&NicUp((2, *ENX)))?;
+
+        assert_eq!(roam.active, Some(*ENX));
+        assert_wired(&kernel, &ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_link_unknown_until_reported() -> Result<()> {
+        let kernel = kernel();
+        let wired_up = Rule {
+            link: Some(LinkState::Up),
+            ..Rule::wired(vec!["en*".into()])
+        };
+        let mut roam = Roam::new(handover(&kernel), vec![wired_up]);
+        roam.handle(&NicNew((2, *ENX, *ENX_LLADDR)))?;
+
+        // Not taken as up before its link is reported.
+        assert_eq!(roam.known()[&ENX].state, LinkState::Unknown);
+        assert_eq!(roam.active, None);
+        assert_eq!(kernel.lladdr(&WIFI), Some(*LLADDR));
+
+        roam.handle(&NicUp((2, *ENX)))?;
+
+        assert_eq!(roam.active, Some(*ENX));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_rescan_link_of_flags() -> Result<()> {
+        let kernel = kernel();
+        kernel.set_link(&ENX, false);
+        let wired_up = Rule {
+            link: Some(LinkState::Up),
+            ..Rule::wired(vec!["en*".into()])
+        };
+        let mut roam = Roam::new(handover(&kernel), vec![wired_up]);
+
+        roam.rescan()?;
+
+        assert_eq!(roam.known()[&ENX].state, LinkState::Down);
+        assert_eq!(roam.known()[&WIFI].state, LinkState::Up);
+        assert_eq!(roam.active, None);
+
+        kernel.set_link(&ENX, true);
+        roam.rescan()?;
+
+        assert_eq!(roam.active, Some(*ENX));
+        assert_wired(&kernel, &ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_link_of_unknown_interface_is_ignored() -> Result<()> {
+        let mut roam = roam(&kernel());
+        roam.handle(&NicDown((2, *ENX)))?;
+
+        assert!(roam.known().is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_policy_preempts() -> Result<()> {
+        let dock: IfName = "enx001122".try_into()?;
+        let kernel = kernel();
+        let dock_first = Rule {
+            patterns: vec!["enx*".into()],
+            kind: None,
+            link: None,
+        };
+        let mut roam = Roam::new(
+            handover(&kernel),
+            vec![dock_first, Rule::wired(vec!["en*".into()])],
+        );
+        roam.handle(&NicNew((2, *ENX, *ENX_LLADDR)))?;
+        roam.handle(&NicNew((2, *ENX, *LLADDR)))?;
+
+        // en7 gets its own address back before the dock takes the shared one.
+        let index = kernel.plug(&dock, &ENX_LLADDR);
+        roam.handle(&NicNew((index, dock, *ENX_LLADDR)))?;
+        roam.handle(&NicNew((2, *ENX, *ENX_LLADDR)))?;
+
+        assert_eq!(roam.active, Some(dock));
+        assert_eq!(kernel.lladdr(&ENX), Some(*ENX_LLADDR));
+        assert_wired(&kernel, &dock);
+
+        kernel.unplug(&dock);
+        roam.handle(&NicDel((index, dock, *LLADDR)))?;
+
+        assert_eq!(roam.active, Some(*ENX));
+        assert_wired(&kernel, &ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_nic_new_error() {
+        let kernel = kernel();
+        kernel.fail(Operation::GetLinkLevelAddress, &WIFI, EBUSY);
+
+        let mut roam = roam(&kernel);
+        let error = roam.handle(&NicNew((2, *ENX, *ENX_LLADDR))).unwrap_err();
+
+        assert_eq!(kind(error.as_ref()), Some(ErrorKind::Busy));
+        assert_eq!(roam.active, None);
+    }
+
+    #[test]
+    fn test_roam_nic_del_hands_back() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &ALT_LLADDR);
+
+        let mut roam = roam(&kernel);
+        roam.active = Some(*ENX);
+        roam.handle(&NicDel((2, *ENX, *LLADDR)))?;
+
+        assert_eq!(roam.active, None);
+        assert_eq!(kernel.lladdr(&WIFI), Some(*LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_nic_del_not_active() -> Result<()> {
+        let en8: IfName = "en8".try_into()?;
+        let kernel = kernel();
+
+        let mut roam = roam(&kernel);
+        roam.handle(&NicNew((2, *ENX, *LLADDR)))?;
+        roam.handle(&NicDel((3, en8, *ENX_LLADDR)))?;
+
+        assert_eq!(roam.active, Some(*ENX));
+        assert_eq!(kernel.calls(), vec![]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_nic_del_error() {
+        let kernel = kernel();
+        kernel.fail(Operation::GetLinkLevelAddress, &WIFI, EBUSY);
+
+        let mut roam = roam(&kernel);
+        roam.active = Some(*ENX);
+        let error = roam.handle(&NicDel((2, *ENX, *LLADDR))).unwrap_err();
+
+        assert_eq!(kind(error.as_ref()), Some(ErrorKind::Busy));
+        assert_eq!(roam.active, None);
+    }
+
+    #[test]
+    fn test_roam_hooks() -> Result<()> {
+        let (hooks, path) = recording_hooks("roam_hooks");
+        let kernel = kernel();
+
+        let mut roam = Roam::new(
+            handover(&kernel).with_hooks(hooks),
+            vec![Rule::wired(vec!["en*".into()])],
+        );
+        roam.handle(&NicNew((2, *ENX, *ENX_LLADDR)))?;
+        roam.handle(&NicNew((2, *ENX, *LLADDR)))?;
+        kernel.unplug(&ENX);
+        roam.handle(&NicDel((2, *ENX, *LLADDR)))?;
+        // Waits for the hooks queued.
+        drop(roam);
+
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            [
+                "new en7 2 00:e0:4c:68:00:01 \n",
+                "applied en0  02:aa:bb:cc:dd:ee 02:11:22:33:44:55\n",
+                "applied en7 2 02:11:22:33:44:55 00:e0:4c:68:00:01\n",
+                "del en7 2 02:11:22:33:44:55 \n",
+                "applied en0  02:11:22:33:44:55 02:aa:bb:cc:dd:ee\n",
+            ]
+            .concat()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_paused_catches_up_on_resume() -> Result<()> {
+        let kernel = kernel();
+        let mut roam = roam(&kernel);
+        roam.pause();
+        roam.handle(&NicNew((2, *ENX, *ENX_LLADDR)))?;
+
+        assert_eq!(roam.active, None);
+        assert_eq!(roam.known()[&ENX].lladdr, *ENX_LLADDR);
+        assert_eq!(kernel.calls(), vec![]);
+
+        roam.resume()?;
+
+        assert!(!roam.is_paused());
+        assert_eq!(roam.active, Some(*ENX));
+        assert_wired(&kernel, &ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_paused_active_gone() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &ALT_LLADDR);
+
+        let mut roam = roam(&kernel);
+        roam.active = Some(*ENX);
+        roam.pause();
+        roam.handle(&NicDel((2, *ENX, *LLADDR)))?;
+
+        assert_eq!(roam.active, Some(*ENX));
+
+        roam.resume()?;
+
+        assert_eq!(roam.active, None);
+        assert_eq!(kernel.lladdr(&WIFI), Some(*LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_reapply() -> Result<()> {
+        let kernel = kernel();
+        let mut roam = roam(&kernel);
+        roam.handle(&NicNew((2, *ENX, *LLADDR)))?;
+        roam.active = Some(*ENX);
+
+        // Taken as holding the shared address already, en7 gets it again.
+        roam.reapply()?;
+
+        assert_eq!(roam.active, Some(*ENX));
+        assert_wired(&kernel, &ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_reapply_wifi() -> Result<()> {
+        let kernel = kernel();
+
+        roam(&kernel).reapply()?;
+
+        assert_eq!(kernel.calls(), vec![Call::GetLLAddr(*WIFI)]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_reapply_paused() {
+        let expected_error = "Roam::PausedError";
+
+        let mut roam = roam(&kernel());
+        roam.pause();
+        let error = roam.reapply().unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+
+    #[test]
+    fn test_roam_run() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &LLADDR);
+        kernel.fail(Operation::GetLinkLevelAddress, &WIFI, EBUSY);
+
+        let roam = Mutex::new(roam(&kernel));
+        run(&roam, &kernel, || {
+            // The first handover fails, the event is skipped.
+            kernel.plug(&ENX, &ENX_LLADDR);
+            kernel.unplug(&ENX);
+            kernel.plug(&ENX, &ENX_LLADDR);
+        })?;
+
+        assert_eq!(roam.lock().unwrap().active, Some(*ENX));
+        assert_wired(&kernel, &ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_run_notifies() -> Result<()> {
+        let (socket, notify) = notify_socket("roam_run")?;
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &LLADDR);
+
+        let roam = Mutex::new(roam(&kernel).with_notify(notify));
+        run(&roam, &kernel, || {
+            kernel.plug(&ENX, &ENX_LLADDR);
+        })?;
+        roam.lock().unwrap().pause();
+        Roam::watchdog(&roam);
+
+        assert_eq!(
+            receive(&socket)?,
+            "READY=1\nSTATUS=02:11:22:33:44:55 on en0"
+        );
+        assert_eq!(receive(&socket)?, "STATUS=02:11:22:33:44:55 on en7");
+        assert_eq!(receive(&socket)?, "STATUS=02:11:22:33:44:55 on en7, paused");
+        assert_eq!(receive(&socket)?, "WATCHDOG=1");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_run_open_error_not_ready() -> Result<()> {
+        let (socket, notify) = notify_socket("roam_open_error")?;
+        socket.set_nonblocking(true)?;
+        let kernel = kernel();
+        kernel.fail_monitor(EPERM);
+
+        assert!(Roam::run(&Mutex::new(roam(&kernel).with_notify(notify))).is_err());
+        assert!(receive(&socket).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_run_debounced() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &LLADDR);
+
+        let roam = Mutex::new(roam(&kernel).with_debounce(Duration::from_secs(1)));
+        run(&roam, &kernel, || {
+            kernel.plug(&ENX, &ENX_LLADDR);
+            kernel.unplug(&ENX);
+            kernel.plug(&ENX, &ENX_LLADDR);
+        })?;
+
+        let sets = kernel
+            .calls()
+            .into_iter()
+            .filter(|call| matches!(call, Call::SetLLAddr(..)))
+            .count();
+
+        // A single handover for the final state.
+        assert_eq!(sets, 2);
+        let expected = Link {
+            state: LinkState::Unknown,
+            ..link(3, *LLADDR)
+        };
+
+        assert_eq!(roam.lock().unwrap().known()[&ENX], expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_run_permission_error() {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &LLADDR);
+        kernel.fail(Operation::GetLinkLevelAddress, &WIFI, EPERM);
+
+        let expected_error = "Nic::Error { operation: get_lladdr, ifname: \"en0\", errno: 1, kind: PermissionDenied }";
+
+        let roam = Mutex::new(roam(&kernel));
+        let error = run(&roam, &kernel, || {
+            kernel.plug(&ENX, &ENX_LLADDR);
+            kernel.unplug(&ENX);
+        })
+        .unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_roam_run_skips_missing_interface() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &LLADDR);
+        kernel.fail(Operation::GetLinkLevelAddress, &WIFI, ENODEV);
+
+        let roam = Mutex::new(roam(&kernel));
+        run(&roam, &kernel, || {
+            kernel.plug(&ENX, &ENX_LLADDR);
+            kernel.unplug(&ENX);
+        })?;
+
+        assert_eq!(roam.lock().unwrap().active, None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_run_rescans_at_start() -> Result<()> {
+        let (socket, notify) = notify_socket("roam_rescan")?;
+        let kernel = kernel();
+
+        let roam = Mutex::new(roam(&kernel).with_notify(notify));
+        run(&roam, &kernel, || ())?;
+
+        // Handed over before ready.
+        assert_eq!(
+            receive(&socket)?,
+            "READY=1\nSTATUS=02:11:22:33:44:55 on en7"
+        );
+        assert_eq!(roam.lock().unwrap().known()[&ENX], link(2, *LLADDR));
+        assert_wired(&kernel, &ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_run_takes_over_earlier_run() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &ALT_LLADDR);
+        kernel.plug(&ENX, &LLADDR);
+
+        let roam = Mutex::new(roam(&kernel));
+        run(&roam, &kernel, || ())?;
+
+        assert_eq!(roam.lock().unwrap().active, Some(*ENX));
+        assert_eq!(kernel.calls(), vec![Call::Monitor, Call::List]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_run_releases_earlier_run() -> Result<()> {
+        let eth: IfName = "eth0".try_into()?;
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &ALT_LLADDR);
+        kernel.plug(&eth, &LLADDR);
+        // Not roaming any more, eth0 gives the shared address back.
+        let roam = Mutex::new(Roam::new(
+            handover(&kernel),
+            vec![Rule::wired(vec!["en*".into()])],
+        ));
+
+        run(&roam, &kernel, || ())?;
+
+        assert_eq!(roam.lock().unwrap().active, None);
+        assert_ne!(kernel.lladdr(&eth), Some(*LLADDR));
+        assert_eq!(kernel.lladdr(&WIFI), Some(*LLADDR));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_run_rescans_after_monitor_error() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &LLADDR);
+
+        let roam = Mutex::new(roam(&kernel));
+        run(&roam, &kernel, || {
+            kernel.report_error(Error::from_errno(Operation::Read, ENOBUFS));
+        })?;
+
+        let lists = kernel
+            .calls()
+            .into_iter()
+            .filter(|call| *call == Call::List)
+            .count();
+
+        assert_eq!(lists, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_run_monitor_permission_error() {
+        let kernel = kernel();
+
+        let expected_error = "Nic::Error { operation: read, errno: 1, kind: PermissionDenied }";
+
+        let error = run(&Mutex::new(roam(&kernel)), &kernel, || {
+            kernel.report_error(Error::from_errno(Operation::Read, EPERM));
+        })
+        .unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_roam_run_monitor_errors_in_a_row() {
+        let kernel = kernel();
+
+        let expected_error = "Nic::Error { operation: read, errno: 5, kind: Other }";
+
+        let error = run(&Mutex::new(roam(&kernel)), &kernel, || {
+            for _ in 0..MONITOR_ERRORS {
+                kernel.report_error(Error::from_errno(Operation::Read, EIO));
+            }
+        })
+        .unwrap_err();
+
+        assert_eq!(error, expected_error);
+    }
+
+    #[test]
+    fn test_roam_run_skips_decode_error() -> Result<()> {
+        let kernel = FakeKernel::new();
+        kernel.plug(&WIFI, &LLADDR);
+
+        let roam = Mutex::new(roam(&kernel));
+        run(&roam, &kernel, || {
+            kernel.report_error(Error::new(Operation::Read, ErrorKind::Decode));
+            kernel.plug(&ENX, &ENX_LLADDR);
+        })?;
+
+        assert_wired(&kernel, &ENX);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_roam_run_open_error() {
+        let kernel = kernel();
+        kernel.fail_monitor(EPERM);
+
+        let expected_error =
+            "Nic::Error { operation: open_socket, errno: 1, kind: PermissionDenied }";
+
+        let error = Roam::run(&Mutex::new(roam(&kernel))).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display};
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use net_sys::ifname::IfName;
+use net_sys::lladdr::LLAddr;
+
+use crate::Result;
+
+pub(crate) const STATE_FILE: &str = "/var/lib/nicr/state.toml";
+
+#[derive(Clone, PartialEq, Eq)]
+enum Error {
+    Read(PathBuf, String),
+    Parse(PathBuf, String),
+    Write(PathBuf, String),
+}
+
+impl std::error::Error for Error {}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+impl Debug for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Read(path, error) => f
+                .debug_struct("State::ReadError")
+                .field("path", path)
+                .field("error", error)
+                .finish(),
+            Error::Parse(path, error) => f
+                .debug_struct("State::ParseError")
+                .field("path", path)
+                .field("error", error)
+                .finish(),
+            Error::Write(path, error) => f
+                .debug_struct("State::WriteError")
+                .field("path", path)
+                .field("error", error)
+                .finish(),
+        }
+    }
+}
+
+/// Original link-level address of every interface nicr changed, kept until
+/// `nicr restore` puts it back, e.g.
+///
+/// ```toml
+/// [original]
+/// en0 = "f0:18:98:01:02:03"
+/// en7 = "00:e0:4c:68:00:01"
+/// ```
+#[derive(Debug)]
+pub(crate) struct State {
+    path: PathBuf,
+    original: BTreeMap<String, LLAddr>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+struct StateFile {
+    #[serde(default)]
+    original: BTreeMap<String, LLAddr>,
+}
+
+impl State {
+    /// Reads the state file at `path`, empty if it does not exist yet.
+    pub(crate) fn load(path: &Path) -> Result<State> {
+        let file = match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content)
+                .map_err(|error| Error::Parse(path.into(), error.message().to_string()))?,
+            Err(error) if error.kind() == ErrorKind::NotFound => StateFile::default(),
+            Err(error) => return Err(Error::Read(path.into(), error.to_string()).into()),
+        };
+
+        Ok(State {
+            path: path.into(),
+            original: file.original,
+        })
+    }
+
+    pub(crate) fn original(&self, ifname: &IfName) -> Option<LLAddr> {
+        self.original.get(&ifname.to_string()).copied()
+    }
+
+    /// Interfaces with a remembered address, in name order.
+    pub(crate) fn interfaces(&self) -> Result<Vec<IfName>> {
+        self.original
+            .keys()
+            .map(|ifname| Ok(IfName::try_from(ifname.as_str())?))
+            .collect()
+    }
+
+    /// Records `lladdr` as the original address of `ifname` unless one is
+    /// already known, and persists it before the caller changes anything.
+    pub(crate) fn remember(&mut self, ifname: &IfName, lladdr: &LLAddr) -> Result<()> {
+        if self.original(ifname).is_some() {
+            return Ok(());
+        }
+
+        self.original.insert(ifname.to_string(), *lladdr);
+        self.save().inspect_err(|_| {
+            self.original.remove(&ifname.to_string());
+        })
+    }
+
+    pub(crate) fn forget(&mut self, ifname: &IfName) -> Result<()> {
+        match self.original.remove(&ifname.to_string()) {
+            Some(_) => self.save(),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes a temporary file next to the state file and renames it over,
+    /// so a crash never leaves a truncated file behind.
+    fn save(&self) -> Result<()> {
+        let error = |error: std::io::Error| Error::Write(self.path.clone(), error.to_string());
+
+        let file = StateFile {
+            original: self.original.clone(),
+        };
+        let content = toml::to_string(&file)
+            .map_err(|err| Error::Write(self.path.clone(), err.to_string()))?;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(error)?;
+        }
+        let tmp = self.path.with_extension("toml.tmp");
+        fs::write(&tmp, content).map_err(error)?;
+        fs::rename(&tmp, &self.path).map_err(error)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::collections::BTreeMap;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use crate::handover::tests::{ENX, ENX_LLADDR, LLADDR, WIFI};
+    use crate::testing::test_path;
+    use crate::Result;
+
+    use super::State;
+
+    /// State file path unique to the calling test, removed beforehand.
+    pub(crate) fn state_path(test: &str) -> PathBuf {
+        test_path(test, "").join("state.toml")
+    }
+
+    #[test]
+    fn test_state_load_missing() -> Result<()> {
+        let state = State::load(&state_path("load_missing"))?;
+
+        assert_eq!(state.original(&WIFI), None);
+        assert!(state.interfaces()?.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_remember() -> Result<()> {
+        let path = state_path("remember");
+
+        let mut state = State::load(&path)?;
+        state.remember(&ENX, &ENX_LLADDR)?;
+        state.remember(&WIFI, &LLADDR)?;
+        // The first address seen is the original one.
+        state.remember(&ENX, &LLADDR)?;
+
+        let state = State::load(&path)?;
+        assert_eq!(state.original(&ENX), Some(*ENX_LLADDR));
+        assert_eq!(state.original(&WIFI), Some(*LLADDR));
+        assert_eq!(state.interfaces()?, vec![*WIFI, *ENX]);
+        assert_eq!(
+            fs::read_to_string(&path)?,
+            "[original]\nen0 = \"02:11:22:33:44:55\"\nen7 = \"00:e0:4c:68:00:01\"\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_forget() -> Result<()> {
+        let path = state_path("forget");
+
+        let mut state = State::load(&path)?;
+        state.remember(&ENX, &ENX_LLADDR)?;
+        state.forget(&ENX)?;
+        state.forget(&WIFI)?;
+
+        assert_eq!(State::load(&path)?.original(&ENX), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_load_parse_error() -> Result<()> {
+        let path = state_path("load_parse_error");
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(&path, "[original]\nen7 = \"00:e0\"\n")?;
+
+        let error = State::load(&path).unwrap_err();
+
+        assert!(format!("{}", error).starts_with("State::ParseError { path: "));
+        assert!(format!("{}", error).contains("WrongNumberOfOctetsError"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_state_remember_write_error() {
+        let mut state = State {
+            path: "/dev/null/state.toml".into(),
+            original: BTreeMap::new(),
+        };
+
+        let expected_error = "State::WriteError { path: \"/dev/null/state.toml\", error: \"File exists (os error 17)\" }";
+
+        let error = state.remember(&ENX, &ENX_LLADDR).unwrap_err();
+
+        assert_eq!(format!("{}", error), expected_error);
+        assert_eq!(state.original(&ENX), None);
+    }
+}
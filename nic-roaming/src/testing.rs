@@ -0,0 +1,13 @@
+use std::fs;
+use std::path::PathBuf;
+
+/// Path in the temporary directory unique to the calling `test` and to this
+/// run, with extension `ext` if not empty. Whatever a previous run left
+/// there is removed.
+pub(crate) fn test_path(test: &str, ext: &str) -> PathBuf {
+    let name = format!("nicr-{}-{test}", std::process::id());
+    let path = std::env::temp_dir().join(name).with_extension(ext);
+    let _ = fs::remove_file(&path);
+    let _ = fs::remove_dir_all(&path);
+    path
+}